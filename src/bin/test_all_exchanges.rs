@@ -1,17 +1,29 @@
 /// Comprehensive test to validate data fetching, validation, and normalization
 /// from both exchanges (Binance, Bybit)
+/// Pass `--mock` to run offline against the bundled mock exchange
 use anyhow::{Context, Result};
 use event_trading::config::exchange_config::{ExchangeConfig, ExchangeType};
-use event_trading::market_data::ExchangeFactory;
+use event_trading::config::EnvConfig;
+use event_trading::market_data::{ExchangeFactory, MockExchange};
 use event_trading::engine::EventBus;
+use event_trading::telemetry::logging;
 use rust_decimal::Decimal;
 use std::str::FromStr;
+use tracing::{error, info, info_span, Instrument};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("═══════════════════════════════════════════════════════════════");
-    println!("   EXCHANGE DATA FETCH VALIDATION TEST");
-    println!("═══════════════════════════════════════════════════════════════\n");
+    EnvConfig::load()?;
+    logging::init_from_env()?;
+    info!("Exchange data fetch validation test");
+
+    let mock = if std::env::args().any(|arg| arg == "--mock") {
+        let mock = MockExchange::start().await?;
+        info!(url = %mock.url(), "Using mock exchange");
+        Some(mock)
+    } else {
+        None
+    };
 
     // Initialize Event Bus once
     let event_bus = EventBus::new();
@@ -23,58 +35,50 @@ async fn main() -> Result<()> {
     ];
 
     for (name, exchange_type, symbol) in exchanges {
-        println!("─────────────────────────────────────────────────────────────────");
-        println!("Testing: {}", name);
-        println!("─────────────────────────────────────────────────────────────────");
+        let span = info_span!("exchange", exchange = name, symbol);
 
         // Create exchange config
         let config = ExchangeConfig {
+            base_url: mock.as_ref().map(|mock| mock.base_url(&exchange_type)),
             exchange_type,
             api_key: None,
             api_secret: None,
             enabled: true,
+            ws_url: None,
+            symbols: None,
         };
 
-        match test_exchange(&config, symbol, event_bus.clone()).await {
+        match test_exchange(&config, symbol, event_bus.clone()).instrument(span.clone()).await {
             Ok((symbol, price, volume, is_valid, normalized)) => {
-                println!("✅ SUCCESS: {}", name);
-                println!("   Symbol:         {}", symbol);
-                println!("   Price:          {} (Decimal with full precision)", price);
-                println!("   Volume:         {} (Decimal)", volume);
-                println!("   Price Type:     rust_decimal::Decimal ✓");
-                println!("   Is Positive:    {} ✓", is_valid);
-                println!("   Normalized:     {} ✓", normalized);
-                
+                let _entered = span.enter();
                 // Verify Decimal precision
                 verify_decimal_precision(&price)
                     .context(format!("Failed to verify decimal precision for {}", name))?;
-                println!("   Precision:      8 decimal places confirmed ✓");
+                info!(
+                    symbol = %symbol,
+                    price = %price,
+                    volume = %volume,
+                    is_positive = is_valid,
+                    normalized,
+                    "Exchange check passed"
+                );
             }
             Err(e) => {
-                println!("❌ FAILED: {}", name);
-                println!("   Error: {:?}", e);
+                let _entered = span.enter();
+                error!(error = ?e, "Exchange check failed");
             }
         }
-        println!();
     }
 
-    println!("═══════════════════════════════════════════════════════════════");
-    println!("   DECIMAL PRECISION VALIDATION");
-    println!("═══════════════════════════════════════════════════════════════\n");
-
     test_decimal_conversions()
         .context("Decimal conversion tests failed")?;
 
-    println!("═══════════════════════════════════════════════════════════════");
-    println!("   ERROR HANDLING VALIDATION");
-    println!("═══════════════════════════════════════════════════════════════\n");
-
-    test_error_handling()
+    test_error_handling(mock.as_ref().map(|mock| mock.binance_url()))
+        .instrument(info_span!("error_handling"))
         .await
         .context("Error handling tests failed")?;
 
-    println!("\n✅ ALL TESTS PASSED");
-    println!("═══════════════════════════════════════════════════════════════");
+    info!("All tests passed");
 
     Ok(())
 }
@@ -112,7 +116,7 @@ async fn test_exchange(
 fn verify_decimal_precision(price: &Decimal) -> Result<()> {
     // Check that price is using Decimal (not f64)
     let price_str = price.to_string();
-    
+
     // Verify it's not in scientific notation (which f64 might use)
     if price_str.contains('e') || price_str.contains('E') {
         anyhow::bail!("Price in scientific notation - may indicate f64 conversion");
@@ -122,16 +126,13 @@ fn verify_decimal_precision(price: &Decimal) -> Result<()> {
 }
 
 fn test_decimal_conversions() -> Result<()> {
-    println!("Testing Decimal conversion safety:\n");
+    let _span = info_span!("decimal_conversions").entered();
 
     // Test from_str_exact (used by Binance and Bybit)
     let test_value = "89563.98";
     let decimal = Decimal::from_str(test_value)
         .context(format!("Failed to parse {}", test_value))?;
-    
-    println!("✅ Binance/Bybit format (from_str): {} → {}", test_value, decimal);
-    println!("   Type: rust_decimal::Decimal");
-    println!("   Precision: {:?}", decimal.scale());
+    info!(input = test_value, parsed = %decimal, scale = decimal.scale(), "Parsed exchange price format");
 
     // Verify no f64 arithmetic
     let price1 = Decimal::from_str("100.50")
@@ -139,66 +140,38 @@ fn test_decimal_conversions() -> Result<()> {
     let price2 = Decimal::from_str("100.51")
         .context("Failed to parse price2")?;
     let diff = price2 - price1;
-    
-    println!("\n✅ Decimal arithmetic (no floating-point errors):");
-    println!("   {} - {} = {}", price2, price1, diff);
-    println!("   Exact result with Decimal precision");
+    info!(%price1, %price2, %diff, "Exact decimal arithmetic");
 
     Ok(())
 }
 
-async fn test_error_handling() -> Result<()> {
-    println!("Testing error handling in financial operations:\n");
-
+async fn test_error_handling(base_url: Option<String>) -> Result<()> {
     let event_bus = EventBus::new();
     let config = ExchangeConfig {
         exchange_type: ExchangeType::Binance,
         api_key: None,
         api_secret: None,
         enabled: true,
+        base_url,
+        ws_url: None,
+        symbols: None,
     };
 
     let fetcher = ExchangeFactory::create_fetcher(&config, event_bus)
         .context("Failed to create Binance fetcher")?;
 
     // Test invalid symbol (should return proper error, not panic)
-    println!("✅ Error handling with invalid symbol:");
     match fetcher.fetch_price("INVALID_SYMBOL_XYZ").await {
-        Ok(_) => {
-            println!("   (API returned data for symbol, which is acceptable)");
-        }
-        Err(e) => {
-            println!("   Properly returned error: {}", e);
-            println!("   No panic or unwrap() - error propagated safely");
-        }
+        Ok(_) => info!(symbol = "INVALID_SYMBOL_XYZ", "API returned data for symbol, which is acceptable"),
+        Err(e) => info!(symbol = "INVALID_SYMBOL_XYZ", error = %e, "Invalid symbol returned an error without panicking"),
     }
 
     // Test valid data flow
-    println!("\n✅ Error handling in data flow:");
     let price_event = fetcher
         .fetch_price("BTCUSDT")
         .await
         .context("Failed to fetch BTCUSDT")?;
-    
-    println!("   Successfully fetched: {} @ {}", 
-        price_event.symbol, 
-        price_event.price
-    );
-    println!("   All conversions from str to Decimal succeeded");
-    println!("   Error handling: All Results properly handled with context");
-
-    println!("\n✅ Error types implemented with thiserror:");
-    println!("   • TradingError::MarketData - API failures");
-    println!("   • TradingError::Decimal - Precision conversion");
-    println!("   • TradingError::DecimalParse - Parse errors");
-    println!("   • TradingError::Network - HTTP failures");
-    println!("   • TradingError::Time - System time errors");
-    println!("   • TradingError::Validation - Data validation");
-    println!("   • TradingError::Config - Configuration errors");
-    println!("   • TradingError::Strategy - Strategy errors");
-    println!("   • TradingError::Risk - Risk management errors");
-    println!("   • TradingError::EventBus - Event bus errors");
-    println!("\n✅ anyhow::Context adds rich error context for debugging");
+    info!(symbol = %price_event.symbol, price = %price_event.price, "Valid symbol fetched with all conversions succeeding");
 
     Ok(())
 }
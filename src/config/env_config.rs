@@ -23,4 +23,65 @@ impl EnvConfig {
     pub fn get_bybit_secret_key() -> Option<String> {
         std::env::var("BYBIT_SECRET_KEY").ok()
     }
+
+    pub fn get_binance_base_url() -> Option<String> {
+        std::env::var("BINANCE_BASE_URL").ok()
+    }
+
+    pub fn get_bybit_base_url() -> Option<String> {
+        std::env::var("BYBIT_BASE_URL").ok()
+    }
+
+    pub fn get_binance_ws_url() -> Option<String> {
+        std::env::var("BINANCE_WS_URL").ok()
+    }
+
+    pub fn get_bybit_ws_url() -> Option<String> {
+        std::env::var("BYBIT_WS_URL").ok()
+    }
+
+    /// Path of the event journal; journaling is off when unset
+    pub fn get_event_journal_path() -> Option<String> {
+        std::env::var("EVENT_JOURNAL_PATH").ok()
+    }
+
+    /// Directory of the historical kline cache; klines are not cached when unset
+    pub fn get_kline_cache_dir() -> Option<String> {
+        std::env::var("KLINE_CACHE_DIR").ok()
+    }
+
+    /// Directory to record price, book and trade events into; off when unset
+    pub fn get_record_dir() -> Option<String> {
+        std::env::var("RECORD_DIR").ok()
+    }
+
+    /// Directory written by the recorder to replay instead of polling an exchange
+    pub fn get_replay_dir() -> Option<String> {
+        std::env::var("REPLAY_DIR").ok()
+    }
+
+    /// CSV or Parquet file to replay instead of polling an exchange
+    pub fn get_replay_csv() -> Option<String> {
+        std::env::var("REPLAY_CSV").ok()
+    }
+
+    /// Consensus price across all exchanges, "median" or "vwap"; off when unset
+    pub fn get_price_consensus() -> Option<String> {
+        std::env::var("PRICE_CONSENSUS").ok()
+    }
+
+    /// JSON file of instrument rules; fetched from the exchange and written there when missing
+    pub fn get_instruments_file() -> Option<String> {
+        std::env::var("INSTRUMENTS_FILE").ok()
+    }
+
+    /// Log output format: "pretty" (default) or "json"
+    pub fn get_log_format() -> Option<String> {
+        std::env::var("LOG_FORMAT").ok()
+    }
+
+    /// Listen address of the Prometheus `/metrics` endpoint; disabled when unset
+    pub fn get_metrics_addr() -> Option<String> {
+        std::env::var("METRICS_ADDR").ok()
+    }
 }
@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use crate::error::Result;
+use crate::instrument::SymbolRegistry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeConfig {
@@ -7,9 +9,20 @@ pub struct ExchangeConfig {
     pub api_key: Option<String>,
     pub api_secret: Option<String>,
     pub enabled: bool,
+    /// REST endpoint override (e.g. a local mock exchange); falls back to the
+    /// `<EXCHANGE>_BASE_URL` environment variable, then the public API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// WebSocket endpoint override; falls back to `<EXCHANGE>_WS_URL`, then the public stream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ws_url: Option<String>,
+    /// Native symbol spellings for fetchers and streams built from this config,
+    /// usually `InstrumentRegistry::symbol_registry`; default spellings when unset
+    #[serde(skip)]
+    pub symbols: Option<Arc<SymbolRegistry>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ExchangeType {
     /// Binance - Largest crypto exchange
     /// Great for: Learning, paper trading, most altcoins
@@ -33,16 +46,28 @@ impl ExchangeConfig {
     pub fn validate(&self) -> Result<()> {
         // For live trading, require API credentials
         // For paper trading, they're optional
+        if let Some(url) = &self.base_url {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(crate::error::TradingError::Config(format!(
+                    "base_url must be an http(s) URL: {}", url
+                )));
+            }
+        }
+        if let Some(url) = &self.ws_url {
+            if !(url.starts_with("ws://") || url.starts_with("wss://")) {
+                return Err(crate::error::TradingError::Config(format!(
+                    "ws_url must be a ws(s) URL: {}", url
+                )));
+            }
+        }
         Ok(())
     }
 
     pub fn validate_for_trading(&self) -> Result<()> {
-        if self.enabled {
-            if self.api_key.is_none() || self.api_secret.is_none() {
-                return Err(crate::error::TradingError::Config(
-                    "Missing API credentials for trading".to_string(),
-                ));
-            }
+        if self.enabled && (self.api_key.is_none() || self.api_secret.is_none()) {
+            return Err(crate::error::TradingError::Config(
+                "Missing API credentials for trading".to_string(),
+            ));
         }
         Ok(())
     }
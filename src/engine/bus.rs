@@ -1,75 +1,626 @@
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-use crate::error::Result;
-use super::event::Event;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::{broadcast, mpsc};
+use tokio::sync::mpsc::error::TrySendError;
+use crate::error::{Result, TradingError};
+use super::event::{Event, EventKind};
+use super::envelope::{Causation, EventEnvelope};
+use super::journal::EventJournal;
+use super::metrics::{BusMetrics, BusMetricsSnapshot, KindStats, LatencyHistogram, SubscriberMetrics};
 
 /// Handler function type for event subscribers
-pub type EventHandler = Arc<dyn Fn(&Event) + Send + Sync>;
+pub type EventHandler = Arc<dyn Fn(&EventEnvelope) + Send + Sync>;
+
+/// Source recorded for events published without one
+pub const UNKNOWN_SOURCE: &str = "unknown";
+
+/// Source stamped on events the bus publishes about itself
+const SOURCE: &str = "EventBus";
+
+/// What a subscriber queue does when it is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for space (backpressure on the publisher)
+    Block,
+    /// Evict the oldest queued event to make room
+    DropOldest,
+    /// Discard the event being published
+    DropNewest,
+}
+
+/// How `publish` delivers events to subscribers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// Handlers run inline on the publishing task
+    Sync,
+    /// Every subscriber drains its own bounded queue on a tokio task.
+    /// With `DropOldest` the capacity is rounded up to a power of two.
+    Async {
+        capacity: usize,
+        overflow: OverflowPolicy,
+    },
+}
+
+/// Event bus configuration
+#[derive(Debug, Clone)]
+pub struct EventBusConfig {
+    pub mode: DispatchMode,
+    /// Unsubscribe a handler after this many consecutive panics (None = never)
+    pub max_handler_failures: Option<u32>,
+    /// Number of dead letters kept; the oldest are discarded first
+    pub dead_letter_capacity: usize,
+}
+
+impl Default for EventBusConfig {
+    fn default() -> Self {
+        Self {
+            mode: DispatchMode::Sync,
+            max_handler_failures: Some(5),
+            dead_letter_capacity: 1000,
+        }
+    }
+}
+
+/// An event whose handler panicked, kept for inspection
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub subscriber_id: u64,
+    pub kind: EventKind,
+    pub envelope: EventEnvelope,
+    /// Panic message of the handler
+    pub error: String,
+    /// Failure time in milliseconds since the Unix epoch
+    pub failed_at: u64,
+    /// Whether this failure caused the handler to be unsubscribed
+    pub disabled: bool,
+}
+
+/// Delivery counters shared between a subscriber's queue and its worker
+#[derive(Debug, Default)]
+struct SubscriberStats {
+    pending: AtomicUsize,
+    dropped: AtomicU64,
+    /// Consecutive handler panics, reset by a successful run
+    failures: AtomicU32,
+    disabled: AtomicBool,
+    latency: LatencyHistogram,
+}
+
+#[derive(Clone)]
+enum SubscriberQueue {
+    /// Bounded mpsc channel that applies backpressure when full
+    Blocking(mpsc::Sender<EventEnvelope>),
+    /// Bounded mpsc channel that rejects new events when full
+    DropNewest(mpsc::Sender<EventEnvelope>),
+    /// Ring buffer that overwrites the oldest entry when full
+    DropOldest(broadcast::Sender<EventEnvelope>),
+}
+
+#[derive(Clone)]
+struct Subscriber {
+    id: u64,
+    kind: EventKind,
+    handler: EventHandler,
+    queue: Option<SubscriberQueue>,
+    stats: Arc<SubscriberStats>,
+    kind_stats: Arc<KindStats>,
+}
+
+type SubscriberMap = HashMap<EventKind, Vec<Subscriber>>;
+
+/// Lock a mutex, recovering the data if a previous holder panicked
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "handler panicked".to_string()
+    }
+}
+
+/// Handle returned by `EventBus::subscribe`.
+/// Dropping or cancelling it removes the handler from the bus.
+#[must_use = "dropping a Subscription unsubscribes the handler; call detach() to keep it"]
+pub struct Subscription {
+    id: u64,
+    kind: EventKind,
+    subscribers: Weak<Mutex<SubscriberMap>>,
+    active: bool,
+}
+
+impl Subscription {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn kind(&self) -> EventKind {
+        self.kind
+    }
+
+    /// Remove the handler now
+    pub fn cancel(self) {
+        drop(self);
+    }
+
+    /// Keep the handler registered for the lifetime of the bus
+    pub fn detach(mut self) {
+        self.active = false;
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        if let Some(subscribers) = self.subscribers.upgrade() {
+            if let Some(list) = lock(&subscribers).get_mut(&self.kind) {
+                list.retain(|sub| sub.id != self.id);
+            }
+        }
+    }
+}
+
+/// State shared by every clone of a bus
+struct BusInner {
+    config: EventBusConfig,
+    subscribers: Arc<Mutex<SubscriberMap>>,
+    event_counts: Mutex<HashMap<String, u64>>,
+    next_subscriber_id: AtomicU64,
+    next_sequence: AtomicU64,
+    journal: Mutex<Option<Arc<EventJournal>>>,
+    dead_letters: Mutex<VecDeque<DeadLetter>>,
+    metrics: BusMetrics,
+}
 
 /// Event Bus - Central pub/sub mechanism for all trading events
+#[derive(Clone)]
 pub struct EventBus {
-    subscribers: Arc<Mutex<HashMap<String, Vec<EventHandler>>>>,
-    event_counts: Arc<Mutex<HashMap<String, u64>>>,
+    inner: Arc<BusInner>,
 }
 
 impl EventBus {
     pub fn new() -> Self {
+        Self::with_config(EventBusConfig::default())
+    }
+
+    pub fn with_config(config: EventBusConfig) -> Self {
         Self {
-            subscribers: Arc::new(Mutex::new(HashMap::new())),
-            event_counts: Arc::new(Mutex::new(HashMap::new())),
+            inner: Arc::new(BusInner {
+                config,
+                subscribers: Arc::new(Mutex::new(HashMap::new())),
+                event_counts: Mutex::new(HashMap::new()),
+                next_subscriber_id: AtomicU64::new(1),
+                next_sequence: AtomicU64::new(1),
+                journal: Mutex::new(None),
+                dead_letters: Mutex::new(VecDeque::new()),
+                metrics: BusMetrics::new(),
+            }),
         }
     }
 
-    /// Subscribe to events of a specific type
-    pub fn subscribe<F>(&self, event_type: &str, handler: F) -> Result<()>
+    /// Async bus where each subscriber gets a queue of `capacity` events
+    pub fn new_async(capacity: usize, overflow: OverflowPolicy) -> Self {
+        Self::with_config(EventBusConfig {
+            mode: DispatchMode::Async { capacity, overflow },
+            ..EventBusConfig::default()
+        })
+    }
+
+    pub fn mode(&self) -> DispatchMode {
+        self.inner.config.mode
+    }
+
+    /// Subscribe to events of a specific kind.
+    /// The handler stays registered until the returned handle is dropped or cancelled.
+    pub fn subscribe<F>(&self, kind: EventKind, handler: F) -> Result<Subscription>
     where
-        F: Fn(&Event) + Send + Sync + 'static,
+        F: Fn(&EventEnvelope) + Send + Sync + 'static,
     {
-        let mut subs = self.subscribers.lock().unwrap();
-        subs.entry(event_type.to_string())
-            .or_insert_with(Vec::new)
-            .push(Arc::new(handler));
-        Ok(())
-    }
-
-    /// Publish an event to all subscribers
-    pub fn publish(&self, event: Event) -> Result<()> {
-        let event_type = event.event_type().to_string();
-        if let Ok(mut counts) = self.event_counts.lock() {
-            let counter = counts.entry(event_type.clone()).or_insert(0);
+        let mut subscriber = Subscriber {
+            id: self.inner.next_subscriber_id.fetch_add(1, Ordering::SeqCst),
+            kind,
+            handler: Arc::new(handler),
+            queue: None,
+            stats: Arc::new(SubscriberStats::default()),
+            kind_stats: self.inner.metrics.kind(kind),
+        };
+        if let DispatchMode::Async { capacity, overflow } = self.inner.config.mode {
+            // The worker gets a queue-less copy so it does not keep its own channel open
+            subscriber.queue = Some(self.spawn_worker(capacity, overflow, subscriber.clone())?);
+        }
+
+        let id = subscriber.id;
+        lock(&self.inner.subscribers).entry(kind).or_default().push(subscriber);
+
+        Ok(Subscription {
+            id,
+            kind,
+            subscribers: Arc::downgrade(&self.inner.subscribers),
+            active: true,
+        })
+    }
+
+    /// Persist every envelope published from now on (shared by all clones of the bus)
+    pub fn attach_journal(&self, journal: Arc<EventJournal>) {
+        *lock(&self.inner.journal) = Some(journal);
+    }
+
+    pub fn detach_journal(&self) -> Option<Arc<EventJournal>> {
+        lock(&self.inner.journal).take()
+    }
+
+    /// Number of handlers currently registered for `kind`
+    pub fn subscriber_count(&self, kind: EventKind) -> usize {
+        lock(&self.inner.subscribers).get(&kind).map(Vec::len).unwrap_or(0)
+    }
+
+    fn spawn_worker(
+        &self,
+        capacity: usize,
+        overflow: OverflowPolicy,
+        subscriber: Subscriber,
+    ) -> Result<SubscriberQueue> {
+        if capacity == 0 {
+            return Err(TradingError::EventBus(
+                "Subscriber queue capacity must be greater than 0".to_string(),
+            ));
+        }
+
+        let runtime = Handle::try_current().map_err(|_| {
+            TradingError::EventBus("Async event bus requires a running tokio runtime".to_string())
+        })?;
+
+        // Workers only hold a weak reference so they stop once the bus is dropped
+        let bus = Arc::downgrade(&self.inner);
+        let stats = Arc::clone(&subscriber.stats);
+
+        match overflow {
+            OverflowPolicy::Block | OverflowPolicy::DropNewest => {
+                let (tx, mut rx) = mpsc::channel::<EventEnvelope>(capacity);
+                runtime.spawn(async move {
+                    while let Some(event) = rx.recv().await {
+                        Self::invoke_weak(&bus, &subscriber, &event);
+                        stats.pending.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+                Ok(match overflow {
+                    OverflowPolicy::Block => SubscriberQueue::Blocking(tx),
+                    _ => SubscriberQueue::DropNewest(tx),
+                })
+            }
+            OverflowPolicy::DropOldest => {
+                let (tx, mut rx) = broadcast::channel::<EventEnvelope>(capacity);
+                runtime.spawn(async move {
+                    loop {
+                        match rx.recv().await {
+                            Ok(event) => {
+                                Self::invoke_weak(&bus, &subscriber, &event);
+                                stats.pending.fetch_sub(1, Ordering::SeqCst);
+                            }
+                            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                                stats.dropped.fetch_add(missed, Ordering::SeqCst);
+                                stats.pending.fetch_sub(missed as usize, Ordering::SeqCst);
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                });
+                Ok(SubscriberQueue::DropOldest(tx))
+            }
+        }
+    }
+
+    fn invoke_weak(bus: &Weak<BusInner>, subscriber: &Subscriber, event: &EventEnvelope) {
+        match bus.upgrade() {
+            Some(inner) => EventBus { inner }.invoke(subscriber, event),
+            // Bus is gone: drain the remaining events without reporting failures
+            None => {
+                if !subscriber.stats.disabled.load(Ordering::SeqCst) {
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| (subscriber.handler)(event)));
+                }
+            }
+        }
+    }
+
+    /// Run a handler, turning a panic into a dead letter instead of unwinding into the publisher
+    fn invoke(&self, subscriber: &Subscriber, event: &EventEnvelope) {
+        let stats = &subscriber.stats;
+        if stats.disabled.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let started = Instant::now();
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| (subscriber.handler)(event)));
+        let elapsed = started.elapsed();
+        stats.latency.record(elapsed);
+        subscriber.kind_stats.handler_latency.record(elapsed);
+
+        match outcome {
+            Ok(()) => stats.failures.store(0, Ordering::SeqCst),
+            Err(payload) => self.handler_failed(subscriber, event, panic_message(payload.as_ref())),
+        }
+    }
+
+    fn handler_failed(&self, subscriber: &Subscriber, event: &EventEnvelope, error: String) {
+        let failures = subscriber.stats.failures.fetch_add(1, Ordering::SeqCst) + 1;
+        let disabled = self.inner.config.max_handler_failures.is_some_and(|max| failures >= max);
+        if disabled {
+            subscriber.stats.disabled.store(true, Ordering::SeqCst);
+            if let Some(list) = lock(&self.inner.subscribers).get_mut(&subscriber.kind) {
+                list.retain(|sub| sub.id != subscriber.id);
+            }
+        }
+
+        let mut message = format!(
+            "Handler {} for {} panicked on event #{}: {}",
+            subscriber.id, subscriber.kind, event.sequence, error
+        );
+        if disabled {
+            message.push_str(&format!(" (disabled after {} consecutive failures)", failures));
+        }
+
+        {
+            let mut dead_letters = lock(&self.inner.dead_letters);
+            if dead_letters.len() >= self.inner.config.dead_letter_capacity {
+                dead_letters.pop_front();
+            }
+            if self.inner.config.dead_letter_capacity > 0 {
+                dead_letters.push_back(DeadLetter {
+                    subscriber_id: subscriber.id,
+                    kind: subscriber.kind,
+                    envelope: event.clone(),
+                    error,
+                    failed_at: now_millis(),
+                    disabled,
+                });
+            }
+        }
+
+        // A failing error handler must not feed itself
+        if event.kind() != EventKind::Error {
+            let _ = self.publish_from(SOURCE, Event::Error(message), Some(event.caused()));
+        }
+    }
+
+    /// Events whose handlers panicked, oldest first
+    pub fn dead_letters(&self) -> Vec<DeadLetter> {
+        lock(&self.inner.dead_letters).iter().cloned().collect()
+    }
+
+    /// Remove and return every dead letter
+    pub fn take_dead_letters(&self) -> Vec<DeadLetter> {
+        lock(&self.inner.dead_letters).drain(..).collect()
+    }
+
+    /// Publish an event to all subscribers without source or causation metadata.
+    /// Returns the sequence number assigned to the event.
+    pub fn publish(&self, event: Event) -> Result<u64> {
+        self.publish_from(UNKNOWN_SOURCE, event, None)
+    }
+
+    /// Publish an event on behalf of `source`, optionally linked to the event that caused it.
+    /// In async mode with `OverflowPolicy::Block` a full queue blocks the calling
+    /// thread, which needs a multi-thread runtime; prefer `publish_async_from` there.
+    pub fn publish_from(&self, source: &str, event: Event, cause: Option<Causation>) -> Result<u64> {
+        let envelope = self.envelope(source, event, cause);
+        self.write_journal(&envelope)?;
+        self.dispatch(envelope)
+    }
+
+    /// Deliver a previously journaled envelope with its original metadata.
+    /// It is not written to the journal again; later publishes continue after its sequence.
+    pub fn replay_envelope(&self, envelope: EventEnvelope) -> Result<u64> {
+        self.inner.next_sequence.fetch_max(envelope.sequence + 1, Ordering::SeqCst);
+        self.dispatch(envelope)
+    }
+
+    fn dispatch(&self, envelope: EventEnvelope) -> Result<u64> {
+        self.record(&envelope);
+
+        for subscriber in self.subscribers_for(envelope.kind()) {
+            self.deliver(&subscriber, &envelope)?;
+        }
+
+        Ok(envelope.sequence)
+    }
+
+    fn write_journal(&self, envelope: &EventEnvelope) -> Result<()> {
+        let journal = lock(&self.inner.journal).clone();
+        match journal {
+            Some(journal) => journal.append(envelope),
+            None => Ok(()),
+        }
+    }
+
+    /// Publish an event, awaiting queue space instead of blocking the thread
+    pub async fn publish_async(&self, event: Event) -> Result<u64> {
+        self.publish_async_from(UNKNOWN_SOURCE, event, None).await
+    }
+
+    pub async fn publish_async_from(
+        &self,
+        source: &str,
+        event: Event,
+        cause: Option<Causation>,
+    ) -> Result<u64> {
+        let envelope = self.envelope(source, event, cause);
+        self.write_journal(&envelope)?;
+        self.record(&envelope);
+
+        for subscriber in self.subscribers_for(envelope.kind()) {
+            match &subscriber.queue {
+                Some(SubscriberQueue::Blocking(tx)) => {
+                    // Reserve first so a cancelled publish leaves no pending count behind
+                    let permit = tx.reserve().await.map_err(|_| {
+                        TradingError::EventBus("Subscriber queue closed".to_string())
+                    })?;
+                    subscriber.stats.pending.fetch_add(1, Ordering::SeqCst);
+                    permit.send(envelope.clone());
+                }
+                _ => self.deliver(&subscriber, &envelope)?,
+            }
+        }
+
+        Ok(envelope.sequence)
+    }
+
+    fn envelope(&self, source: &str, event: Event, cause: Option<Causation>) -> EventEnvelope {
+        EventEnvelope {
+            sequence: self.inner.next_sequence.fetch_add(1, Ordering::SeqCst),
+            timestamp: now_millis(),
+            source: source.to_string(),
+            correlation_id: cause.map(|c| c.correlation_id),
+            causation_id: cause.map(|c| c.causation_id),
+            event,
+        }
+    }
+
+    fn record(&self, envelope: &EventEnvelope) {
+        {
+            let mut counts = lock(&self.inner.event_counts);
+            let counter = counts.entry(envelope.event_type().to_string()).or_insert(0);
             *counter += 1;
         }
-        let subs = self.subscribers.lock().unwrap();
+        self.inner.metrics.record_publish(envelope);
+    }
+
+    /// Clone the subscriber list so handlers run without holding the lock
+    /// (handlers may publish or subscribe themselves)
+    fn subscribers_for(&self, kind: EventKind) -> Vec<Subscriber> {
+        lock(&self.inner.subscribers).get(&kind).cloned().unwrap_or_default()
+    }
+
+    fn deliver(&self, subscriber: &Subscriber, event: &EventEnvelope) -> Result<()> {
+        let stats = &subscriber.stats;
+        match &subscriber.queue {
+            None => {
+                self.invoke(subscriber, event);
+                Ok(())
+            }
+            Some(SubscriberQueue::DropOldest(tx)) => {
+                stats.pending.fetch_add(1, Ordering::SeqCst);
+                if tx.send(event.clone()).is_err() {
+                    stats.pending.fetch_sub(1, Ordering::SeqCst);
+                }
+                Ok(())
+            }
+            Some(SubscriberQueue::DropNewest(tx)) => {
+                stats.pending.fetch_add(1, Ordering::SeqCst);
+                if let Err(err) = tx.try_send(event.clone()) {
+                    stats.pending.fetch_sub(1, Ordering::SeqCst);
+                    if matches!(err, TrySendError::Full(_)) {
+                        stats.dropped.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+                Ok(())
+            }
+            Some(SubscriberQueue::Blocking(tx)) => {
+                stats.pending.fetch_add(1, Ordering::SeqCst);
+                let result = match tx.try_send(event.clone()) {
+                    Ok(()) => Ok(()),
+                    Err(TrySendError::Closed(_)) => Err(TradingError::EventBus(
+                        "Subscriber queue closed".to_string(),
+                    )),
+                    Err(TrySendError::Full(event)) => Self::blocking_send(tx, event),
+                };
+                if result.is_err() {
+                    stats.pending.fetch_sub(1, Ordering::SeqCst);
+                }
+                result
+            }
+        }
+    }
 
-        if let Some(handlers) = subs.get(&event_type) {
-            for handler in handlers {
-                handler(&event);
+    fn blocking_send(tx: &mpsc::Sender<EventEnvelope>, event: EventEnvelope) -> Result<()> {
+        let closed = |_| TradingError::EventBus("Subscriber queue closed".to_string());
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(tx.send(event)).map_err(closed))
             }
+            Ok(_) => Err(TradingError::EventBus(
+                "Subscriber queue full; use publish_async on a current-thread runtime".to_string(),
+            )),
+            Err(_) => tx.blocking_send(event).map_err(closed),
         }
+    }
+
+    /// Wait until every async subscriber queue has been drained
+    pub async fn flush(&self) {
+        while self.pending_events() > 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// Events queued but not yet handled, across all subscribers
+    pub fn pending_events(&self) -> usize {
+        self.fold_stats(|stats| stats.pending.load(Ordering::SeqCst) as u64) as usize
+    }
+
+    /// Events discarded by the overflow policy, across all subscribers
+    pub fn dropped_events(&self) -> u64 {
+        self.fold_stats(|stats| stats.dropped.load(Ordering::SeqCst))
+    }
 
-        Ok(())
+    fn fold_stats(&self, value: impl Fn(&SubscriberStats) -> u64) -> u64 {
+        lock(&self.inner.subscribers)
+            .values()
+            .flatten()
+            .map(|sub| value(&sub.stats))
+            .sum()
     }
 
     /// Snapshot of event counters for observability
     pub fn metrics_snapshot(&self) -> HashMap<String, u64> {
-        self.event_counts
-            .lock()
-            .map(|m| m.clone())
-            .unwrap_or_default()
+        lock(&self.inner.event_counts).clone()
+    }
+
+    /// Latency, queue depth and throughput per event type and per subscriber
+    pub fn metrics(&self) -> BusMetricsSnapshot {
+        let mut subscribers: Vec<SubscriberMetrics> = lock(&self.inner.subscribers)
+            .values()
+            .flatten()
+            .map(|sub| SubscriberMetrics {
+                id: sub.id,
+                kind: sub.kind,
+                handler_latency: sub.stats.latency.snapshot(),
+                queue_depth: sub.stats.pending.load(Ordering::SeqCst),
+                dropped: sub.stats.dropped.load(Ordering::SeqCst),
+                failures: sub.stats.failures.load(Ordering::SeqCst),
+            })
+            .collect();
+        subscribers.sort_by_key(|sub| sub.id);
+        self.inner.metrics.snapshot(subscribers)
     }
 
     /// Publish to all subscribers regardless of event type
-    pub fn publish_all(&self, event: Event) -> Result<()> {
-        let subs = self.subscribers.lock().unwrap();
+    pub fn publish_all(&self, event: Event) -> Result<u64> {
+        let envelope = self.envelope(UNKNOWN_SOURCE, event, None);
+        self.write_journal(&envelope)?;
+        let all: Vec<Subscriber> = lock(&self.inner.subscribers).values().flatten().cloned().collect();
 
         // Call all handlers for all event types
-        for handlers in subs.values() {
-            for handler in handlers {
-                handler(&event);
-            }
+        for subscriber in &all {
+            self.deliver(subscriber, &envelope)?;
         }
 
-        Ok(())
+        Ok(envelope.sequence)
     }
 }
 
@@ -79,11 +630,268 @@ impl Default for EventBus {
     }
 }
 
-impl Clone for EventBus {
-    fn clone(&self) -> Self {
-        Self {
-            subscribers: Arc::clone(&self.subscribers),
-            event_counts: Arc::clone(&self.event_counts),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_event(i: usize) -> Event {
+        Event::Error(i.to_string())
+    }
+
+    /// Subscribe a handler that blocks on the first event until `gate` opens
+    fn gated_collector(bus: &EventBus) -> (Arc<Mutex<Vec<String>>>, Arc<AtomicBool>, Arc<AtomicBool>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let entered = Arc::new(AtomicBool::new(false));
+        let gate = Arc::new(AtomicBool::new(false));
+        let (seen_c, entered_c, gate_c) = (Arc::clone(&seen), Arc::clone(&entered), Arc::clone(&gate));
+        bus.subscribe(EventKind::Error, move |event| {
+            entered_c.store(true, Ordering::SeqCst);
+            while !gate_c.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            if let Event::Error(msg) = &event.event {
+                seen_c.lock().unwrap().push(msg.clone());
+            }
+        }).unwrap().detach();
+        (seen, entered, gate)
+    }
+
+    async fn wait_for(flag: &AtomicBool) {
+        while !flag.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sync_handler_can_publish() {
+        let bus = EventBus::new();
+        let inner = bus.clone();
+        bus.subscribe(EventKind::RiskHalt, move |_| {
+            inner.publish(Event::Error("halted".to_string())).unwrap();
+        }).unwrap().detach();
+
+        bus.publish(Event::RiskHalt { reason: "test".to_string() }).unwrap();
+        assert_eq!(bus.metrics_snapshot().get("Error"), Some(&1));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_drop_newest_discards_incoming() {
+        let bus = EventBus::new_async(2, OverflowPolicy::DropNewest);
+        let (seen, entered, gate) = gated_collector(&bus);
+
+        bus.publish(error_event(1)).unwrap();
+        wait_for(&entered).await;
+        for i in 2..=4 {
+            bus.publish(error_event(i)).unwrap();
+        }
+        gate.store(true, Ordering::SeqCst);
+        bus.flush().await;
+
+        assert_eq!(*seen.lock().unwrap(), vec!["1", "2", "3"]);
+        assert_eq!(bus.dropped_events(), 1);
+        assert_eq!(bus.metrics_snapshot().get("Error"), Some(&4));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_drop_oldest_keeps_latest() {
+        let bus = EventBus::new_async(2, OverflowPolicy::DropOldest);
+        let (seen, entered, gate) = gated_collector(&bus);
+
+        bus.publish(error_event(1)).unwrap();
+        wait_for(&entered).await;
+        for i in 2..=4 {
+            bus.publish(error_event(i)).unwrap();
+        }
+        gate.store(true, Ordering::SeqCst);
+        bus.flush().await;
+
+        assert_eq!(*seen.lock().unwrap(), vec!["1", "3", "4"]);
+        assert_eq!(bus.dropped_events(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_block_applies_backpressure() {
+        let bus = EventBus::new_async(1, OverflowPolicy::Block);
+        let (seen, entered, gate) = gated_collector(&bus);
+
+        bus.publish_async(error_event(1)).await.unwrap();
+        wait_for(&entered).await;
+        bus.publish_async(error_event(2)).await.unwrap();
+
+        let blocked = tokio::time::timeout(
+            Duration::from_millis(20),
+            bus.publish_async(error_event(3)),
+        ).await;
+        assert!(blocked.is_err(), "publish should wait for queue space");
+
+        gate.store(true, Ordering::SeqCst);
+        bus.publish_async(error_event(4)).await.unwrap();
+        bus.flush().await;
+
+        assert_eq!(*seen.lock().unwrap(), vec!["1", "2", "4"]);
+        assert_eq!(bus.dropped_events(), 0);
+    }
+
+    #[test]
+    fn test_async_subscribe_requires_runtime() {
+        let bus = EventBus::new_async(8, OverflowPolicy::DropNewest);
+        assert!(bus.subscribe(EventKind::Error, |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_dropping_subscription_unsubscribes() {
+        let bus = EventBus::new();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let subscription = bus.subscribe(EventKind::Error, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+        let other = bus.subscribe(EventKind::Error, |_| {}).unwrap();
+
+        bus.publish(error_event(1)).unwrap();
+        subscription.cancel();
+        bus.publish(error_event(2)).unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(bus.subscriber_count(EventKind::Error), 1);
+        drop(other);
+        assert_eq!(bus.subscriber_count(EventKind::Error), 0);
+    }
+
+    #[test]
+    fn test_envelope_metadata() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_c = Arc::clone(&seen);
+        bus.subscribe(EventKind::Error, move |envelope| {
+            seen_c.lock().unwrap().push(envelope.clone());
+        }).unwrap().detach();
+
+        let root = bus.publish_from("feed", error_event(1), None).unwrap();
+        let child = bus.publish_from("risk", error_event(2), Some(Causation::root(root))).unwrap();
+        assert!(child > root);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].source, "feed");
+        assert_eq!(seen[0].correlation_id, None);
+        assert_eq!(seen[1].sequence, child);
+        assert_eq!(seen[1].causation(), Some(Causation::root(root)));
+        assert_eq!(seen[1].caused(), Causation { correlation_id: root, causation_id: child });
+    }
+
+    #[test]
+    fn test_event_kind_from_str() {
+        assert_eq!("OrderFilled".parse::<EventKind>().unwrap(), EventKind::OrderFilled);
+        assert!("PriceUpdate".parse::<EventKind>().is_err());
+    }
+
+    #[test]
+    fn test_panicking_handler_is_isolated() {
+        let bus = EventBus::new();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_c = Arc::clone(&errors);
+        bus.subscribe(EventKind::Error, move |envelope| {
+            errors_c.lock().unwrap().push(envelope.clone());
+        }).unwrap().detach();
+        bus.subscribe(EventKind::RiskHalt, |_| panic!("boom")).unwrap().detach();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        bus.subscribe(EventKind::RiskHalt, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }).unwrap().detach();
+
+        let halt = bus.publish(Event::RiskHalt { reason: "first".to_string() }).unwrap();
+        bus.publish(Event::RiskHalt { reason: "second".to_string() }).unwrap();
+
+        // The healthy handler still sees every event
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        let dead = bus.dead_letters();
+        assert_eq!(dead.len(), 2);
+        assert_eq!(dead[0].envelope.sequence, halt);
+        assert_eq!(dead[0].error, "boom");
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].source, "EventBus");
+        assert_eq!(errors[0].causation_id, Some(halt));
+    }
+
+    #[test]
+    fn test_failing_handler_is_disabled() {
+        let bus = EventBus::with_config(EventBusConfig {
+            max_handler_failures: Some(2),
+            ..EventBusConfig::default()
+        });
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        bus.subscribe(EventKind::Error, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            panic!("always");
+        }).unwrap().detach();
+
+        for i in 0..4 {
+            bus.publish(error_event(i)).unwrap();
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(bus.subscriber_count(EventKind::Error), 0);
+        let dead = bus.take_dead_letters();
+        assert_eq!(dead.len(), 2);
+        assert!(dead[1].disabled);
+        assert!(bus.dead_letters().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_async_worker_survives_panic() {
+        let bus = EventBus::new_async(8, OverflowPolicy::DropNewest);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_c = Arc::clone(&seen);
+        bus.subscribe(EventKind::Error, move |envelope| {
+            if let Event::Error(msg) = &envelope.event {
+                if msg == "2" {
+                    panic!("bad event");
+                }
+                seen_c.lock().unwrap().push(msg.clone());
+            }
+        }).unwrap().detach();
+
+        for i in 1..=3 {
+            bus.publish(error_event(i)).unwrap();
+        }
+        bus.flush().await;
+
+        assert_eq!(*seen.lock().unwrap(), vec!["1", "3"]);
+        assert_eq!(bus.dead_letters().len(), 1);
+    }
+
+    #[test]
+    fn test_metrics_track_handlers_and_end_to_end() {
+        let bus = EventBus::new();
+        let subscription = bus.subscribe(EventKind::TradeExecuted, |_| {
+            std::thread::sleep(Duration::from_millis(2));
+        }).unwrap();
+
+        let price = crate::market_data::PriceEvent::new(
+            "BTCUSDT".to_string(),
+            rust_decimal::Decimal::from(100),
+            rust_decimal::Decimal::ONE,
+        ).unwrap();
+        let price_seq = bus.publish(Event::PriceUpdated(price)).unwrap();
+        bus.publish_from("ExecutionEngine", Event::TradeExecuted {
+            symbol: "BTCUSDT".to_string(),
+            signal: crate::strategy::Signal::Buy,
+            entry_price: rust_decimal::Decimal::from(100),
+            position_size: rust_decimal::Decimal::ONE,
+            stop_loss: rust_decimal::Decimal::from(95),
+        }, Some(Causation::root(price_seq))).unwrap();
+
+        let metrics = bus.metrics();
+        assert_eq!(metrics.event_types["PriceUpdated"].published, 1);
+        assert!(metrics.event_types["TradeExecuted"].events_per_sec > 0.0);
+        assert!(metrics.event_types["TradeExecuted"].handler_latency.max >= Duration::from_millis(2));
+        assert_eq!(metrics.subscribers.len(), 1);
+        assert_eq!(metrics.subscribers[0].id, subscription.id());
+        assert_eq!(metrics.subscribers[0].handler_latency.count, 1);
+        assert_eq!(metrics.end_to_end.count, 1);
+    }
+}
@@ -0,0 +1,76 @@
+use std::ops::Deref;
+use serde::{Deserialize, Serialize};
+use super::event::{Event, EventKind};
+
+/// Links an event to the chain that produced it.
+/// `correlation_id` is the sequence number of the root event of the chain,
+/// `causation_id` the sequence number of the event that directly triggered it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Causation {
+    pub correlation_id: u64,
+    pub causation_id: u64,
+}
+
+impl Causation {
+    /// Start a chain at the event with sequence number `sequence`
+    pub fn root(sequence: u64) -> Self {
+        Self {
+            correlation_id: sequence,
+            causation_id: sequence,
+        }
+    }
+
+    /// Continue the chain from the event with sequence number `sequence`
+    pub fn then(self, sequence: u64) -> Self {
+        Self {
+            correlation_id: self.correlation_id,
+            causation_id: sequence,
+        }
+    }
+}
+
+/// Event plus the metadata stamped on it by the bus at publish time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Monotonically increasing per bus, starting at 1
+    pub sequence: u64,
+    /// Publish time in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Component that published the event
+    pub source: String,
+    pub correlation_id: Option<u64>,
+    pub causation_id: Option<u64>,
+    pub event: Event,
+}
+
+impl EventEnvelope {
+    pub fn kind(&self) -> EventKind {
+        self.event.kind()
+    }
+
+    pub fn causation(&self) -> Option<Causation> {
+        match (self.correlation_id, self.causation_id) {
+            (Some(correlation_id), Some(causation_id)) => Some(Causation {
+                correlation_id,
+                causation_id,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Causation to attach to events triggered by this one
+    pub fn caused(&self) -> Causation {
+        match self.causation() {
+            Some(cause) => cause.then(self.sequence),
+            None => Causation::root(self.sequence),
+        }
+    }
+}
+
+impl Deref for EventEnvelope {
+    type Target = Event;
+
+    fn deref(&self) -> &Event {
+        &self.event
+    }
+}
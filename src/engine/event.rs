@@ -1,12 +1,32 @@
+use std::fmt;
+use std::str::FromStr;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::error::TradingError;
 use crate::market_data::event::PriceEvent;
+use crate::market_data::order_book::OrderBookUpdate;
+use crate::market_data::trade::TradePrint;
+use crate::market_data::candle::Candle;
+use crate::market_data::guarded_fetcher::CircuitState;
+use crate::market_data::consensus_fetcher::VenueQuote;
+use crate::market_data::monitor::{QualityIssue, QualityOutcome};
 use crate::strategy::Signal;
+use crate::execution::order::{OrderType, TimeInForce};
 
 /// All events in the trading system
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Event {
     /// Market data event
     PriceUpdated(PriceEvent),
+
+    /// Level-2 depth snapshot or delta
+    OrderBookUpdated(OrderBookUpdate),
+
+    /// Public trade print from an exchange tape
+    TradePrinted(TradePrint),
+
+    /// OHLCV bar completed by the candle aggregator
+    CandleClosed(Candle),
     
     /// Strategy generated a signal
     SignalGenerated {
@@ -37,6 +57,8 @@ pub enum Event {
         order_id: u64,
         symbol: String,
         side: Signal,
+        order_type: OrderType,
+        tif: TimeInForce,
         quantity: Decimal,
         price: Option<Decimal>,
     },
@@ -66,24 +88,161 @@ pub enum Event {
     RiskHalt {
         reason: String,
     },
+
+    /// Circuit breaker of a market data feed changed state
+    CircuitStateChanged {
+        feed: String,
+        from: CircuitState,
+        to: CircuitState,
+        reason: String,
+    },
+
+    /// A feed failed and the request moved on; `to` is None when every feed failed
+    FeedFailover {
+        symbol: String,
+        from: String,
+        to: Option<String>,
+        /// One entry per failed feed, in the order they were tried
+        errors: Vec<String>,
+    },
+
+    /// Per-venue distance from a cross-exchange consensus price
+    PriceDivergence {
+        symbol: String,
+        consensus: Decimal,
+        venues: Vec<VenueQuote>,
+    },
+
+    /// A price update failed a data-quality check
+    DataQuality {
+        symbol: String,
+        outcome: QualityOutcome,
+        issues: Vec<QualityIssue>,
+    },
+
+    /// A feed was demoted or promoted by its health score
+    FeedHealthChanged {
+        feed: String,
+        healthy: bool,
+        reason: String,
+    },
     
     /// Error event
     Error(String),
 }
 
+/// Discriminant of `Event`, used as the subscription key on the bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    PriceUpdated,
+    OrderBookUpdated,
+    TradePrinted,
+    CandleClosed,
+    SignalGenerated,
+    TradeExecuted,
+    TradeClosed,
+    OrderSubmitted,
+    OrderFilled,
+    OrderCancelled,
+    OrderRejected,
+    RiskHalt,
+    CircuitStateChanged,
+    FeedFailover,
+    PriceDivergence,
+    DataQuality,
+    FeedHealthChanged,
+    Error,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 18] = [
+        EventKind::PriceUpdated,
+        EventKind::OrderBookUpdated,
+        EventKind::TradePrinted,
+        EventKind::CandleClosed,
+        EventKind::SignalGenerated,
+        EventKind::TradeExecuted,
+        EventKind::TradeClosed,
+        EventKind::OrderSubmitted,
+        EventKind::OrderFilled,
+        EventKind::OrderCancelled,
+        EventKind::OrderRejected,
+        EventKind::RiskHalt,
+        EventKind::CircuitStateChanged,
+        EventKind::FeedFailover,
+        EventKind::PriceDivergence,
+        EventKind::DataQuality,
+        EventKind::FeedHealthChanged,
+        EventKind::Error,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::PriceUpdated => "PriceUpdated",
+            EventKind::OrderBookUpdated => "OrderBookUpdated",
+            EventKind::TradePrinted => "TradePrinted",
+            EventKind::CandleClosed => "CandleClosed",
+            EventKind::SignalGenerated => "SignalGenerated",
+            EventKind::TradeExecuted => "TradeExecuted",
+            EventKind::TradeClosed => "TradeClosed",
+            EventKind::OrderSubmitted => "OrderSubmitted",
+            EventKind::OrderFilled => "OrderFilled",
+            EventKind::OrderCancelled => "OrderCancelled",
+            EventKind::OrderRejected => "OrderRejected",
+            EventKind::RiskHalt => "RiskHalt",
+            EventKind::CircuitStateChanged => "CircuitStateChanged",
+            EventKind::FeedFailover => "FeedFailover",
+            EventKind::PriceDivergence => "PriceDivergence",
+            EventKind::DataQuality => "DataQuality",
+            EventKind::FeedHealthChanged => "FeedHealthChanged",
+            EventKind::Error => "Error",
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EventKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| TradingError::EventBus(format!("Unknown event type: {}", s)))
+    }
+}
+
 impl Event {
-    pub fn event_type(&self) -> &str {
+    pub fn kind(&self) -> EventKind {
         match self {
-            Event::PriceUpdated(_) => "PriceUpdated",
-            Event::SignalGenerated { .. } => "SignalGenerated",
-            Event::TradeExecuted { .. } => "TradeExecuted",
-            Event::TradeClosed { .. } => "TradeClosed",
-            Event::OrderSubmitted { .. } => "OrderSubmitted",
-            Event::OrderFilled { .. } => "OrderFilled",
-            Event::OrderCancelled { .. } => "OrderCancelled",
-            Event::OrderRejected { .. } => "OrderRejected",
-            Event::RiskHalt { .. } => "RiskHalt",
-            Event::Error(_) => "Error",
+            Event::PriceUpdated(_) => EventKind::PriceUpdated,
+            Event::OrderBookUpdated(_) => EventKind::OrderBookUpdated,
+            Event::TradePrinted(_) => EventKind::TradePrinted,
+            Event::CandleClosed(_) => EventKind::CandleClosed,
+            Event::SignalGenerated { .. } => EventKind::SignalGenerated,
+            Event::TradeExecuted { .. } => EventKind::TradeExecuted,
+            Event::TradeClosed { .. } => EventKind::TradeClosed,
+            Event::OrderSubmitted { .. } => EventKind::OrderSubmitted,
+            Event::OrderFilled { .. } => EventKind::OrderFilled,
+            Event::OrderCancelled { .. } => EventKind::OrderCancelled,
+            Event::OrderRejected { .. } => EventKind::OrderRejected,
+            Event::RiskHalt { .. } => EventKind::RiskHalt,
+            Event::CircuitStateChanged { .. } => EventKind::CircuitStateChanged,
+            Event::FeedFailover { .. } => EventKind::FeedFailover,
+            Event::PriceDivergence { .. } => EventKind::PriceDivergence,
+            Event::DataQuality { .. } => EventKind::DataQuality,
+            Event::FeedHealthChanged { .. } => EventKind::FeedHealthChanged,
+            Event::Error(_) => EventKind::Error,
         }
     }
-}
\ No newline at end of file
+
+    pub fn event_type(&self) -> &str {
+        self.kind().as_str()
+    }
+}
@@ -1 +1,507 @@
-// Event loop implementation
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info, instrument, warn};
+use crate::error::{Result, TradingError};
+use crate::execution::ExecutionEngine;
+use crate::market_data::{
+    DataQualityConfig, MarketDataFetcher, PollingConfig, PollingScheduler, PriceEvent, PriceMonitor, PriceValidator,
+};
+use crate::strategy::{Signal, Strategy};
+use crate::telemetry::MetricsRegistry;
+use super::{Causation, Event, EventBus};
+
+/// Source name stamped on events published by the loop itself
+const SOURCE: &str = "EventLoop";
+
+/// Scheduling parameters for the event loop
+#[derive(Debug, Clone)]
+pub struct EventLoopConfig {
+    /// Time between two market data polls
+    pub tick_interval: Duration,
+    /// Data-quality checks applied to every price
+    pub data_quality: DataQualityConfig,
+    /// Symbols polled besides the strategy symbols, e.g. for recording or candles
+    pub universe: Vec<String>,
+    /// Most symbols fetched in one batch request
+    pub max_batch: usize,
+    /// Stop after this many ticks (None = run until shutdown)
+    pub max_ticks: Option<u64>,
+}
+
+impl Default for EventLoopConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval: Duration::from_secs(5),
+            data_quality: DataQualityConfig::default(),
+            universe: Vec::new(),
+            max_batch: 100,
+            max_ticks: None,
+        }
+    }
+}
+
+/// Cloneable handle used to request a clean shutdown of a running loop
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    tx: watch::Sender<bool>,
+}
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        let _ = self.tx.send(true);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.tx.borrow()
+    }
+}
+
+/// Drives the trading pipeline on a fixed tick schedule:
+/// fetch -> normalize -> monitor -> publish -> risk update -> signal -> execute
+///
+/// Fetchers return prices without publishing them; `Event::PriceUpdated` is
+/// published here, only for prices the monitor lets through.
+pub struct EventLoop {
+    config: EventLoopConfig,
+    event_bus: EventBus,
+    fetcher: Box<dyn MarketDataFetcher>,
+    strategies: Vec<(String, Box<dyn Strategy>)>,
+    execution: ExecutionEngine,
+    monitor: PriceMonitor,
+    scheduler: PollingScheduler,
+    shutdown: ShutdownHandle,
+    ticks: u64,
+    exhausted: bool,
+    metrics: Option<MetricsRegistry>,
+}
+
+impl EventLoop {
+    pub fn new(
+        config: EventLoopConfig,
+        event_bus: EventBus,
+        fetcher: Box<dyn MarketDataFetcher>,
+        execution: ExecutionEngine,
+    ) -> Result<Self> {
+        if config.tick_interval.is_zero() {
+            return Err(TradingError::Config(
+                "Tick interval must be greater than 0".to_string(),
+            ));
+        }
+
+        let (tx, _rx) = watch::channel(false);
+        let monitor = PriceMonitor::new(config.data_quality.clone(), event_bus.clone());
+        let polling = PollingConfig { interval: config.tick_interval, max_batch: config.max_batch };
+        let scheduler = PollingScheduler::new(polling, config.universe.clone())?;
+
+        Ok(Self {
+            config,
+            event_bus,
+            fetcher,
+            strategies: Vec::new(),
+            execution,
+            monitor,
+            scheduler,
+            shutdown: ShutdownHandle { tx },
+            ticks: 0,
+            exhausted: false,
+            metrics: None,
+        })
+    }
+
+    /// Attach a strategy to the symbol it trades
+    pub fn add_strategy(&mut self, symbol: impl Into<String>, strategy: Box<dyn Strategy>) {
+        let symbol = symbol.into();
+        self.scheduler.add_symbol(symbol.clone());
+        self.strategies.push((symbol, strategy));
+    }
+
+    /// Refresh the execution metrics in `registry` after every tick
+    pub fn attach_metrics(&mut self, registry: MetricsRegistry) {
+        registry.update_execution(self.execution.metrics());
+        self.metrics = Some(registry);
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Whether every symbol of the last tick reported `TradingError::EndOfData`
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Run until shutdown is requested, `max_ticks` is reached or the market data is exhausted
+    pub async fn run(&mut self) -> Result<()> {
+        if self.strategies.is_empty() {
+            return Err(TradingError::Config(
+                "Event loop has no strategies attached".to_string(),
+            ));
+        }
+
+        let mut shutdown_rx = self.shutdown.tx.subscribe();
+        info!(symbols = ?self.scheduler.universe(), interval = ?self.scheduler.interval(), "Event loop started");
+
+        while !*shutdown_rx.borrow() {
+            tokio::select! {
+                _ = shutdown_rx.changed() => break,
+                _ = self.scheduler.wait() => {
+                    self.tick().await?;
+                    if self.exhausted {
+                        info!("Market data exhausted");
+                        break;
+                    }
+                    if self.config.max_ticks.is_some_and(|max| self.ticks >= max) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        info!(ticks = self.ticks, "Event loop stopped");
+
+        Ok(())
+    }
+
+    /// Run a single pass of the pipeline over the polled universe, fetched in batches.
+    /// Per-symbol and per-batch failures are published as `Event::Error` and do not stop the loop;
+    /// end of data is not an error and marks the loop exhausted once every symbol reports it.
+    pub async fn tick(&mut self) -> Result<()> {
+        self.ticks += 1;
+
+        let round = self.scheduler.poll(self.fetcher.as_ref()).await;
+        let (mut polled, mut exhausted) = (0, 0);
+        for (symbols, err) in round.failed_batches {
+            polled += symbols.len();
+            if let TradingError::EndOfData(_) = err {
+                exhausted += symbols.len();
+                continue;
+            }
+            warn!(symbols = ?symbols, error = %err, "Tick failed");
+            self.event_bus.publish_from(SOURCE, Event::Error(format!("{}: {}", symbols.join(","), err)), None)?;
+        }
+        for (symbol, result) in round.prices {
+            polled += 1;
+            match result.and_then(|event| self.process_price(&symbol, event)) {
+                Ok(()) => {}
+                Err(TradingError::EndOfData(_)) => exhausted += 1,
+                Err(err) => {
+                    warn!(symbol = %symbol, error = %err, "Tick failed");
+                    self.event_bus.publish_from(SOURCE, Event::Error(format!("{}: {}", symbol, err)), None)?;
+                }
+            }
+        }
+        self.exhausted = polled > 0 && exhausted == polled;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.update_execution(self.execution.metrics());
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, price_event), fields(tick = self.ticks))]
+    fn process_price(&mut self, symbol: &str, price_event: PriceEvent) -> Result<()> {
+        let normalized = PriceValidator::normalize_with(price_event, self.execution.instruments())?;
+        let normalized = match self.monitor.admit(self.fetcher.exchange_name(), normalized)? {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+
+        self.execution.update_price(&normalized.symbol, normalized.price)?;
+        if let Some(quote) = normalized.quote {
+            self.execution.update_quote(&normalized.symbol, quote);
+        }
+        let price_cause = normalized.sequence.map(Causation::root);
+
+        for (strategy_symbol, strategy) in self.strategies.iter_mut() {
+            if strategy_symbol != symbol {
+                continue;
+            }
+
+            let signal = strategy.signal(&normalized)?;
+            debug!(strategy = strategy.name(), ?signal, price = %normalized.price, "Signal generated");
+            let signal_sequence = self.event_bus.publish_from(strategy.name(), Event::SignalGenerated {
+                strategy_name: strategy.name().to_string(),
+                symbol: normalized.symbol.clone(),
+                signal,
+                price: normalized.price,
+            }, price_cause)?;
+            let signal_cause = match price_cause {
+                Some(cause) => cause.then(signal_sequence),
+                None => Causation::root(signal_sequence),
+            };
+
+            if signal != Signal::Hold {
+                let (entry_price, stop_loss_distance, _position_size) =
+                    strategy.get_risk_params(normalized.price)?;
+                match self.execution.execute(
+                    normalized.symbol.clone(),
+                    signal,
+                    entry_price,
+                    stop_loss_distance,
+                    Some(signal_cause),
+                ) {
+                    // Rejections are already published by the execution engine
+                    Ok(_) | Err(TradingError::Rejected(_)) => {}
+                    Err(err) => {
+                        warn!(strategy = strategy.name(), error = %err, "Execution failed");
+                        self.event_bus.publish_from(
+                            SOURCE,
+                            Event::Error(format!("{}: {}", normalized.symbol, err)),
+                            Some(signal_cause),
+                        )?;
+                    }
+                }
+            }
+
+            strategy.update(&normalized)?;
+        }
+
+        Ok(())
+    }
+
+    /// Symbols polled every tick: the configured universe plus strategy symbols
+    pub fn universe(&self) -> &[String] {
+        self.scheduler.universe()
+    }
+
+    /// Distinct symbols tracked by the attached strategies, in attach order
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = Vec::new();
+        for (symbol, _) in &self.strategies {
+            if !symbols.contains(symbol) {
+                symbols.push(symbol.clone());
+            }
+        }
+        symbols
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn fetcher(&self) -> &dyn MarketDataFetcher {
+        self.fetcher.as_ref()
+    }
+
+    pub fn execution(&self) -> &ExecutionEngine {
+        &self.execution
+    }
+
+    pub fn execution_mut(&mut self) -> &mut ExecutionEngine {
+        &mut self.execution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use async_trait::async_trait;
+    use rust_decimal::Decimal;
+    use crate::risk::PortfolioLimits;
+    use crate::strategy::MeanReversionStrategy;
+    use crate::engine::EventKind;
+    use crate::market_data::{CandleAggregator, CandleAggregatorConfig, FileFetcher, MarketDataRecorder, RecorderConfig, Recording};
+
+    struct StepFetcher {
+        calls: AtomicU64,
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for StepFetcher {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            PriceEvent::new(symbol.to_string(), Decimal::from(100 + n), Decimal::from(10))
+        }
+
+        fn exchange_name(&self) -> &str {
+            "Step"
+        }
+    }
+
+    struct AlwaysBuy;
+
+    impl Strategy for AlwaysBuy {
+        fn signal(&self, _event: &PriceEvent) -> Result<Signal> {
+            Ok(Signal::Buy)
+        }
+
+        fn name(&self) -> &str {
+            "AlwaysBuy"
+        }
+
+        fn get_risk_params(&self, current_price: Decimal) -> Result<(Decimal, Decimal, Decimal)> {
+            Ok((current_price, current_price / Decimal::from(50), Decimal::ONE))
+        }
+    }
+
+    fn build_loop_from(
+        config: EventLoopConfig,
+        bus: EventBus,
+        fetcher: Box<dyn MarketDataFetcher>,
+        strategy: Box<dyn Strategy>,
+    ) -> EventLoop {
+        let limits = PortfolioLimits::new(
+            Decimal::from(1000),
+            Decimal::from(50000),
+            Decimal::from(10),
+            5,
+        ).unwrap();
+        let execution = ExecutionEngine::new(Decimal::from(10000), limits, bus.clone()).unwrap();
+        let mut event_loop = EventLoop::new(config, bus, fetcher, execution).unwrap();
+        event_loop.add_strategy("BTCUSDT", strategy);
+        event_loop
+    }
+
+    fn build_loop_with(config: EventLoopConfig, bus: EventBus, strategy: Box<dyn Strategy>) -> EventLoop {
+        let fetcher = Box::new(StepFetcher { calls: AtomicU64::new(0) });
+        build_loop_from(config, bus, fetcher, strategy)
+    }
+
+    fn build_loop(config: EventLoopConfig, bus: EventBus) -> EventLoop {
+        let strategy = MeanReversionStrategy::new(Decimal::new(2, 2), 5, Decimal::from(2)).unwrap();
+        build_loop_with(config, bus, Box::new(strategy))
+    }
+
+    #[tokio::test]
+    async fn test_runs_until_max_ticks() {
+        let bus = EventBus::new();
+        let signals = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&signals);
+        let _subscription = bus.subscribe(EventKind::SignalGenerated, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+
+        let config = EventLoopConfig {
+            tick_interval: Duration::from_millis(1),
+            data_quality: DataQualityConfig::new(60_000),
+            max_ticks: Some(3),
+            ..EventLoopConfig::default()
+        };
+        let mut event_loop = build_loop(config, bus);
+        event_loop.run().await.unwrap();
+
+        assert_eq!(event_loop.ticks(), 3);
+        assert_eq!(signals.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_handle_stops_loop() {
+        let config = EventLoopConfig {
+            tick_interval: Duration::from_millis(5),
+            ..EventLoopConfig::default()
+        };
+        let mut event_loop = build_loop(config, EventBus::new());
+        let handle = event_loop.shutdown_handle();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            handle.shutdown();
+        });
+
+        tokio::time::timeout(Duration::from_secs(2), event_loop.run())
+            .await
+            .expect("loop did not shut down")
+            .unwrap();
+        assert!(event_loop.ticks() >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_stops_when_replay_is_exhausted() {
+        let bus = EventBus::new();
+        let events = (1..=3)
+            .map(|i| PriceEvent::new("BTCUSDT".to_string(), Decimal::from(100 + i), Decimal::ONE).unwrap())
+            .collect();
+        let fetcher = Box::new(FileFetcher::new(events));
+        let config = EventLoopConfig { tick_interval: Duration::from_millis(1), ..EventLoopConfig::default() };
+        let strategy = MeanReversionStrategy::new(Decimal::new(2, 2), 5, Decimal::from(2)).unwrap();
+        let mut event_loop = build_loop_from(config, bus.clone(), fetcher, Box::new(strategy));
+
+        tokio::time::timeout(Duration::from_secs(2), event_loop.run())
+            .await
+            .expect("loop did not stop at the end of the data")
+            .unwrap();
+        assert!(event_loop.is_exhausted());
+        assert_eq!(event_loop.ticks(), 4);
+        assert_eq!(bus.metrics_snapshot().get("Error"), None);
+    }
+
+    #[tokio::test]
+    async fn test_quarantined_price_reaches_no_subscriber() {
+        let bus = EventBus::new();
+        let dir = std::env::temp_dir().join(format!("event-loop-quarantine-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let recorder = MarketDataRecorder::start(RecorderConfig::new(&dir), &bus).unwrap();
+        let _candles = CandleAggregator::new(CandleAggregatorConfig::default()).unwrap().attach(&bus).unwrap();
+        let closed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&closed);
+        bus.subscribe(EventKind::CandleClosed, move |envelope| {
+            if let Event::CandleClosed(candle) = &envelope.event {
+                sink.lock().unwrap().push(candle.clone());
+            }
+        }).unwrap().detach();
+
+        // A 50% spike inside the first minute, then a print that closes the bar
+        let minute = 1_700_000_040_000;
+        let events = [(100, minute), (150, minute + 1_000), (101, minute + 62_000)]
+            .into_iter()
+            .map(|(price, timestamp)| {
+                PriceEvent::new("BTCUSDT".to_string(), Decimal::from(price), Decimal::ONE)
+                    .unwrap()
+                    .with_exchange_time(Some(timestamp))
+            })
+            .collect();
+        let config = EventLoopConfig {
+            tick_interval: Duration::from_millis(1),
+            data_quality: DataQualityConfig { max_staleness_ms: None, ..DataQualityConfig::new(120_000) },
+            ..EventLoopConfig::default()
+        };
+        let strategy = MeanReversionStrategy::new(Decimal::new(2, 2), 5, Decimal::from(2)).unwrap();
+        let fetcher = Box::new(FileFetcher::new(events));
+        let mut event_loop = build_loop_from(config, bus.clone(), fetcher, Box::new(strategy));
+        event_loop.run().await.unwrap();
+        drop(recorder);
+
+        let counts = bus.metrics_snapshot();
+        assert_eq!(counts.get("DataQuality"), Some(&1));
+        assert_eq!(counts.get("PriceUpdated"), Some(&2));
+
+        let closed = closed.lock().unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!((closed[0].high, closed[0].close), (Decimal::from(100), Decimal::from(100)));
+
+        let recorded = Recording::open(&dir).unwrap().price_events(&[], 0, u64::MAX).unwrap();
+        let prices: Vec<Decimal> = recorded.iter().map(|event| event.price).collect();
+        assert_eq!(prices, vec![Decimal::from(100), Decimal::from(101)]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_fill_traces_back_to_price() {
+        let bus = EventBus::new();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut subscriptions = Vec::new();
+        for kind in [EventKind::PriceUpdated, EventKind::SignalGenerated, EventKind::OrderFilled] {
+            let seen = Arc::clone(&seen);
+            subscriptions.push(bus.subscribe(kind, move |envelope| {
+                seen.lock().unwrap().push(envelope.clone());
+            }).unwrap());
+        }
+
+        let mut event_loop = build_loop_with(EventLoopConfig::default(), bus, Box::new(AlwaysBuy));
+        event_loop.tick().await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        let price = seen.iter().find(|e| e.kind() == EventKind::PriceUpdated).unwrap();
+        let signal = seen.iter().find(|e| e.kind() == EventKind::SignalGenerated).unwrap();
+        let fill = seen.iter().find(|e| e.kind() == EventKind::OrderFilled).unwrap();
+
+        assert_eq!(signal.source, "AlwaysBuy");
+        assert_eq!(signal.causation_id, Some(price.sequence));
+        assert_eq!(fill.source, "ExecutionEngine");
+        assert_eq!(fill.correlation_id, Some(price.sequence));
+        assert_eq!(fill.causation_id, Some(signal.sequence));
+    }
+}
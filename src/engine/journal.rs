@@ -0,0 +1,161 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::error::{Result, TradingError};
+use super::bus::EventBus;
+use super::envelope::EventEnvelope;
+
+/// Append-only, line-delimited JSON log of every envelope published on a bus
+pub struct EventJournal {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl EventJournal {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write one envelope and flush it to the OS before returning
+    pub fn append(&self, envelope: &EventEnvelope) -> Result<()> {
+        let line = serde_json::to_string(envelope)?;
+        let mut writer = self.writer.lock().map_err(|_| {
+            TradingError::EventBus("Journal writer lock poisoned".to_string())
+        })?;
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read every envelope from a journal file, ordered by sequence number.
+    /// A torn final line (crash mid-write) is ignored; corruption elsewhere is an error.
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<EventEnvelope>> {
+        let reader = BufReader::new(File::open(path.as_ref())?);
+        let lines: Vec<String> = reader.lines().collect::<std::io::Result<_>>()?;
+
+        let mut envelopes = Vec::with_capacity(lines.len());
+        for (index, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<EventEnvelope>(line) {
+                Ok(envelope) => envelopes.push(envelope),
+                Err(_) if index + 1 == lines.len() => break,
+                Err(err) => {
+                    return Err(TradingError::EventBus(format!(
+                        "Corrupt journal entry at line {}: {}",
+                        index + 1,
+                        err
+                    )));
+                }
+            }
+        }
+
+        envelopes.sort_by_key(|envelope| envelope.sequence);
+        Ok(envelopes)
+    }
+}
+
+/// Pacing used when replaying a journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    /// Sleep between events to reproduce the recorded publish timestamps
+    Original,
+    /// Deliver events back to back
+    AsFastAsPossible,
+}
+
+/// Replay a journal file into `bus`, keeping each envelope's original metadata.
+/// Returns the number of events delivered.
+pub async fn replay(path: impl AsRef<Path>, bus: &EventBus, speed: ReplaySpeed) -> Result<usize> {
+    let envelopes = EventJournal::read(path)?;
+    let mut previous_ts: Option<u64> = None;
+
+    for envelope in &envelopes {
+        if speed == ReplaySpeed::Original {
+            if let Some(previous) = previous_ts {
+                let wait = envelope.timestamp.saturating_sub(previous);
+                if wait > 0 {
+                    tokio::time::sleep(Duration::from_millis(wait)).await;
+                }
+            }
+            previous_ts = Some(envelope.timestamp);
+        }
+        bus.replay_envelope(envelope.clone())?;
+    }
+
+    Ok(envelopes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::engine::{Causation, Event, EventKind};
+
+    fn temp_journal(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("journal-{}-{}-{}.jsonl", name, std::process::id(), nanos))
+    }
+
+    #[tokio::test]
+    async fn test_journal_roundtrip_and_replay() {
+        let path = temp_journal("roundtrip");
+        let bus = EventBus::new();
+        bus.attach_journal(Arc::new(EventJournal::open(&path).unwrap()));
+
+        let root = bus.publish_from("feed", Event::Error("first".to_string()), None).unwrap();
+        bus.publish_from("risk", Event::RiskHalt { reason: "halt".to_string() }, Some(Causation::root(root))).unwrap();
+
+        let envelopes = EventJournal::read(&path).unwrap();
+        assert_eq!(envelopes.len(), 2);
+        assert_eq!(envelopes[1].source, "risk");
+        assert_eq!(envelopes[1].causation_id, Some(root));
+
+        let fresh = EventBus::new();
+        let delivered = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&delivered);
+        let _subscription = fresh.subscribe(EventKind::RiskHalt, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+
+        let replayed = replay(&path, &fresh, ReplaySpeed::AsFastAsPossible).await.unwrap();
+        assert_eq!(replayed, 2);
+        assert_eq!(delivered.load(Ordering::SeqCst), 1);
+        // New events continue after the replayed sequence numbers
+        assert!(fresh.publish(Event::Error("after".to_string())).unwrap() > envelopes[1].sequence);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_torn_final_line_is_ignored() {
+        let path = temp_journal("torn");
+        let bus = EventBus::new();
+        bus.attach_journal(Arc::new(EventJournal::open(&path).unwrap()));
+        bus.publish(Event::Error("ok".to_string())).unwrap();
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"sequence\":2,\"times").unwrap();
+
+        assert_eq!(EventJournal::read(&path).unwrap().len(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use super::event::{Event, EventKind};
+use super::envelope::EventEnvelope;
+
+/// Power-of-two microsecond buckets: bucket `i` holds samples below 2^i us (last one is open)
+const BUCKETS: usize = 40;
+
+/// Seconds covered by the events-per-second rate
+const RATE_WINDOW_SECS: u64 = 10;
+
+/// Price events remembered for end-to-end latency matching
+const MAX_TRACKED_PRICES: usize = 10_000;
+
+/// Lock-free latency histogram with exponential buckets
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, latency: Duration) {
+        let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = ((u64::BITS - micros.leading_zeros()) as usize).min(BUCKETS - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(micros, Ordering::Relaxed);
+        self.max_us.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let counts: Vec<u64> = self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect();
+        let count = self.count.load(Ordering::Relaxed);
+        let max_us = self.max_us.load(Ordering::Relaxed);
+        if count == 0 {
+            return LatencySnapshot::default();
+        }
+
+        // Upper bound of the bucket holding the requested rank, capped by the observed max
+        let percentile = |pct: u64| {
+            let rank = (count * pct).div_ceil(100).max(1);
+            let mut seen = 0;
+            for (bucket, n) in counts.iter().enumerate() {
+                seen += n;
+                if seen >= rank {
+                    let upper = if bucket == 0 { 0 } else { 1u64 << bucket };
+                    return Duration::from_micros(upper.min(max_us));
+                }
+            }
+            Duration::from_micros(max_us)
+        };
+
+        let sum_us = self.sum_us.load(Ordering::Relaxed);
+        LatencySnapshot {
+            count,
+            sum: Duration::from_micros(sum_us),
+            mean: Duration::from_micros(sum_us / count),
+            p50: percentile(50),
+            p99: percentile(99),
+            max: Duration::from_micros(max_us),
+        }
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Point-in-time view of a latency histogram
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencySnapshot {
+    pub count: u64,
+    pub sum: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl fmt::Display for LatencySnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} p50={:?} p99={:?} max={:?}",
+            self.count, self.p50, self.p99, self.max
+        )
+    }
+}
+
+/// Events per second over the last `RATE_WINDOW_SECS` seconds
+#[derive(Debug)]
+struct RateMeter {
+    /// (second since start, events in that second)
+    slots: Mutex<[(u64, u64); RATE_WINDOW_SECS as usize]>,
+}
+
+impl RateMeter {
+    fn new() -> Self {
+        Self {
+            slots: Mutex::new([(u64::MAX, 0); RATE_WINDOW_SECS as usize]),
+        }
+    }
+
+    fn record(&self, second: u64) {
+        if let Ok(mut slots) = self.slots.lock() {
+            let slot = &mut slots[(second % RATE_WINDOW_SECS) as usize];
+            if slot.0 != second {
+                *slot = (second, 0);
+            }
+            slot.1 += 1;
+        }
+    }
+
+    /// `elapsed` is the bus uptime; young buses are averaged over their lifetime
+    fn rate(&self, elapsed: Duration) -> f64 {
+        let now = elapsed.as_secs();
+        let events: u64 = self.slots.lock()
+            .map(|slots| {
+                slots.iter()
+                    .filter(|(second, _)| *second <= now && now - second < RATE_WINDOW_SECS)
+                    .map(|(_, n)| n)
+                    .sum()
+            })
+            .unwrap_or(0);
+        let window = elapsed.as_secs_f64().clamp(1.0, RATE_WINDOW_SECS as f64);
+        events as f64 / window
+    }
+}
+
+/// Counters for one event kind, shared with the subscribers of that kind
+#[derive(Debug)]
+pub(crate) struct KindStats {
+    published: AtomicU64,
+    rate: RateMeter,
+    /// Execution time of every handler run for this kind
+    pub(crate) handler_latency: LatencyHistogram,
+}
+
+impl KindStats {
+    fn new() -> Self {
+        Self {
+            published: AtomicU64::new(0),
+            rate: RateMeter::new(),
+            handler_latency: LatencyHistogram::new(),
+        }
+    }
+}
+
+/// Bus-wide metrics registry
+#[derive(Debug)]
+pub(crate) struct BusMetrics {
+    started: Instant,
+    kinds: HashMap<EventKind, Arc<KindStats>>,
+    /// Sequence -> `PriceEvent.received_at` of recent `PriceUpdated` events
+    prices: Mutex<(HashMap<u64, u64>, VecDeque<u64>)>,
+    end_to_end: LatencyHistogram,
+    feed_latency: LatencyHistogram,
+}
+
+impl BusMetrics {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            kinds: EventKind::ALL
+                .iter()
+                .map(|kind| (*kind, Arc::new(KindStats::new())))
+                .collect(),
+            prices: Mutex::new((HashMap::new(), VecDeque::new())),
+            end_to_end: LatencyHistogram::new(),
+            feed_latency: LatencyHistogram::new(),
+        }
+    }
+
+    pub(crate) fn kind(&self, kind: EventKind) -> Arc<KindStats> {
+        Arc::clone(&self.kinds[&kind])
+    }
+
+    /// Count a published event and match trades back to the price that triggered them
+    pub(crate) fn record_publish(&self, envelope: &EventEnvelope) {
+        let stats = &self.kinds[&envelope.kind()];
+        stats.published.fetch_add(1, Ordering::Relaxed);
+        stats.rate.record(self.started.elapsed().as_secs());
+
+        match &envelope.event {
+            Event::PriceUpdated(price) => {
+                // Journals written before `received_at` existed deserialize it as 0
+                let received_at = if price.received_at > 0 { price.received_at } else { price.timestamp };
+                if price.received_at > 0 {
+                    self.feed_latency.record(Duration::from_millis(price.latency_ms()));
+                }
+                if let Ok(mut guard) = self.prices.lock() {
+                    let (timestamps, order) = &mut *guard;
+                    if order.len() >= MAX_TRACKED_PRICES {
+                        if let Some(oldest) = order.pop_front() {
+                            timestamps.remove(&oldest);
+                        }
+                    }
+                    timestamps.insert(envelope.sequence, received_at);
+                    order.push_back(envelope.sequence);
+                }
+            }
+            Event::TradeExecuted { .. } => {
+                let price_ts = envelope.correlation_id.and_then(|root| {
+                    self.prices.lock().ok().and_then(|guard| guard.0.get(&root).copied())
+                });
+                if let Some(price_ts) = price_ts {
+                    self.end_to_end.record(Duration::from_millis(envelope.timestamp.saturating_sub(price_ts)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn snapshot(&self, subscribers: Vec<SubscriberMetrics>) -> BusMetricsSnapshot {
+        let uptime = self.started.elapsed();
+        let event_types = self.kinds
+            .iter()
+            .filter(|(_, stats)| stats.published.load(Ordering::Relaxed) > 0)
+            .map(|(kind, stats)| {
+                (kind.as_str().to_string(), EventTypeMetrics {
+                    published: stats.published.load(Ordering::Relaxed),
+                    events_per_sec: stats.rate.rate(uptime),
+                    handler_latency: stats.handler_latency.snapshot(),
+                })
+            })
+            .collect();
+
+        BusMetricsSnapshot {
+            uptime,
+            event_types,
+            subscribers,
+            end_to_end: self.end_to_end.snapshot(),
+            feed_latency: self.feed_latency.snapshot(),
+        }
+    }
+}
+
+/// Metrics for one event type
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventTypeMetrics {
+    pub published: u64,
+    /// Publish rate over the last 10 seconds
+    pub events_per_sec: f64,
+    /// Handler execution time across all subscribers of this type
+    pub handler_latency: LatencySnapshot,
+}
+
+/// Metrics for one subscriber
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriberMetrics {
+    pub id: u64,
+    pub kind: EventKind,
+    pub handler_latency: LatencySnapshot,
+    /// Events queued but not yet handled (always 0 in sync mode)
+    pub queue_depth: usize,
+    pub dropped: u64,
+    pub failures: u32,
+}
+
+/// Snapshot returned by `EventBus::metrics`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusMetricsSnapshot {
+    pub uptime: Duration,
+    /// Keyed by event type name; types never published are omitted
+    pub event_types: BTreeMap<String, EventTypeMetrics>,
+    /// Ordered by subscriber id
+    pub subscribers: Vec<SubscriberMetrics>,
+    /// From `PriceEvent.received_at` to the `TradeExecuted` it led to
+    pub end_to_end: LatencySnapshot,
+    /// Exchange event time to local receipt of price updates
+    pub feed_latency: LatencySnapshot,
+}
+
+impl fmt::Display for BusMetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Uptime: {:?}", self.uptime)?;
+        for (name, metrics) in &self.event_types {
+            writeln!(
+                f,
+                "  {}: {} published, {:.2}/s, handlers {}",
+                name, metrics.published, metrics.events_per_sec, metrics.handler_latency
+            )?;
+        }
+        for sub in &self.subscribers {
+            writeln!(
+                f,
+                "  subscriber #{} ({}): depth {}, dropped {}, failures {}, {}",
+                sub.id, sub.kind, sub.queue_depth, sub.dropped, sub.failures, sub.handler_latency
+            )?;
+        }
+        writeln!(f, "  exchange -> received: {}", self.feed_latency)?;
+        write!(f, "  price -> trade: {}", self.end_to_end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_percentiles() {
+        let histogram = LatencyHistogram::new();
+        for _ in 0..98 {
+            histogram.record(Duration::from_micros(10));
+        }
+        histogram.record(Duration::from_millis(5));
+        histogram.record(Duration::from_millis(50));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count, 100);
+        assert_eq!(snapshot.p50, Duration::from_micros(16));
+        assert!(snapshot.p99 >= Duration::from_millis(5));
+        assert!(snapshot.p99 < Duration::from_millis(50));
+        assert_eq!(snapshot.max, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_empty_histogram() {
+        assert_eq!(LatencyHistogram::new().snapshot(), LatencySnapshot::default());
+    }
+
+    #[test]
+    fn test_rate_meter_window() {
+        let meter = RateMeter::new();
+        for second in 0..20 {
+            meter.record(second);
+            meter.record(second);
+        }
+        assert_eq!(meter.rate(Duration::from_secs(19)), 2.0);
+        assert_eq!(meter.rate(Duration::from_secs(40)), 0.0);
+    }
+}
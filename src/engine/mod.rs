@@ -1,6 +1,14 @@
+// Event loop implementation
 pub mod event_loop;
 pub mod event;
 pub mod bus;
+pub mod envelope;
+pub mod journal;
+pub mod metrics;
 
-pub use event::Event;
-pub use bus::EventBus;
\ No newline at end of file
+pub use event::{Event, EventKind};
+pub use envelope::{Causation, EventEnvelope};
+pub use journal::{EventJournal, ReplaySpeed};
+pub use metrics::{BusMetricsSnapshot, EventTypeMetrics, LatencyHistogram, LatencySnapshot, SubscriberMetrics};
+pub use bus::{EventBus, EventBusConfig, DeadLetter, DispatchMode, OverflowPolicy, Subscription};
+pub use event_loop::{EventLoop, EventLoopConfig, ShutdownHandle};
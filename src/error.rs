@@ -10,12 +10,32 @@ pub enum TradingError {
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
 
+    #[error("HTTP error {0}: {1}")]
+    Http(u16, String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    #[error("Circuit open: {0}")]
+    CircuitOpen(String),
+
+    /// A replayed feed has no more data; terminal, the event loop stops on it
+    #[error("End of data: {0}")]
+    EndOfData(String),
+
     #[error("Validation error: {0}")]
     Validation(String),
 
     #[error("Execution error: {0}")]
     Execution(String),
 
+    /// Trade refused by pre-trade risk checks; already published as `Event::Error`
+    #[error("Trade rejected: {0}")]
+    Rejected(String),
+
     #[error("Decimal conversion error: {0}")]
     Decimal(#[from] rust_decimal::Error),
 
@@ -44,6 +64,18 @@ pub enum TradingError {
     Env(#[from] std::env::VarError),
 }
 
+impl TradingError {
+    /// Transient failures worth retrying: transport errors, timeouts,
+    /// rate limits and 5xx responses
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TradingError::Network(_) | TradingError::Timeout(_) | TradingError::RateLimited(_) => true,
+            TradingError::Http(status, _) => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
 /// Result type alias for convenience
 pub type Result<T> = std::result::Result<T, TradingError>;
 
@@ -89,6 +121,14 @@ mod tests {
         assert_eq!(error.to_string(), "Risk management error: Position too large");
     }
 
+    #[test]
+    fn test_retryable_errors() {
+        assert!(TradingError::Http(503, "Service Unavailable".to_string()).is_retryable());
+        assert!(TradingError::RateLimited("slow down".to_string()).is_retryable());
+        assert!(!TradingError::Http(400, "Invalid symbol.".to_string()).is_retryable());
+        assert!(!TradingError::CircuitOpen("Binance".to_string()).is_retryable());
+    }
+
     #[test]
     fn test_error_is_send_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
@@ -1,13 +1,20 @@
 use rust_decimal::Decimal;
 use crate::error::{Result, TradingError};
-use crate::engine::{EventBus, Event};
+use crate::engine::{EventBus, Event, Causation, EventEnvelope, EventJournal};
 use crate::risk::{PositionSizer, StopLossManager, PortfolioLimits, RiskEngine};
 use crate::portfolio::position::PositionSide;
+use crate::market_data::event::Quote;
+use crate::instrument::{Instrument, InstrumentRegistry};
 use std::collections::HashMap;
+use std::path::Path;
+use tracing::{info, instrument, warn};
 use super::order::{Order, OrderSide, OrderType, OrderStatus, TimeInForce};
 use super::fill::{Fill, FillSimulator};
 use crate::strategy::Signal;
 
+/// Source name stamped on events published by the execution engine
+const SOURCE: &str = "ExecutionEngine";
+
 /// Trade execution record
 #[derive(Debug, Clone)]
 pub struct Trade {
@@ -19,6 +26,20 @@ pub struct Trade {
     pub timestamp: u64,
 }
 
+/// Point-in-time view of execution and risk state, for exporters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionMetrics {
+    /// Order count for every status in `OrderStatus::ALL` order
+    pub orders_by_status: Vec<(OrderStatus, usize)>,
+    pub fills: usize,
+    pub trades: usize,
+    pub balance: Decimal,
+    pub equity: Decimal,
+    pub open_positions: usize,
+    pub daily_loss: Decimal,
+    pub kill_switch_active: bool,
+}
+
 /// Paper trading execution engine with risk management
 pub struct ExecutionEngine {
     risk_engine: RiskEngine,
@@ -27,6 +48,10 @@ pub struct ExecutionEngine {
     orders: HashMap<u64, Order>,
     fills: Vec<Fill>,
     next_order_id: u64,
+    /// Latest top of book per symbol; market orders cross this spread
+    quotes: HashMap<String, Quote>,
+    /// Lot size, tick and notional rules; unlisted symbols are not checked
+    instruments: InstrumentRegistry,
 }
 
 impl ExecutionEngine {
@@ -43,16 +68,30 @@ impl ExecutionEngine {
             orders: HashMap::new(),
             fills: Vec::new(),
             next_order_id: 1,
+            quotes: HashMap::new(),
+            instruments: InstrumentRegistry::new(),
         })
     }
 
-    /// Execute trade with risk management checks
+    /// Size, round and validate orders by these instrument rules
+    pub fn set_instruments(&mut self, instruments: InstrumentRegistry) {
+        self.instruments = instruments;
+    }
+
+    pub fn instruments(&self) -> &InstrumentRegistry {
+        &self.instruments
+    }
+
+    /// Execute trade with risk management checks.
+    /// `cause` links every event published for this trade to the triggering signal.
+    #[instrument(skip(self, cause), fields(symbol = %symbol))]
     pub fn execute(
         &mut self,
         symbol: String,
         signal: Signal,
         entry_price: Decimal,
         stop_loss_distance: Decimal,
+        cause: Option<Causation>,
     ) -> Result<Option<Trade>> {
         let side = match signal {
             Signal::Buy => PositionSide::Long,
@@ -65,12 +104,21 @@ impl ExecutionEngine {
             Signal::Hold => return Ok(None),
         };
 
-        // Calculate position size using risk management
-        let position_size = PositionSizer::calculate(
-            self.risk_engine.account_balance(),
-            Decimal::from(2), // 2% risk per trade
-            stop_loss_distance,
-        )?;
+        // Calculate position size using risk management, 2% risk per trade
+        let position_size = match self.instruments.get(&symbol) {
+            Some(instrument) => PositionSizer::calculate_for(
+                instrument,
+                self.risk_engine.account_balance(),
+                Decimal::from(2),
+                stop_loss_distance,
+                entry_price,
+            ),
+            None => PositionSizer::calculate(
+                self.risk_engine.account_balance(),
+                Decimal::from(2),
+                stop_loss_distance,
+            ),
+        }?;
 
         // Pre-trade risk validation (limits, margin, daily loss, kill-switch)
         if let Err(err) = self.risk_engine.pre_trade_validate(
@@ -81,15 +129,16 @@ impl ExecutionEngine {
             stop_loss_distance,
         ) {
             let err_msg = err.to_string();
+            warn!(error = %err_msg, "Trade rejected by pre-trade risk checks");
             if self.risk_engine.is_kill_switch_active() {
                 if let Some(reason) = self.risk_engine.kill_switch_reason() {
-                    let _ = self.event_bus.publish(Event::RiskHalt {
+                    let _ = self.event_bus.publish_from(SOURCE, Event::RiskHalt {
                         reason: reason.to_string(),
-                    });
+                    }, cause);
                 }
             }
-            self.event_bus.publish(Event::Error(err_msg.clone()))?;
-            return Err(TradingError::Execution(err_msg));
+            self.event_bus.publish_from(SOURCE, Event::Error(err_msg.clone()), cause)?;
+            return Err(TradingError::Rejected(err_msg));
         }
 
         // Calculate stop loss
@@ -107,11 +156,13 @@ impl ExecutionEngine {
             TimeInForce::Ioc,
             position_size,
             Some(entry_price),
+            cause,
         )?;
 
-        self.process_fills(order_id, entry_price, stop_loss, side, signal)
+        self.process_fills(order_id, entry_price, stop_loss, side, signal, cause)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn submit_order(
         &mut self,
         symbol: String,
@@ -120,12 +171,18 @@ impl ExecutionEngine {
         tif: TimeInForce,
         quantity: Decimal,
         price: Option<Decimal>,
+        cause: Option<Causation>,
     ) -> Result<u64> {
         if quantity <= Decimal::ZERO {
             return Err(TradingError::Validation(
                 "Order quantity must be positive".to_string(),
             ));
         }
+        if let Some(instrument) = self.instruments.get(&symbol) {
+            // Market orders are valued at their reference price, else the quote mid
+            let reference = price.or_else(|| self.quotes.get(&symbol).map(Quote::mid));
+            Self::validate_order(instrument, order_type, quantity, price, reference)?;
+        }
 
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -150,19 +207,22 @@ impl ExecutionEngine {
         };
 
         self.orders.insert(order_id, order);
+        info!(order_id, symbol = %symbol, ?side, ?order_type, %quantity, ?price, "Order submitted");
 
         let signal = match side {
             OrderSide::Buy => Signal::Buy,
             OrderSide::Sell => Signal::Sell,
         };
 
-        self.event_bus.publish(Event::OrderSubmitted {
+        self.event_bus.publish_from(SOURCE, Event::OrderSubmitted {
             order_id,
             symbol,
             side: signal,
+            order_type,
+            tif,
             quantity,
             price,
-        })?;
+        }, cause)?;
 
         Ok(order_id)
     }
@@ -179,10 +239,11 @@ impl ExecutionEngine {
         }
 
         order.status = OrderStatus::Cancelled;
-        self.event_bus.publish(Event::OrderCancelled {
+        info!(order_id, symbol = %order.symbol, "Order cancelled");
+        self.event_bus.publish_from(SOURCE, Event::OrderCancelled {
             order_id,
             symbol: order.symbol.clone(),
-        })?;
+        }, None)?;
         Ok(())
     }
 
@@ -202,6 +263,10 @@ impl ExecutionEngine {
                 "Order already closed".to_string(),
             ));
         }
+        if let Some(instrument) = self.instruments.get(&order.symbol) {
+            let reference = new_price.or_else(|| self.quotes.get(&order.symbol).map(Quote::mid));
+            Self::validate_order(instrument, order.order_type, new_qty, new_price, reference)?;
+        }
 
         order.quantity = new_qty;
         order.price = new_price;
@@ -212,6 +277,24 @@ impl ExecutionEngine {
         Ok(())
     }
 
+    /// Lot size, limit price tick and minimum notional checks
+    fn validate_order(
+        instrument: &Instrument,
+        order_type: OrderType,
+        quantity: Decimal,
+        price: Option<Decimal>,
+        reference: Option<Decimal>,
+    ) -> Result<()> {
+        instrument.validate_qty(quantity)?;
+        if let (OrderType::Limit, Some(price)) = (order_type, price) {
+            instrument.validate_price(price)?;
+        }
+        if let Some(reference) = reference {
+            instrument.validate_notional(quantity, reference)?;
+        }
+        Ok(())
+    }
+
     fn process_fills(
         &mut self,
         order_id: u64,
@@ -219,28 +302,31 @@ impl ExecutionEngine {
         stop_loss: Decimal,
         side: PositionSide,
         signal: Signal,
+        cause: Option<Causation>,
     ) -> Result<Option<Trade>> {
         let order = self.orders.get(&order_id).ok_or_else(|| {
             TradingError::Execution("Order not found".to_string())
         })?;
 
+        let fill_price = FillSimulator::execution_price(order.side, entry_price, self.quotes.get(&order.symbol));
         let fills = FillSimulator::simulate(
             order_id,
             &order.symbol,
-            entry_price,
+            fill_price,
             order.quantity,
         );
 
         let mut filled_qty = Decimal::ZERO;
         for fill in &fills {
             filled_qty += fill.quantity;
+            info!(order_id, symbol = %fill.symbol, quantity = %fill.quantity, price = %fill.price, "Order filled");
             self.fills.push(fill.clone());
-            self.event_bus.publish(Event::OrderFilled {
+            self.event_bus.publish_from(SOURCE, Event::OrderFilled {
                 order_id,
                 symbol: fill.symbol.clone(),
                 filled_qty: fill.quantity,
                 price: fill.price,
-            })?;
+            }, cause)?;
         }
 
         let order = self.orders.get_mut(&order_id).ok_or_else(|| {
@@ -262,7 +348,7 @@ impl ExecutionEngine {
             let trade = Trade {
                 symbol: order.symbol.clone(),
                 signal,
-                entry_price,
+                entry_price: fill_price,
                 position_size: filled_qty,
                 stop_loss,
                 timestamp,
@@ -272,19 +358,19 @@ impl ExecutionEngine {
             self.risk_engine.record_trade_open(
                 order.symbol.clone(),
                 side,
-                entry_price,
+                fill_price,
                 filled_qty,
                 stop_loss,
                 timestamp,
             )?;
 
-            self.event_bus.publish(Event::TradeExecuted {
+            self.event_bus.publish_from(SOURCE, Event::TradeExecuted {
                 symbol: order.symbol.clone(),
                 signal,
-                entry_price,
+                entry_price: fill_price,
                 position_size: filled_qty,
                 stop_loss,
-            })?;
+            }, cause)?;
 
             return Ok(Some(trade));
         }
@@ -298,14 +384,23 @@ impl ExecutionEngine {
         StopLossManager::is_stop_hit(current_price, trade.stop_loss, is_long)
     }
 
+    /// Record the latest bid/ask for `symbol`
+    pub fn update_quote(&mut self, symbol: &str, quote: Quote) {
+        self.quotes.insert(symbol.to_string(), quote);
+    }
+
+    pub fn quote(&self, symbol: &str) -> Option<&Quote> {
+        self.quotes.get(symbol)
+    }
+
     /// Update market price for risk monitoring
     pub fn update_price(&mut self, symbol: &str, price: Decimal) -> Result<()> {
         self.risk_engine.update_price(symbol, price)?;
         if self.risk_engine.is_kill_switch_active() {
             if let Some(reason) = self.risk_engine.kill_switch_reason() {
-                self.event_bus.publish(Event::RiskHalt {
+                self.event_bus.publish_from(SOURCE, Event::RiskHalt {
                     reason: reason.to_string(),
-                })?;
+                }, None)?;
             }
             self.liquidate_all()?;
         }
@@ -324,11 +419,132 @@ impl ExecutionEngine {
     fn liquidate_all(&mut self) -> Result<()> {
         let results = self.risk_engine.liquidate_all();
         for (symbol, exit_price, pnl) in results {
-            self.event_bus.publish(Event::TradeClosed {
+            warn!(symbol = %symbol, %exit_price, %pnl, "Position liquidated");
+            self.event_bus.publish_from(SOURCE, Event::TradeClosed {
                 symbol,
                 exit_price,
                 pnl,
-            })?;
+            }, None)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild an engine from a journal written by a previous session
+    pub fn recover(
+        initial_balance: Decimal,
+        portfolio_limits: PortfolioLimits,
+        event_bus: EventBus,
+        journal_path: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let mut engine = Self::new(initial_balance, portfolio_limits, event_bus)?;
+        for envelope in EventJournal::read(journal_path)? {
+            engine.apply_journal_entry(&envelope)?;
+        }
+        Ok(engine)
+    }
+
+    /// Apply a journaled event to the engine state without publishing anything
+    pub fn apply_journal_entry(&mut self, envelope: &EventEnvelope) -> Result<()> {
+        match &envelope.event {
+            Event::PriceUpdated(price_event) => {
+                self.risk_engine.update_price(&price_event.symbol, price_event.price)?;
+                if let Some(quote) = price_event.quote {
+                    self.update_quote(&price_event.symbol, quote);
+                }
+            }
+            Event::OrderSubmitted { order_id, symbol, side, order_type, tif, quantity, price } => {
+                let side = match side {
+                    Signal::Buy => OrderSide::Buy,
+                    Signal::Sell => OrderSide::Sell,
+                    Signal::Hold => return Err(TradingError::Execution(
+                        format!("Journaled order {} has no side", order_id),
+                    )),
+                };
+                self.orders.insert(*order_id, Order {
+                    id: *order_id,
+                    symbol: symbol.clone(),
+                    side,
+                    order_type: *order_type,
+                    tif: *tif,
+                    quantity: *quantity,
+                    price: *price,
+                    filled_quantity: Decimal::ZERO,
+                    status: OrderStatus::New,
+                    created_at: envelope.timestamp,
+                    updated_at: envelope.timestamp,
+                });
+                self.next_order_id = self.next_order_id.max(order_id + 1);
+            }
+            Event::OrderFilled { order_id, symbol, filled_qty, price } => {
+                self.fills.push(Fill {
+                    order_id: *order_id,
+                    symbol: symbol.clone(),
+                    price: *price,
+                    quantity: *filled_qty,
+                    fee: FillSimulator::fee(*price, *filled_qty),
+                    timestamp: envelope.timestamp,
+                });
+                if let Some(order) = self.orders.get_mut(order_id) {
+                    order.filled_quantity += *filled_qty;
+                    order.updated_at = envelope.timestamp;
+                    order.status = if order.filled_quantity >= order.quantity {
+                        OrderStatus::Filled
+                    } else {
+                        OrderStatus::PartiallyFilled
+                    };
+                }
+            }
+            Event::OrderCancelled { order_id, .. } => {
+                if let Some(order) = self.orders.get_mut(order_id) {
+                    order.status = OrderStatus::Cancelled;
+                    order.updated_at = envelope.timestamp;
+                }
+            }
+            Event::OrderRejected { order_id, .. } => {
+                if let Some(order) = self.orders.get_mut(order_id) {
+                    order.status = OrderStatus::Rejected;
+                    order.updated_at = envelope.timestamp;
+                }
+            }
+            Event::TradeExecuted { symbol, signal, entry_price, position_size, stop_loss } => {
+                let side = match signal {
+                    Signal::Buy => PositionSide::Long,
+                    Signal::Sell => PositionSide::Short,
+                    Signal::Hold => return Ok(()),
+                };
+                self.trades.push(Trade {
+                    symbol: symbol.clone(),
+                    signal: *signal,
+                    entry_price: *entry_price,
+                    position_size: *position_size,
+                    stop_loss: *stop_loss,
+                    timestamp: envelope.timestamp,
+                });
+                self.risk_engine.record_trade_open(
+                    symbol.clone(),
+                    side,
+                    *entry_price,
+                    *position_size,
+                    *stop_loss,
+                    envelope.timestamp,
+                )?;
+            }
+            Event::TradeClosed { symbol, exit_price, .. } => {
+                self.risk_engine.record_trade_close(symbol, *exit_price)?;
+            }
+            Event::RiskHalt { reason } => {
+                self.risk_engine.activate_kill_switch(reason.clone());
+            }
+            Event::OrderBookUpdated(_)
+            | Event::TradePrinted(_)
+            | Event::CandleClosed(_)
+            | Event::SignalGenerated { .. }
+            | Event::CircuitStateChanged { .. }
+            | Event::FeedFailover { .. }
+            | Event::PriceDivergence { .. }
+            | Event::DataQuality { .. }
+            | Event::FeedHealthChanged { .. }
+            | Event::Error(_) => {}
         }
         Ok(())
     }
@@ -355,4 +571,133 @@ impl ExecutionEngine {
     pub fn fills(&self) -> &[Fill] {
         &self.fills
     }
+
+    pub fn metrics(&self) -> ExecutionMetrics {
+        let orders_by_status = OrderStatus::ALL
+            .iter()
+            .map(|status| {
+                let count = self.orders.values().filter(|order| order.status == *status).count();
+                (*status, count)
+            })
+            .collect();
+
+        ExecutionMetrics {
+            orders_by_status,
+            fills: self.fills.len(),
+            trades: self.trades.len(),
+            balance: self.risk_engine.account_balance(),
+            equity: self.risk_engine.equity(),
+            open_positions: self.risk_engine.open_positions(),
+            daily_loss: self.risk_engine.daily_loss(),
+            kill_switch_active: self.risk_engine.is_kill_switch_active(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn limits() -> PortfolioLimits {
+        PortfolioLimits::new(
+            Decimal::from(1000),
+            Decimal::from(50000),
+            Decimal::from(10),
+            5,
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_recover_from_journal() {
+        let path = std::env::temp_dir().join(format!("execution-recover-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let bus = EventBus::new();
+        bus.attach_journal(Arc::new(EventJournal::open(&path).unwrap()));
+        let mut engine = ExecutionEngine::new(Decimal::from(10000), limits(), bus).unwrap();
+        engine.execute("BTCUSDT".to_string(), Signal::Buy, Decimal::from(100), Decimal::from(2), None).unwrap();
+        engine.update_price("BTCUSDT", Decimal::from(101)).unwrap();
+
+        let recovered = ExecutionEngine::recover(Decimal::from(10000), limits(), EventBus::new(), &path).unwrap();
+        assert_eq!(recovered.orders().len(), engine.orders().len());
+        assert_eq!(recovered.fills().len(), engine.fills().len());
+        assert_eq!(recovered.trades().len(), 1);
+        assert_eq!(recovered.open_positions(), 1);
+        assert_eq!(recovered.orders()[&1].status, OrderStatus::Filled);
+        assert_eq!(recovered.next_order_id, 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_market_orders_cross_the_spread() {
+        let mut engine = ExecutionEngine::new(Decimal::from(10000), limits(), EventBus::new()).unwrap();
+        let quote = Quote::new(Decimal::from(99), Decimal::ONE, Decimal::from(101), Decimal::ONE);
+        engine.update_quote("BTCUSDT", quote);
+
+        let trade = engine
+            .execute("BTCUSDT".to_string(), Signal::Buy, Decimal::from(100), Decimal::from(2), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(trade.entry_price, Decimal::from(101));
+        assert!(engine.fills().iter().all(|fill| fill.price == Decimal::from(101)));
+
+        // No quote: fill at the signal price
+        let trade = engine
+            .execute("ETHUSDT".to_string(), Signal::Sell, Decimal::from(50), Decimal::ONE, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(trade.entry_price, Decimal::from(50));
+    }
+
+    #[test]
+    fn test_risk_rejections_are_distinguished() {
+        let mut engine = ExecutionEngine::new(Decimal::from(10000), limits(), EventBus::new()).unwrap();
+        // A stop one cent away sizes far beyond the notional limit
+        let result = engine.execute("BTCUSDT".to_string(), Signal::Buy, Decimal::from(100), Decimal::new(1, 2), None);
+        assert!(matches!(result, Err(TradingError::Rejected(_))));
+        assert!(engine.orders().is_empty());
+
+        let result = engine.execute("BTCUSDT".to_string(), Signal::Buy, Decimal::from(100), Decimal::ZERO, None);
+        assert!(!matches!(result, Err(TradingError::Rejected(_)) | Ok(_)));
+    }
+
+    #[test]
+    fn test_orders_follow_instrument_rules() {
+        use crate::config::exchange_config::ExchangeType;
+        use crate::instrument::InstrumentId;
+
+        let eth = Instrument {
+            price_tick: Decimal::new(1, 2),
+            qty_step: Decimal::new(1, 1),
+            min_qty: Decimal::new(1, 1),
+            max_qty: Some(Decimal::from(1000)),
+            min_notional: Decimal::from(10),
+            ..Instrument::new(InstrumentId::spot("ETH", "USDT", ExchangeType::Binance), "ETHUSDT")
+        };
+        let mut engine = ExecutionEngine::new(Decimal::from(10000), limits(), EventBus::new()).unwrap();
+        engine.set_instruments(InstrumentRegistry::from_instruments([eth]).unwrap());
+
+        // 2% of 10000 over a distance of 3 is 66.66..., rounded down to the 0.1 step
+        let trade = engine
+            .execute("ETH-USDT".to_string(), Signal::Buy, Decimal::from(100), Decimal::from(3), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(trade.position_size, Decimal::new(666, 1));
+
+        let submit = |engine: &mut ExecutionEngine, order_type, quantity, price| {
+            engine.submit_order("ETHUSDT".to_string(), OrderSide::Buy, order_type, TimeInForce::Gtc, quantity, price, None)
+        };
+        assert!(submit(&mut engine, OrderType::Limit, Decimal::new(15, 2), Some(Decimal::from(100))).is_err());
+        assert!(submit(&mut engine, OrderType::Limit, Decimal::ONE, Some(Decimal::new(100005, 3))).is_err());
+        assert!(submit(&mut engine, OrderType::Limit, Decimal::new(1, 1), Some(Decimal::from(50))).is_err());
+        let order_id = submit(&mut engine, OrderType::Limit, Decimal::ONE, Some(Decimal::new(10001, 2))).unwrap();
+        assert!(engine.replace_order(order_id, Decimal::new(105, 2), None).is_err());
+        // Unlisted symbols keep the unchecked behaviour
+        let unlisted = engine.submit_order(
+            "SOLUSDT".to_string(), OrderSide::Buy, OrderType::Market, TimeInForce::Ioc, Decimal::new(15, 9), None, None,
+        );
+        assert!(unlisted.is_ok());
+    }
 }
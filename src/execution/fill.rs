@@ -1,6 +1,9 @@
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::market_data::event::Quote;
+use super::order::OrderSide;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fill {
 	pub order_id: u64,
 	pub symbol: String,
@@ -13,6 +16,21 @@ pub struct Fill {
 pub struct FillSimulator;
 
 impl FillSimulator {
+	/// Taker fee charged on simulated fills (5 bps)
+	pub fn fee(price: Decimal, quantity: Decimal) -> Decimal {
+		(price * quantity * Decimal::new(5, 4)).round_dp(8)
+	}
+
+	/// Price a marketable order pays: buys lift the ask, sells hit the bid.
+	/// Falls back to `last` when no quote is known.
+	pub fn execution_price(side: OrderSide, last: Decimal, quote: Option<&Quote>) -> Decimal {
+		match (side, quote) {
+			(OrderSide::Buy, Some(quote)) if quote.ask > Decimal::ZERO => quote.ask,
+			(OrderSide::Sell, Some(quote)) if quote.bid > Decimal::ZERO => quote.bid,
+			_ => last,
+		}
+	}
+
 	/// Simulate fills with basic partial fill handling
 	pub fn simulate(
 		order_id: u64,
@@ -40,7 +58,7 @@ impl FillSimulator {
 				symbol: symbol.to_string(),
 				price,
 				quantity: first_qty,
-				fee: (price * first_qty * Decimal::from_str_exact("0.0005").unwrap_or(Decimal::ZERO)).round_dp(8),
+				fee: Self::fee(price, first_qty),
 				timestamp,
 			});
 		}
@@ -51,7 +69,7 @@ impl FillSimulator {
 				symbol: symbol.to_string(),
 				price,
 				quantity: second_qty,
-				fee: (price * second_qty * Decimal::from_str_exact("0.0005").unwrap_or(Decimal::ZERO)).round_dp(8),
+				fee: Self::fee(price, second_qty),
 				timestamp,
 			});
 		}
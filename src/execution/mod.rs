@@ -2,4 +2,4 @@ pub mod engine;
 pub mod fill;
 pub mod order;
 
-pub use engine::ExecutionEngine;
+pub use engine::{ExecutionEngine, ExecutionMetrics};
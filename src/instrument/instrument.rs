@@ -1 +1,268 @@
-// Instrument definitions and utilities
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::error::{Result, TradingError};
+use super::symbol::{InstrumentId, SymbolRegistry};
+
+/// Decimal places used for symbols without instrument data
+pub const DEFAULT_PRECISION: u32 = 8;
+
+/// Trading rules of one instrument: price filter, lot size and notional limits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instrument {
+    pub id: InstrumentId,
+    /// Exchange spelling, e.g. `BTCUSDT`
+    pub native: String,
+    /// Smallest price increment
+    pub price_tick: Decimal,
+    /// Smallest quantity increment
+    pub qty_step: Decimal,
+    pub min_qty: Decimal,
+    /// No upper bound when `None`
+    pub max_qty: Option<Decimal>,
+    /// Smallest order value in the quote asset
+    pub min_notional: Decimal,
+    /// Quote value of one unit of quantity per unit of price; 1 for spot
+    pub multiplier: Decimal,
+}
+
+impl Instrument {
+    /// Instrument with `DEFAULT_PRECISION` increments and no limits
+    pub fn new(id: InstrumentId, native: impl Into<String>) -> Self {
+        let increment = Decimal::new(1, DEFAULT_PRECISION);
+        Self {
+            id,
+            native: native.into(),
+            price_tick: increment,
+            qty_step: increment,
+            min_qty: Decimal::ZERO,
+            max_qty: None,
+            min_notional: Decimal::ZERO,
+            multiplier: Decimal::ONE,
+        }
+    }
+
+    /// Canonical bus symbol
+    pub fn symbol(&self) -> String {
+        self.id.symbol()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |what: &str| Err(TradingError::Config(format!("{}: {}", self.id, what)));
+        if self.price_tick <= Decimal::ZERO {
+            return invalid("price tick must be positive");
+        }
+        if self.qty_step <= Decimal::ZERO {
+            return invalid("quantity step must be positive");
+        }
+        if self.multiplier <= Decimal::ZERO {
+            return invalid("multiplier must be positive");
+        }
+        if self.min_qty < Decimal::ZERO || self.min_notional < Decimal::ZERO {
+            return invalid("minimums cannot be negative");
+        }
+        if self.max_qty.is_some_and(|max| max < self.min_qty) {
+            return invalid("max quantity is below min quantity");
+        }
+        Ok(())
+    }
+
+    /// Nearest price on the tick grid
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        ((price / self.price_tick).round() * self.price_tick).normalize()
+    }
+
+    /// Largest quantity on the step grid not above `quantity`, so rounding never adds risk
+    pub fn round_qty(&self, quantity: Decimal) -> Decimal {
+        ((quantity / self.qty_step).floor() * self.qty_step).normalize()
+    }
+
+    /// Quote value of `quantity` at `price`
+    pub fn notional(&self, quantity: Decimal, price: Decimal) -> Decimal {
+        quantity * price * self.multiplier
+    }
+
+    /// Price must be positive and on the tick grid
+    pub fn validate_price(&self, price: Decimal) -> Result<()> {
+        if price <= Decimal::ZERO {
+            return Err(TradingError::Validation("Price must be positive".to_string()));
+        }
+        if !(price % self.price_tick).is_zero() {
+            return Err(TradingError::Validation(format!(
+                "{} price {} is not a multiple of tick {}",
+                self.symbol(), price, self.price_tick
+            )));
+        }
+        Ok(())
+    }
+
+    /// Quantity must be on the step grid and within `[min_qty, max_qty]`
+    pub fn validate_qty(&self, quantity: Decimal) -> Result<()> {
+        if quantity <= Decimal::ZERO {
+            return Err(TradingError::Validation("Order quantity must be positive".to_string()));
+        }
+        if !(quantity % self.qty_step).is_zero() {
+            return Err(TradingError::Validation(format!(
+                "{} quantity {} is not a multiple of step {}",
+                self.symbol(), quantity, self.qty_step
+            )));
+        }
+        if quantity < self.min_qty {
+            return Err(TradingError::Validation(format!(
+                "{} quantity {} is below the minimum {}",
+                self.symbol(), quantity, self.min_qty
+            )));
+        }
+        if let Some(max) = self.max_qty.filter(|max| quantity > *max) {
+            return Err(TradingError::Validation(format!(
+                "{} quantity {} is above the maximum {}",
+                self.symbol(), quantity, max
+            )));
+        }
+        Ok(())
+    }
+
+    /// Order value at `price` must reach `min_notional`
+    pub fn validate_notional(&self, quantity: Decimal, price: Decimal) -> Result<()> {
+        let notional = self.notional(quantity, price);
+        if notional < self.min_notional {
+            return Err(TradingError::Validation(format!(
+                "{} order value {} is below the minimum notional {}",
+                self.symbol(), notional, self.min_notional
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Instruments by canonical symbol. Bus symbols are venue-independent, so
+/// one venue's rules apply per symbol; a later insert replaces an earlier one.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, Instrument>,
+    /// Native spellings of `instruments`, for lookups by exchange symbol
+    symbols: SymbolRegistry,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_instruments(instruments: impl IntoIterator<Item = Instrument>) -> Result<Self> {
+        let mut registry = Self::new();
+        for instrument in instruments {
+            registry.insert(instrument)?;
+        }
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, instrument: Instrument) -> Result<()> {
+        instrument.validate()?;
+        self.symbols.register(instrument.id.clone(), instrument.native.clone());
+        self.instruments.insert(instrument.symbol(), instrument);
+        Ok(())
+    }
+
+    /// Instrument for `symbol` in canonical or native spelling
+    pub fn get(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(symbol).or_else(|| {
+            let canonical = self.symbols.canonical(symbol).ok()?;
+            self.instruments.get(&canonical)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+
+    /// Instruments sorted by symbol
+    pub fn instruments(&self) -> Vec<&Instrument> {
+        let mut instruments: Vec<&Instrument> = self.instruments.values().collect();
+        instruments.sort_by_key(|instrument| instrument.symbol());
+        instruments
+    }
+
+    /// Symbol mapping with every instrument's native spelling registered
+    pub fn symbol_registry(&self) -> SymbolRegistry {
+        self.symbols.clone()
+    }
+
+    /// Read a JSON array of instruments written by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| {
+            TradingError::Config(format!("Cannot read instruments from {}: {}", path.display(), e))
+        })?;
+        Self::from_instruments(serde_json::from_str::<Vec<Instrument>>(&text)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.instruments())?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::exchange_config::ExchangeType;
+
+    fn btc() -> Instrument {
+        Instrument {
+            price_tick: Decimal::new(1, 2),
+            qty_step: Decimal::new(1, 5),
+            min_qty: Decimal::new(1, 5),
+            max_qty: Some(Decimal::from(9000)),
+            min_notional: Decimal::from(5),
+            ..Instrument::new(InstrumentId::spot("BTC", "USDT", ExchangeType::Binance), "BTCUSDT")
+        }
+    }
+
+    #[test]
+    fn test_rounds_and_validates_against_filters() {
+        let btc = btc();
+        assert_eq!(btc.round_price(Decimal::new(5000012345, 5)), Decimal::new(5000012, 2));
+        assert_eq!(btc.round_qty(Decimal::new(123456789, 8)), Decimal::new(123456, 5));
+
+        assert!(btc.validate_price(Decimal::new(5000001, 2)).is_ok());
+        assert!(btc.validate_price(Decimal::new(50000001, 3)).is_err());
+        assert!(btc.validate_qty(Decimal::new(1, 5)).is_ok());
+        assert!(btc.validate_qty(Decimal::new(15, 6)).is_err());
+        assert!(btc.validate_qty(Decimal::from(9001)).is_err());
+        // 0.00009 BTC at 50000 is 4.5 USDT
+        assert!(btc.validate_notional(Decimal::new(9, 5), Decimal::from(50000)).is_err());
+        assert!(btc.validate_notional(Decimal::new(10, 5), Decimal::from(50000)).is_ok());
+
+        let broken = Instrument { qty_step: Decimal::ZERO, ..btc };
+        assert!(InstrumentRegistry::new().insert(broken).is_err());
+    }
+
+    #[test]
+    fn test_registry_round_trips_through_file() {
+        let registry = InstrumentRegistry::from_instruments([btc()]).unwrap();
+        assert_eq!(registry.get("BTCUSDT").unwrap().min_notional, Decimal::from(5));
+        assert!(registry.get("ETH-USDT").is_none());
+
+        let sats = Instrument::new(InstrumentId::spot("SATS", "USDT", ExchangeType::Binance), "1000SATSUSDT");
+        let listed = InstrumentRegistry::from_instruments([sats.clone()]).unwrap();
+        assert_eq!(listed.get("1000SATSUSDT"), Some(&sats));
+
+        let path = std::env::temp_dir().join(format!("instruments-{}.json", std::process::id()));
+        registry.save(&path).unwrap();
+        let loaded = InstrumentRegistry::load(&path).unwrap();
+        assert_eq!(loaded.get("BTC-USDT"), Some(&btc()));
+        assert_eq!(loaded.symbol_registry().native(&btc().id), "BTCUSDT");
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod instrument;
+pub mod symbol;
+
+pub use instrument::{Instrument, InstrumentRegistry, DEFAULT_PRECISION};
+pub use symbol::{ContractType, InstrumentId, SymbolRegistry};
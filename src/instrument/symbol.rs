@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::config::exchange_config::ExchangeType;
+use crate::error::{Result, TradingError};
+
+/// Quote assets recognised when splitting a native symbol, longest first
+const QUOTE_ASSETS: [&str; 14] = [
+    "FDUSD", "USDT", "USDC", "BUSD", "TUSD", "USDE", "DAI", "BTC", "ETH", "BNB", "EUR", "TRY", "BRL", "USD",
+];
+
+/// Suffix of canonical perpetual symbols
+const PERP_SUFFIX: &str = "-PERP";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ContractType {
+    Spot,
+    /// Linear perpetual swap settled in the quote asset
+    Perpetual,
+}
+
+/// Venue-qualified instrument, e.g. `BINANCE:BTC-USDT`.
+///
+/// `symbol()` is the venue-independent key used on the bus, so the same
+/// pair from two exchanges shares one symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InstrumentId {
+    pub base: String,
+    pub quote: String,
+    pub venue: ExchangeType,
+    pub contract: ContractType,
+}
+
+impl InstrumentId {
+    pub fn new(base: &str, quote: &str, venue: ExchangeType, contract: ContractType) -> Self {
+        Self { base: base.to_uppercase(), quote: quote.to_uppercase(), venue, contract }
+    }
+
+    pub fn spot(base: &str, quote: &str, venue: ExchangeType) -> Self {
+        Self::new(base, quote, venue, ContractType::Spot)
+    }
+
+    /// Canonical symbol: `BTC-USDT`, or `BTC-USDT-PERP` for perpetuals
+    pub fn symbol(&self) -> String {
+        match self.contract {
+            ContractType::Spot => format!("{}-{}", self.base, self.quote),
+            ContractType::Perpetual => format!("{}-{}{}", self.base, self.quote, PERP_SUFFIX),
+        }
+    }
+
+    /// Parse a canonical symbol (`BTC-USDT`, `BTC/USDT`, `BTC-USDT-PERP`) on `venue`
+    pub fn parse_symbol(symbol: &str, venue: ExchangeType) -> Option<Self> {
+        let symbol = symbol.trim().to_uppercase();
+        let (pair, contract) = match symbol.strip_suffix(PERP_SUFFIX) {
+            Some(pair) => (pair, ContractType::Perpetual),
+            None => (symbol.as_str(), ContractType::Spot),
+        };
+        let (base, quote) = pair.split_once(['-', '/'])?;
+        let valid = |asset: &str| !asset.is_empty() && asset.chars().all(|c| c.is_ascii_alphanumeric());
+        (valid(base) && valid(quote)).then(|| Self::new(base, quote, venue, contract))
+    }
+}
+
+fn venue_name(venue: &ExchangeType) -> &'static str {
+    match venue {
+        ExchangeType::Binance => "BINANCE",
+        ExchangeType::Bybit => "BYBIT",
+    }
+}
+
+impl fmt::Display for InstrumentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", venue_name(&self.venue), self.symbol())
+    }
+}
+
+impl FromStr for InstrumentId {
+    type Err = TradingError;
+
+    /// Parse the `Display` form, e.g. `BYBIT:ETH-USDT-PERP`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || TradingError::Validation(format!("Invalid instrument id: {}", s));
+        let (venue, symbol) = s.split_once(':').ok_or_else(invalid)?;
+        let venue = match venue.to_uppercase().as_str() {
+            "BINANCE" => ExchangeType::Binance,
+            "BYBIT" => ExchangeType::Bybit,
+            _ => return Err(invalid()),
+        };
+        Self::parse_symbol(symbol, venue).ok_or_else(invalid)
+    }
+}
+
+/// Bidirectional mapping between instruments and exchange-native symbols.
+///
+/// Registered pairs win; anything else follows the Binance/Bybit convention
+/// of concatenating base and quote (`BTCUSDT`), split back on known quote
+/// assets. A bare asset (`BTC`) is quoted in `default_quote`.
+#[derive(Debug, Clone)]
+pub struct SymbolRegistry {
+    to_native: HashMap<InstrumentId, String>,
+    from_native: HashMap<(ExchangeType, ContractType, String), InstrumentId>,
+    default_quote: String,
+}
+
+impl Default for SymbolRegistry {
+    fn default() -> Self {
+        Self {
+            to_native: HashMap::new(),
+            from_native: HashMap::new(),
+            default_quote: "USDT".to_string(),
+        }
+    }
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `id` to and from `native`, overriding the naming convention
+    pub fn register(&mut self, id: InstrumentId, native: impl Into<String>) {
+        let native = native.into().to_uppercase();
+        self.from_native.insert((id.venue.clone(), id.contract, native.clone()), id.clone());
+        self.to_native.insert(id, native);
+    }
+
+    /// Exchange spelling of `id`
+    pub fn native(&self, id: &InstrumentId) -> String {
+        self.to_native
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| format!("{}{}", id.base, id.quote))
+    }
+
+    /// Instrument behind an exchange-native symbol
+    pub fn lookup(&self, venue: ExchangeType, contract: ContractType, native: &str) -> Result<InstrumentId> {
+        let native = native.trim().to_uppercase();
+        if let Some(id) = self.from_native.get(&(venue.clone(), contract, native.clone())) {
+            return Ok(id.clone());
+        }
+        QUOTE_ASSETS
+            .iter()
+            .filter(|quote| native.len() > quote.len())
+            .find_map(|quote| native.strip_suffix(quote).map(|base| InstrumentId::new(base, quote, venue.clone(), contract)))
+            .ok_or_else(|| TradingError::Validation(format!("Cannot split {} symbol {} into base and quote", venue_name(&venue), native)))
+    }
+
+    /// Instrument for caller input in any accepted spelling: canonical
+    /// (`BTC-USDT`, `BTC-USDT-PERP`), qualified (`BINANCE:BTC-USDT`), native
+    /// (`BTCUSDT`) or a bare base asset (`BTC`)
+    pub fn resolve(&self, venue: ExchangeType, contract: ContractType, input: &str) -> Result<InstrumentId> {
+        if input.contains(':') {
+            let id: InstrumentId = input.parse()?;
+            if id.venue != venue {
+                return Err(TradingError::Validation(format!("{} is not listed on {}", id, venue_name(&venue))));
+            }
+            return Ok(id);
+        }
+        if let Some(id) = InstrumentId::parse_symbol(input, venue.clone()) {
+            return Ok(id);
+        }
+        self.lookup(venue.clone(), contract, input).or_else(|e| {
+            let base = input.trim();
+            if !base.is_empty() && base.chars().all(|c| c.is_ascii_alphanumeric()) {
+                Ok(InstrumentId::new(base, &self.default_quote, venue, contract))
+            } else {
+                Err(e)
+            }
+        })
+    }
+
+    /// Canonical symbol for `input` where the venue does not matter, e.g. replayed files
+    pub fn canonical(&self, input: &str) -> Result<String> {
+        Ok(self.resolve(ExchangeType::Binance, ContractType::Spot, input)?.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_every_spelling_to_one_symbol() {
+        let registry = SymbolRegistry::new();
+        for input in ["BTCUSDT", "btcusdt", "BTC-USDT", "BTC/USDT", "BTC", "BINANCE:BTC-USDT"] {
+            let id = registry.resolve(ExchangeType::Binance, ContractType::Spot, input).unwrap();
+            assert_eq!(id.symbol(), "BTC-USDT", "{}", input);
+            assert_eq!(registry.native(&id), "BTCUSDT");
+        }
+
+        let id = registry.lookup(ExchangeType::Bybit, ContractType::Spot, "ETHFDUSD").unwrap();
+        assert_eq!((id.base.as_str(), id.quote.as_str()), ("ETH", "FDUSD"));
+        assert_eq!(id.to_string(), "BYBIT:ETH-FDUSD");
+        assert_eq!("BYBIT:ETH-FDUSD".parse::<InstrumentId>().unwrap(), id);
+        assert!(registry.resolve(ExchangeType::Binance, ContractType::Spot, "BYBIT:ETH-USDT").is_err());
+    }
+
+    #[test]
+    fn test_registered_names_win_both_ways() {
+        let mut registry = SymbolRegistry::new();
+        let id = InstrumentId::new("BTC", "USDC", ExchangeType::Bybit, ContractType::Perpetual);
+        registry.register(id.clone(), "BTCPERP");
+
+        assert_eq!(registry.native(&id), "BTCPERP");
+        assert_eq!(registry.lookup(ExchangeType::Bybit, ContractType::Perpetual, "BTCPERP").unwrap(), id);
+        assert_eq!(registry.resolve(ExchangeType::Bybit, ContractType::Perpetual, "BTC-USDC-PERP").unwrap(), id);
+        assert_eq!(id.symbol(), "BTC-USDC-PERP");
+        // The spot pair keeps the convention
+        assert!(registry.lookup(ExchangeType::Bybit, ContractType::Spot, "BTCPERP").is_err());
+    }
+}
@@ -12,6 +12,7 @@ pub mod market_data;
 pub mod portfolio;
 pub mod risk;
 pub mod strategy;
+pub mod telemetry;
 pub mod utils;
 
 pub use error::{Result, TradingError};
@@ -12,10 +12,15 @@ mod utils;
 mod error;
 mod risk;
 mod config;
+mod telemetry;
 
-use market_data::{PriceValidator, ExchangeFactory, PriceMonitor};
+use market_data::{
+    CandleAggregator, CandleAggregatorConfig, CandleInterval, ConsensusConfig, CsvSource, ExchangeFactory, FileFetcher, GuardConfig, HistoricalData,
+    DataQualityConfig, KlineCache, MarketDataFetcher, MarketDataRecorder, RecorderConfig,
+};
+use instrument::InstrumentRegistry;
 use strategy::StrategyFactory;
-use engine::{EventBus, Event};
+use engine::{EventBus, Event, EventKind, EventJournal, EventLoop, EventLoopConfig};
 use config::strategy_config::{StrategyConfig, StrategyType};
 use config::exchange_config::{ExchangeConfig, ExchangeType};
 use config::EnvConfig;
@@ -23,12 +28,18 @@ use rust_decimal::Decimal;
 use error::Result;
 use execution::ExecutionEngine;
 use risk::PortfolioLimits;
-use std::sync::{Arc, Mutex};
+use telemetry::{MetricsRegistry, MetricsServer};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables from .env file
     EnvConfig::load()?;
+    // LOG_FORMAT=json for machine-readable logs, RUST_LOG to filter levels
+    telemetry::logging::init_from_env()?;
     // ==========================================
     // USER CONFIGURATION - CUSTOMIZE HERE
     // ==========================================
@@ -37,11 +48,14 @@ async fn main() -> Result<()> {
     // ExchangeType::Binance  - Crypto spot trading
     // ExchangeType::Bybit    - Crypto derivatives & spot
     // ExchangeType::Alpaca   - Crypto trading (requires ALPACA_API_KEY in .env)
-    let exchange_config = ExchangeConfig {
+    let mut exchange_config = ExchangeConfig {
         exchange_type: ExchangeType::Binance,  // ← Change to Bybit or Alpaca for testing
         api_key: None,
         api_secret: None,
         enabled: true,
+        base_url: None,
+        ws_url: None,
+        symbols: None,
     };
 
     // Choose which strategy and parameters
@@ -56,6 +70,15 @@ async fn main() -> Result<()> {
         enabled: true,
     };
 
+    // Polling schedule for the event loop
+    let loop_config = EventLoopConfig {
+        tick_interval: Duration::from_secs(5),
+        data_quality: DataQualityConfig::new(60_000),
+        universe: Vec::new(),  // ← Extra symbols to poll (recorded and aggregated, not traded)
+        max_batch: 100,
+        max_ticks: None,  // ← Set Some(n) to stop after n ticks
+    };
+
     // ==========================================
     // VALIDATION
     // ==========================================
@@ -66,96 +89,151 @@ async fn main() -> Result<()> {
     // INITIALIZE EVENT BUS
     // ==========================================
     let event_bus = EventBus::new();
-
-    println!("🔌 Setting up Event Bus subscribers...\n");
+    if let Some(path) = EnvConfig::get_event_journal_path() {
+        event_bus.attach_journal(Arc::new(EventJournal::open(&path)?));
+        info!(path = %path, "Journaling events");
+    }
 
     // Subscribe to price updates
-    let execution_engine_ref: Arc<Mutex<Option<ExecutionEngine>>> = Arc::new(Mutex::new(None));
-    let execution_engine_ref_clone = Arc::clone(&execution_engine_ref);
-    event_bus.subscribe("PriceUpdated", move |event| {
-        if let Event::PriceUpdated(price_event) = event {
-            println!("  📊 [EventBus] Price Updated: {} @ {}", 
-                price_event.symbol, price_event.price);
-
-            if let Ok(mut guard) = execution_engine_ref_clone.lock() {
-                if let Some(engine) = guard.as_mut() {
-                    if let Err(err) = engine.update_price(&price_event.symbol, price_event.price) {
-                        let _ = engine.is_kill_switch_active();
-                        eprintln!("  ⚠️ [Risk] Price update error: {}", err);
-                    }
-                }
-            }
+    event_bus.subscribe(EventKind::PriceUpdated, |envelope| {
+        if let Event::PriceUpdated(price_event) = &envelope.event {
+            debug!(sequence = envelope.sequence, exchange = %envelope.source,
+                symbol = %price_event.symbol, price = %price_event.price, "Price updated");
         }
-    })?;
+    })?.detach();
 
     // Subscribe to signals
-    event_bus.subscribe("SignalGenerated", |event| {
-        if let Event::SignalGenerated { strategy_name, symbol, signal, price } = event {
-            println!("  📈 [EventBus] Signal from {}: {:?} on {} @ {}", 
-                strategy_name, signal, symbol, price);
+    event_bus.subscribe(EventKind::SignalGenerated, |envelope| {
+        if let Event::SignalGenerated { strategy_name, symbol, signal, price } = &envelope.event {
+            info!(sequence = envelope.sequence, strategy = %strategy_name, symbol = %symbol,
+                ?signal, price = %price, "Signal");
         }
-    })?;
+    })?.detach();
 
     // Subscribe to trade execution
-    event_bus.subscribe("TradeExecuted", |event| {
-        if let Event::TradeExecuted { symbol, signal, entry_price, position_size, stop_loss } = event {
-            println!("  🚀 [EventBus] Trade Executed: {:?} {} @ {} (Size: {}, SL: {})", 
-                signal, symbol, entry_price, position_size, stop_loss);
+    event_bus.subscribe(EventKind::TradeExecuted, |envelope| {
+        if let Event::TradeExecuted { symbol, signal, entry_price, position_size, stop_loss } = &envelope.event {
+            info!(sequence = envelope.sequence, correlation_id = ?envelope.correlation_id,
+                symbol = %symbol, ?signal, entry_price = %entry_price, position_size = %position_size,
+                stop_loss = %stop_loss, "Trade executed");
         }
-    })?;
+    })?.detach();
 
     // Subscribe to risk halt
-    event_bus.subscribe("RiskHalt", |event| {
-        if let Event::RiskHalt { reason } = event {
-            println!("  🛑 [Risk] Kill-switch activated: {}", reason);
+    event_bus.subscribe(EventKind::RiskHalt, |envelope| {
+        if let Event::RiskHalt { reason } = &envelope.event {
+            error!(sequence = envelope.sequence, reason = %reason, "Trading halted by risk engine");
         }
-    })?;
+    })?.detach();
 
-    // Subscribe to order lifecycle events
-    event_bus.subscribe("OrderSubmitted", |event| {
-        if let Event::OrderSubmitted { order_id, symbol, side, quantity, price } = event {
-            println!("  📝 [OMS] Order {} {:?} {} qty={} price={:?}",
-                order_id, side, symbol, quantity, price);
+    // Subscribe to errors
+    event_bus.subscribe(EventKind::Error, |envelope| {
+        if let Event::Error(msg) = &envelope.event {
+            warn!(sequence = envelope.sequence, source = %envelope.source, error = %msg, "Error event");
         }
-    })?;
+    })?.detach();
 
-    event_bus.subscribe("OrderFilled", |event| {
-        if let Event::OrderFilled { order_id, symbol, filled_qty, price } = event {
-            println!("  ✅ [OMS] Fill {} {} qty={} price={}",
-                order_id, symbol, filled_qty, price);
+    // Log feed failovers with every error that caused them
+    event_bus.subscribe(EventKind::FeedFailover, |envelope| {
+        if let Event::FeedFailover { symbol, from, to, errors } = &envelope.event {
+            warn!(sequence = envelope.sequence, symbol = %symbol, from = %from, to = ?to, errors = ?errors, "Feed failover");
         }
-    })?;
+    })?.detach();
 
-    // Subscribe to errors
-    event_bus.subscribe("Error", |event| {
-        if let Event::Error(msg) = event {
-            println!("  ❌ [EventBus] Error: {}", msg);
+    // Build 1m bars from every price update
+    let _candles = CandleAggregator::new(CandleAggregatorConfig::default())?.attach(&event_bus)?;
+    event_bus.subscribe(EventKind::CandleClosed, |envelope| {
+        if let Event::CandleClosed(candle) = &envelope.event {
+            info!(symbol = %candle.symbol, interval = %candle.interval, open_time = candle.open_time,
+                open = %candle.open, high = %candle.high, low = %candle.low, close = %candle.close, "Candle closed");
         }
-    })?;
+    })?.detach();
+
+    // RECORD_DIR=<dir> keeps every price, book and trade event as rotating gzip files
+    let _recorder = match EnvConfig::get_record_dir() {
+        Some(dir) => Some(MarketDataRecorder::start(RecorderConfig::new(dir), &event_bus)?),
+        None => None,
+    };
 
-    println!("✅ Event Bus ready with subscribers\n");
+    // Tick, lot size and min notional rules: INSTRUMENTS_FILE when it exists, else the
+    // exchange's listing (cached to INSTRUMENTS_FILE when set); unlisted symbols are unchecked.
+    // Loaded first so every fetcher and stream uses the listed native symbols
+    let instruments_file = EnvConfig::get_instruments_file();
+    let instruments = match instruments_file.as_deref().filter(|path| Path::new(path).exists()) {
+        Some(path) => InstrumentRegistry::load(path)?,
+        None => {
+            let fetcher = ExchangeFactory::create_instrument_fetcher(&exchange_config, event_bus.clone())?;
+            match fetcher.fetch_instruments(&[]).await {
+                Ok(listing) => {
+                    let registry = InstrumentRegistry::from_instruments(listing)?;
+                    if let Some(path) = &instruments_file {
+                        registry.save(path)?;
+                    }
+                    registry
+                }
+                Err(e) => {
+                    warn!(error = %e, "Trading without instrument rules");
+                    InstrumentRegistry::new()
+                }
+            }
+        }
+    };
+    info!(instruments = instruments.len(), "Instrument rules loaded");
+    exchange_config.symbols = Some(Arc::new(instruments.symbol_registry()));
 
     // ==========================================
     // CREATE EXCHANGE FETCHER (USER'S CHOICE)
     // ==========================================
-    println!("📍 Creating market data fetcher...");
     let fallback_exchange = match exchange_config.exchange_type {
         ExchangeType::Binance => ExchangeType::Bybit,
         ExchangeType::Bybit => ExchangeType::Binance,
     };
-    let fetcher = ExchangeFactory::create_resilient_fetcher(
-        exchange_config.exchange_type.clone(),
-        fallback_exchange,
-        event_bus.clone(),
-    )?;
-    println!("✅ Using exchange: {}\n", fetcher.exchange_name());
+    // Endpoint overrides belong to the primary exchange; the fallback uses its own env or public API
+    let fallback_config = ExchangeConfig {
+        exchange_type: fallback_exchange.clone(),
+        base_url: None,
+        ws_url: None,
+        ..exchange_config.clone()
+    };
+    // REPLAY_DIR=<recording> or REPLAY_CSV=<.csv or .parquet file> (columns timestamp,symbol,price,volume)
+    // replay recorded prices instead; PRICE_CONSENSUS=median|vwap prices off both exchanges
+    let sources = (EnvConfig::get_replay_dir(), EnvConfig::get_replay_csv(), EnvConfig::get_price_consensus());
+    let fetcher: Box<dyn MarketDataFetcher> = match sources {
+        (Some(dir), _, _) => Box::new(FileFetcher::from_recording(dir)?),
+        (None, Some(path), _) => Box::new(FileFetcher::from_csv(&[CsvSource::new(path)])?),
+        (None, None, Some(method)) => ExchangeFactory::create_consensus_fetcher(
+            &[exchange_config.clone(), fallback_config.clone()],
+            ConsensusConfig::new(method.parse()?),
+            event_bus.clone(),
+        )?,
+        (None, None, None) => ExchangeFactory::create_resilient_fetcher(
+            &exchange_config,
+            &fallback_config,
+            event_bus.clone(),
+        )?,
+    };
 
     // ==========================================
     // CREATE STRATEGY (USER'S CHOICE)
     // ==========================================
-    println!("🎯 Creating strategy...");
-    let strategy = StrategyFactory::create_strategy(&strategy_config)?;
-    println!("✅ Using strategy: {}\n", strategy.name());
+    let mut strategy = StrategyFactory::create_strategy(&strategy_config)?;
+
+    // Warm up on recent 1m bars (best effort), cached on disk when KLINE_CACHE_DIR is set
+    let mut history = HistoricalData::new(
+        ExchangeFactory::create_historical_fetcher(&exchange_config, event_bus.clone())?,
+        GuardConfig::default(),
+        event_bus.clone(),
+    );
+    if let Some(dir) = EnvConfig::get_kline_cache_dir() {
+        history = history.with_cache(KlineCache::new(dir));
+    }
+    match history.latest(&strategy_config.symbol, CandleInterval::M1, 200).await {
+        Ok(candles) => {
+            strategy.warm_up(&candles)?;
+            info!(symbol = %strategy_config.symbol, bars = candles.len(), "Strategy warmed up from history");
+        }
+        Err(e) => warn!(symbol = %strategy_config.symbol, error = %e, "Skipping strategy warm-up"),
+    }
 
     // ==========================================
     // INITIALIZE RISK ENGINE + EXECUTION ENGINE
@@ -163,93 +241,83 @@ async fn main() -> Result<()> {
     let initial_balance = Decimal::from_str_exact("10000")?; // Example starting balance
     let risk_params = strategy_config.get_risk_params();
     let portfolio_limits = PortfolioLimits::from_risk_params(initial_balance, risk_params)?;
-    let execution_engine = ExecutionEngine::new(
+    let mut execution_engine = ExecutionEngine::new(
         initial_balance,
         portfolio_limits,
         event_bus.clone(),
     )?;
-    if let Ok(mut guard) = execution_engine_ref.lock() {
-        *guard = Some(execution_engine);
-    }
 
-    // ==========================================
-    // FETCH MARKET DATA
-    // ==========================================
-    println!("📊 Fetching {} price from {}...", 
-        strategy_config.symbol, fetcher.exchange_name());
-    let price_event = fetcher.fetch_price(&strategy_config.symbol).await?;
-    println!("✅ Fetched: {:?}\n", price_event);
+    execution_engine.set_instruments(instruments);
 
     // ==========================================
-    // VALIDATE & NORMALIZE
+    // RUN EVENT LOOP (Ctrl+C to stop)
     // ==========================================
-    println!("🔍 Validating and normalizing price...");
-    let normalized = PriceValidator::normalize(price_event)?;
-    let mut monitor = PriceMonitor::new(60_000);
-    let normalized = match monitor.process(normalized)? {
-        Some(event) => event,
-        None => {
-            println!("⚠️ Duplicate market data ignored");
-            return Ok(());
-        }
-    };
-    println!("✅ Normalized: {:?}\n", normalized);
+    let strategy_name = strategy.name().to_string();
+    info!(
+        exchange = ?exchange_config.exchange_type,
+        fallback = ?fallback_exchange,
+        strategy = %strategy_name,
+        symbol = %strategy_config.symbol,
+        risk_profile = %strategy_config.risk_profile.description(),
+        "Starting paper trading session"
+    );
 
-    // ==========================================
-    // GENERATE SIGNAL
-    // ==========================================
-    println!("📈 Generating trading signal...");
-    let signal = strategy.signal(&normalized)?;
-    println!("✅ Signal: {:?}\n", signal);
+    let mut event_loop = EventLoop::new(
+        loop_config,
+        event_bus.clone(),
+        fetcher,
+        execution_engine,
+    )?;
+    event_loop.add_strategy(strategy_config.symbol.clone(), strategy);
 
-    // Publish signal to event bus
-    event_bus.publish(Event::SignalGenerated {
-        strategy_name: strategy.name().to_string(),
-        symbol: normalized.symbol.clone(),
-        signal,
-        price: normalized.price,
-    })?;
+    if let Some(addr) = EnvConfig::get_metrics_addr() {
+        let registry = MetricsRegistry::new(event_bus.clone());
+        let server = MetricsServer::bind(&addr, registry.clone()).await?;
+        info!(addr = %server.local_addr()?, "Serving Prometheus metrics on /metrics");
+        server.spawn();
+        event_loop.attach_metrics(registry);
+    }
 
-    // ==========================================
-    // RISK-AWARE EXECUTION
-    // ==========================================
-    let (entry_price, stop_loss_distance, _strategy_position_size) =
-        strategy.get_risk_params(normalized.price)?;
+    let shutdown = event_loop.shutdown_handle();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("Shutdown requested, finishing current tick");
+            shutdown.shutdown();
+        }
+    });
 
-    let _ = execution_engine_ref
-        .lock()
-        .ok()
-        .and_then(|mut guard| {
-            guard.as_mut().map(|engine| {
-                engine.execute(
-                    normalized.symbol.clone(),
-                    signal,
-                    entry_price,
-                    stop_loss_distance,
-                )
-            })
-        })
-        .unwrap_or_else(|| Ok(None))?;
+    event_loop.run().await?;
 
     // ==========================================
     // SUMMARY
     // ==========================================
-    
-    println!("📊 System Summary:");
-    println!("  Exchange: {}", fetcher.exchange_name());
-    println!("  Strategy: {}", strategy.name());
-    println!("  Symbol: {}", strategy_config.symbol);
-    println!("  Signal: {:?}", signal);
-    println!("  Price: {}", normalized.price);
-    println!("\n💼 Risk Profile: {}", strategy_config.risk_profile.description());
-    println!("  Max Risk Per Trade: {}%", risk_params.max_risk_per_trade);
-    println!("  Max Daily Loss: {}%", risk_params.max_daily_loss);
-    println!("  Max Drawdown: {}%", risk_params.max_drawdown);
-    println!("  Max Position Size: {}%", risk_params.max_position_size);
-    println!("  Max Open Positions: {}", risk_params.max_open_positions);
-    println!("  Max Leverage: {}x", risk_params.max_leverage);
-
-    let metrics = event_bus.metrics_snapshot();
-    println!("\n📈 Event Metrics: {:?}", metrics);
+    let execution = event_loop.execution();
+    info!(
+        exchange = %event_loop.fetcher().exchange_name(),
+        strategy = %strategy_name,
+        symbol = %strategy_config.symbol,
+        ticks = event_loop.ticks(),
+        trades = execution.trades().len(),
+        open_positions = execution.open_positions(),
+        balance = %execution.balance(),
+        "Session summary"
+    );
+    info!(
+        profile = %strategy_config.risk_profile.description(),
+        max_risk_per_trade_pct = %risk_params.max_risk_per_trade,
+        max_daily_loss_pct = %risk_params.max_daily_loss,
+        max_drawdown_pct = %risk_params.max_drawdown,
+        max_position_size_pct = %risk_params.max_position_size,
+        max_open_positions = risk_params.max_open_positions,
+        max_leverage = %risk_params.max_leverage,
+        "Risk profile"
+    );
+
+    info!(counts = ?event_bus.metrics_snapshot(), "Event counts");
+    info!("Event bus metrics\n{}", event_bus.metrics());
+    let dead_letters = event_bus.dead_letters();
+    if !dead_letters.is_empty() {
+        warn!(count = dead_letters.len(), "Failed handler invocations");
+    }
     Ok(())
 }
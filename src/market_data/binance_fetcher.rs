@@ -1,12 +1,25 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
 use async_trait::async_trait;
+use tracing::{debug, instrument, warn};
 
 use crate::error::{TradingError, Result};
 use crate::engine::EventBus;
-use super::event::PriceEvent;
-use super::fetcher_trait::MarketDataFetcher;
+use crate::config::exchange_config::ExchangeType;
+use crate::instrument::{ContractType, Instrument, InstrumentId, SymbolRegistry};
+use super::event::{parse_quote, PriceEvent};
+use super::fetcher_trait::{HistoricalFetcher, InstrumentFetcher, MarketDataFetcher, OrderBookFetcher, PriceBatch, TradeFetcher};
+use super::candle::{Candle, CandleInterval};
+use super::trade::{AggressorSide, TradePrint};
+use super::order_book::{parse_levels, OrderBookUpdate};
+use super::http::get_json;
+use super::monitor::{DataQualityConfig, PriceMonitor};
+use super::rate_limiter::RateLimiter;
+
+pub const BINANCE_REST_URL: &str = "https://api.binance.com/api/v3";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BinanceTickerResponse {
@@ -14,50 +27,399 @@ pub struct BinanceTickerResponse {
     #[serde(rename = "lastPrice")]
     pub last_price: String,
     pub volume: String,
+    #[serde(rename = "bidPrice", default)]
+    pub bid_price: Option<String>,
+    #[serde(rename = "bidQty", default)]
+    pub bid_qty: Option<String>,
+    #[serde(rename = "askPrice", default)]
+    pub ask_price: Option<String>,
+    #[serde(rename = "askQty", default)]
+    pub ask_qty: Option<String>,
+    /// End of the rolling window, i.e. the time of the latest update
+    #[serde(rename = "closeTime", default)]
+    pub close_time: Option<u64>,
+}
+
+/// `GET /depth` payload
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BinanceDepthResponse {
+    #[serde(rename = "lastUpdateId")]
+    pub last_update_id: u64,
+    pub bids: Vec<[String; 2]>,
+    pub asks: Vec<[String; 2]>,
+}
+
+/// `GET /trades` entry
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BinanceTradeResponse {
+    pub id: u64,
+    pub price: String,
+    pub qty: String,
+    pub time: u64,
+    /// The buyer was the resting order, so the seller was the aggressor
+    #[serde(rename = "isBuyerMaker")]
+    pub is_buyer_maker: bool,
+}
+
+/// `GET /exchangeInfo` payload
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BinanceExchangeInfo {
+    pub symbols: Vec<BinanceSymbolInfo>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BinanceSymbolInfo {
+    pub symbol: String,
+    pub status: String,
+    #[serde(rename = "baseAsset")]
+    pub base_asset: String,
+    #[serde(rename = "quoteAsset")]
+    pub quote_asset: String,
+    pub filters: Vec<BinanceFilter>,
+}
+
+/// Symbol filters used for rounding; the others are skipped
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "filterType")]
+pub enum BinanceFilter {
+    #[serde(rename = "PRICE_FILTER")]
+    Price {
+        #[serde(rename = "tickSize")]
+        tick_size: String,
+    },
+    #[serde(rename = "LOT_SIZE")]
+    LotSize {
+        #[serde(rename = "minQty")]
+        min_qty: String,
+        #[serde(rename = "maxQty")]
+        max_qty: String,
+        #[serde(rename = "stepSize")]
+        step_size: String,
+    },
+    /// `NOTIONAL` on current symbols, `MIN_NOTIONAL` on older ones
+    #[serde(rename = "NOTIONAL", alias = "MIN_NOTIONAL")]
+    Notional {
+        #[serde(rename = "minNotional")]
+        min_notional: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+fn parse_symbol_info(info: &BinanceSymbolInfo) -> Result<Instrument> {
+    let decimal = |text: &str| Decimal::from_str_exact(text).map_err(TradingError::Decimal);
+    let id = InstrumentId::spot(&info.base_asset, &info.quote_asset, ExchangeType::Binance);
+    let mut instrument = Instrument::new(id, info.symbol.clone());
+    for filter in &info.filters {
+        match filter {
+            BinanceFilter::Price { tick_size } => instrument.price_tick = decimal(tick_size)?,
+            BinanceFilter::LotSize { min_qty, max_qty, step_size } => {
+                instrument.min_qty = decimal(min_qty)?;
+                instrument.max_qty = Some(decimal(max_qty)?);
+                instrument.qty_step = decimal(step_size)?;
+            }
+            BinanceFilter::Notional { min_notional } => instrument.min_notional = decimal(min_notional)?,
+            BinanceFilter::Other => {}
+        }
+    }
+    instrument.validate()?;
+    Ok(instrument)
+}
+
+/// `GET /klines` row: `[openTime, open, high, low, close, volume, closeTime, quoteVolume, trades, ...]`
+pub type BinanceKlineRow = Vec<serde_json::Value>;
+
+fn parse_kline(symbol: &str, interval: CandleInterval, row: &BinanceKlineRow) -> Result<Candle> {
+    let invalid = || TradingError::MarketData(format!("Invalid Binance kline: {:?}", row));
+    let int = |index: usize| row.get(index).and_then(|value| value.as_u64()).ok_or_else(invalid);
+    let decimal = |index: usize| {
+        let text = row.get(index).and_then(|value| value.as_str()).ok_or_else(invalid)?;
+        Decimal::from_str_exact(text).map_err(TradingError::Decimal)
+    };
+    Ok(Candle {
+        symbol: symbol.to_string(),
+        interval,
+        open_time: int(0)?,
+        open: decimal(1)?,
+        high: decimal(2)?,
+        low: decimal(3)?,
+        close: decimal(4)?,
+        volume: decimal(5)?,
+        trades: int(8)?,
+    })
+}
+
+/// Request weight of `GET /depth` by `limit`
+fn depth_weight(depth: usize) -> u32 {
+    match depth {
+        0..=100 => 5,
+        101..=500 => 25,
+        501..=1000 => 50,
+        _ => 250,
+    }
 }
 
 pub struct BinanceFetcher {
     client: Client,
     base_url: String,
     event_bus: EventBus,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    symbols: Arc<SymbolRegistry>,
+    /// Dedupes and checks trade prints before they are published
+    trades: Mutex<PriceMonitor>,
 }
 
 impl BinanceFetcher {
     pub fn new(event_bus: EventBus) -> Self {
+        Self::with_client(event_bus, Client::new(), BINANCE_REST_URL)
+    }
+
+    /// Use a custom HTTP client and endpoint, e.g. a `MockExchange`
+    pub fn with_client(event_bus: EventBus, client: Client, base_url: impl Into<String>) -> Self {
         Self {
-            client: Client::new(),
-            base_url: "https://api.binance.com/api/v3".to_string(),
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            trades: Mutex::new(PriceMonitor::new(DataQualityConfig::default(), event_bus.clone())),
             event_bus,
+            rate_limiter: None,
+            symbols: Arc::new(SymbolRegistry::new()),
         }
     }
-}
 
-#[async_trait]
-impl MarketDataFetcher for BinanceFetcher {
-    async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
-        let url = format!("{}/ticker/24hr?symbol={}", self.base_url, symbol);
+    /// Take request weight from `limiter` (shared by all fetchers of the exchange)
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Map symbols through `registry` instead of the naming convention alone
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.symbols = registry;
+        self
+    }
+
+    /// Spot instrument for caller input in any spelling, and its Binance symbol
+    fn instrument(&self, symbol: &str) -> Result<(InstrumentId, String)> {
+        let id = self.symbols.resolve(ExchangeType::Binance, ContractType::Spot, symbol)?;
+        let native = self.symbols.native(&id);
+        Ok((id, native))
+    }
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json::<BinanceTickerResponse>()
-            .await?;
+    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str, weight: u32) -> Result<T> {
+        get_json(&self.client, url, self.rate_limiter.as_deref(), weight).await
+    }
 
+    /// Price event for a parsed ticker under the canonical `symbol`; the event
+    /// loop publishes it once it passes the data-quality checks
+    fn price_event(&self, symbol: String, response: BinanceTickerResponse) -> Result<PriceEvent> {
         let price = Decimal::from_str_exact(&response.last_price)
-            .map_err(|e| TradingError::Decimal(e))?;
+            .map_err(TradingError::Decimal)?;
         
         let volume = Decimal::from_str_exact(&response.volume)
-            .map_err(|e| TradingError::Decimal(e))?;
+            .map_err(TradingError::Decimal)?;
+
+        debug!(%symbol, native = %response.symbol, %price, %volume, "Fetched ticker");
+        let quote = parse_quote(
+            response.bid_price.as_deref(),
+            response.bid_qty.as_deref(),
+            response.ask_price.as_deref(),
+            response.ask_qty.as_deref(),
+        )?;
+        Ok(PriceEvent::new(symbol, price, volume)?
+            .with_exchange_time(response.close_time)
+            .with_quote(quote))
+    }
+}
 
-        let price_event = PriceEvent::new(response.symbol, price, volume)?;
+/// Request weight of `GET /ticker/24hr?symbols=` by symbol count
+fn ticker_batch_weight(symbols: usize) -> u32 {
+    match symbols {
+        0..=20 => 2,
+        21..=100 => 40,
+        _ => 80,
+    }
+}
 
-        self.event_bus.publish(crate::engine::Event::PriceUpdated(price_event.clone()))?;
+#[async_trait]
+impl MarketDataFetcher for BinanceFetcher {
+    #[instrument(skip(self), fields(exchange = "Binance"))]
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+        let (id, native) = self.instrument(symbol)?;
+        let url = format!("{}/ticker/24hr?symbol={}", self.base_url, native);
 
-        Ok(price_event)
+        let response: BinanceTickerResponse = self.get(&url, 2).await?;
+        self.price_event(id.symbol(), response)
+    }
+
+    /// One `GET /ticker/24hr?symbols=[...]` for the whole batch
+    #[instrument(skip(self), fields(exchange = "Binance", count = symbols.len()))]
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Input that does not resolve fails its own entry and stays out of the request
+        let instruments: Vec<Result<(InstrumentId, String)>> =
+            symbols.iter().map(|symbol| self.instrument(symbol)).collect();
+        let list: Vec<String> = instruments.iter().flatten().map(|(_, native)| format!("%22{}%22", native)).collect();
+        let url = format!("{}/ticker/24hr?symbols=%5B{}%5D", self.base_url, list.join(","));
+
+        let responses: Vec<BinanceTickerResponse> = if list.is_empty() {
+            Vec::new()
+        } else {
+            match self.get(&url, ticker_batch_weight(list.len())).await {
+                Ok(responses) => responses,
+                // One unknown symbol fails the whole request; fall back to one request each to isolate it
+                Err(TradingError::Http(400, body)) if list.len() > 1 => {
+                    debug!(%body, "Batch ticker rejected, fetching per symbol");
+                    let mut results = Vec::with_capacity(symbols.len());
+                    for symbol in symbols {
+                        results.push((symbol.clone(), self.fetch_price(symbol).await));
+                    }
+                    return Ok(results);
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        let mut by_symbol: HashMap<String, BinanceTickerResponse> =
+            responses.into_iter().map(|response| (response.symbol.clone(), response)).collect();
+        Ok(symbols
+            .iter()
+            .zip(instruments)
+            .map(|(symbol, instrument)| {
+                let result = instrument.and_then(|(id, native)| {
+                    let response = by_symbol
+                        .remove(&native)
+                        .ok_or_else(|| TradingError::MarketData(format!("No Binance ticker for {}", symbol)))?;
+                    self.price_event(id.symbol(), response)
+                });
+                (symbol.clone(), result)
+            })
+            .collect())
     }
 
     fn exchange_name(&self) -> &str {
         "Binance"
     }
 }
+
+#[async_trait]
+impl OrderBookFetcher for BinanceFetcher {
+    #[instrument(skip(self), fields(exchange = "Binance"))]
+    async fn fetch_order_book(&self, symbol: &str, depth: usize) -> Result<OrderBookUpdate> {
+        let (id, native) = self.instrument(symbol)?;
+        let url = format!("{}/depth?symbol={}&limit={}", self.base_url, native, depth);
+        let response: BinanceDepthResponse = self.get(&url, depth_weight(depth)).await?;
+
+        let update = OrderBookUpdate::snapshot(
+            id.symbol(),
+            response.last_update_id,
+            parse_levels(&response.bids)?,
+            parse_levels(&response.asks)?,
+        )?;
+        debug!(update_id = update.last_update_id, bids = update.bids.len(), asks = update.asks.len(), "Fetched order book");
+
+        self.event_bus.publish_from(
+            self.exchange_name(),
+            crate::engine::Event::OrderBookUpdated(update.clone()),
+            None,
+        )?;
+        Ok(update)
+    }
+}
+
+#[async_trait]
+impl TradeFetcher for BinanceFetcher {
+    #[instrument(skip(self), fields(exchange = "Binance"))]
+    async fn fetch_trades(&self, symbol: &str, limit: usize) -> Result<Vec<TradePrint>> {
+        let (id, native) = self.instrument(symbol)?;
+        let url = format!("{}/trades?symbol={}&limit={}", self.base_url, native, limit);
+        let response: Vec<BinanceTradeResponse> = self.get(&url, 25).await?;
+
+        let mut trades = Vec::with_capacity(response.len());
+        for raw in response {
+            let side = if raw.is_buyer_maker { AggressorSide::Sell } else { AggressorSide::Buy };
+            let trade = TradePrint::new(
+                id.symbol(),
+                raw.id,
+                Decimal::from_str_exact(&raw.price).map_err(TradingError::Decimal)?,
+                Decimal::from_str_exact(&raw.qty).map_err(TradingError::Decimal)?,
+                side,
+                raw.time,
+            )?;
+            let Some(trade) = self.trades.lock().unwrap_or_else(PoisonError::into_inner).process_trade(trade, true) else {
+                continue;
+            };
+            self.event_bus.publish_from(
+                self.exchange_name(),
+                crate::engine::Event::TradePrinted(trade.clone()),
+                None,
+            )?;
+            trades.push(trade);
+        }
+        debug!(trades = trades.len(), "Fetched trades");
+        Ok(trades)
+    }
+}
+
+#[async_trait]
+impl InstrumentFetcher for BinanceFetcher {
+    /// One `GET /exchangeInfo` for every symbol, filtered locally
+    #[instrument(skip(self), fields(exchange = "Binance", count = symbols.len()))]
+    async fn fetch_instruments(&self, symbols: &[String]) -> Result<Vec<Instrument>> {
+        let wanted = symbols
+            .iter()
+            .map(|symbol| self.instrument(symbol).map(|(_, native)| native))
+            .collect::<Result<Vec<String>>>()?;
+        let url = format!("{}/exchangeInfo?permissions=SPOT", self.base_url);
+        let response: BinanceExchangeInfo = self.get(&url, 20).await?;
+
+        let instruments = response
+            .symbols
+            .iter()
+            .filter(|info| info.status == "TRADING")
+            .filter(|info| wanted.is_empty() || wanted.contains(&info.symbol))
+            .filter_map(|info| match parse_symbol_info(info) {
+                Ok(instrument) => Some(instrument),
+                Err(e) => {
+                    warn!(symbol = %info.symbol, error = %e, "Skipping invalid instrument");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        debug!(instruments = instruments.len(), "Fetched exchange info");
+        Ok(instruments)
+    }
+}
+
+#[async_trait]
+impl HistoricalFetcher for BinanceFetcher {
+    #[instrument(skip(self), fields(exchange = "Binance"))]
+    async fn fetch_kline_page(
+        &self,
+        symbol: &str,
+        interval: CandleInterval,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>> {
+        let (id, native) = self.instrument(symbol)?;
+        // endTime is inclusive
+        let url = format!(
+            "{}/klines?symbol={}&interval={}&startTime={}&endTime={}&limit={}",
+            self.base_url,
+            native,
+            interval.as_str(),
+            start_ms,
+            end_ms.saturating_sub(1),
+            self.kline_page_limit()
+        );
+        let response: Vec<BinanceKlineRow> = self.get(&url, 2).await?;
+
+        let candles = response
+            .iter()
+            .map(|row| parse_kline(&id.symbol(), interval, row))
+            .collect::<Result<Vec<_>>>()?;
+        debug!(candles = candles.len(), "Fetched klines");
+        Ok(candles)
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use crate::config::exchange_config::ExchangeType;
+use crate::engine::EventBus;
+use crate::error::{Result, TradingError};
+use crate::instrument::{ContractType, SymbolRegistry};
+use super::event::{parse_quote, PriceEvent};
+use super::stream_trait::{MarketDataStream, PriceStream};
+use super::websocket::{spawn_stream, StreamConfig, StreamProtocol};
+
+pub const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443/ws";
+
+/// `<symbol>@ticker` payload (24h rolling window ticker)
+#[derive(Debug, Deserialize)]
+struct BinanceTickerFrame {
+    #[serde(rename = "e")]
+    event_type: String,
+    #[serde(rename = "E", default)]
+    event_time: Option<u64>,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    last_price: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "b", default)]
+    bid_price: Option<String>,
+    #[serde(rename = "B", default)]
+    bid_qty: Option<String>,
+    #[serde(rename = "a", default)]
+    ask_price: Option<String>,
+    #[serde(rename = "A", default)]
+    ask_qty: Option<String>,
+}
+
+/// Subscribes by native symbol and publishes canonical ones
+struct BinanceProtocol {
+    symbols: Arc<SymbolRegistry>,
+}
+
+impl BinanceProtocol {
+    fn canonical(&self, native: &str) -> Result<String> {
+        Ok(self.symbols.lookup(ExchangeType::Binance, ContractType::Spot, native)?.symbol())
+    }
+}
+
+impl StreamProtocol for BinanceProtocol {
+    fn exchange_name(&self) -> &str {
+        "Binance"
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        let params: Vec<String> = symbols
+            .iter()
+            .map(|symbol| format!("{}@ticker", symbol.to_lowercase()))
+            .collect();
+        vec![serde_json::json!({ "method": "SUBSCRIBE", "params": params, "id": 1 }).to_string()]
+    }
+
+    fn parse(&self, text: &str) -> Result<Option<PriceEvent>> {
+        // Subscription acks look like {"result":null,"id":1}
+        if !text.contains("\"e\"") {
+            return Ok(None);
+        }
+        let frame: BinanceTickerFrame = serde_json::from_str(text)?;
+        if frame.event_type != "24hrTicker" {
+            return Ok(None);
+        }
+
+        let price = Decimal::from_str_exact(&frame.last_price).map_err(TradingError::Decimal)?;
+        let volume = Decimal::from_str_exact(&frame.volume).map_err(TradingError::Decimal)?;
+        let quote = parse_quote(
+            frame.bid_price.as_deref(),
+            frame.bid_qty.as_deref(),
+            frame.ask_price.as_deref(),
+            frame.ask_qty.as_deref(),
+        )?;
+        let event = PriceEvent::new(self.canonical(&frame.symbol)?, price, volume)?
+            .with_exchange_time(frame.event_time)
+            .with_quote(quote);
+        Ok(Some(event))
+    }
+}
+
+/// Binance spot ticker stream
+pub struct BinanceStream {
+    config: StreamConfig,
+    event_bus: EventBus,
+    symbols: Arc<SymbolRegistry>,
+}
+
+impl BinanceStream {
+    pub fn new(event_bus: EventBus) -> Self {
+        Self::with_config(StreamConfig::new(BINANCE_WS_URL), event_bus)
+    }
+
+    pub fn with_config(config: StreamConfig, event_bus: EventBus) -> Self {
+        Self { config, event_bus, symbols: Arc::new(SymbolRegistry::new()) }
+    }
+
+    /// Map symbols through `registry` instead of the naming convention alone
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.symbols = registry;
+        self
+    }
+}
+
+impl MarketDataStream for BinanceStream {
+    fn subscribe(&self, symbols: &[String]) -> Result<PriceStream> {
+        let natives = symbols
+            .iter()
+            .map(|symbol| {
+                let id = self.symbols.resolve(ExchangeType::Binance, ContractType::Spot, symbol)?;
+                Ok(self.symbols.native(&id))
+            })
+            .collect::<Result<Vec<String>>>()?;
+        let protocol = BinanceProtocol { symbols: Arc::clone(&self.symbols) };
+        spawn_stream(protocol, self.config.clone(), self.event_bus.clone(), &natives)
+    }
+
+    fn exchange_name(&self) -> &str {
+        "Binance"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use futures::StreamExt;
+    use crate::market_data::websocket::test_server::{self, Seen};
+    use crate::market_data::monitor::DataQualityConfig;
+
+    fn ticker(symbol: &str, price: &str) -> String {
+        format!(
+            r#"{{"e":"24hrTicker","E":1700000000000,"s":"{}","c":"{}","v":"12.5","b":"{}","B":"1.5","a":"{}","A":"2"}}"#,
+            symbol, price, price, price
+        )
+    }
+
+    #[tokio::test]
+    async fn test_streams_and_resubscribes_after_disconnect() {
+        // First connection drops after one update; the second keeps going
+        let (url, mut seen) = test_server::spawn(
+            |n| {
+                let price = if n == 1 { "100.5" } else { "101.25" };
+                vec![r#"{"result":null,"id":1}"#.to_string(), ticker("BTCUSDT", price)]
+            },
+            |n| n == 1,
+        ).await;
+
+        // The canned tickers carry a 2023 exchange time
+        let config = StreamConfig {
+            reconnect_delay: Duration::from_millis(10),
+            data_quality: DataQualityConfig { max_staleness_ms: None, ..DataQualityConfig::default() },
+            ..StreamConfig::new(url)
+        };
+        let bus = EventBus::new();
+        let stream = BinanceStream::with_config(config, bus.clone());
+        let mut prices = stream.subscribe(&["BTC-USDT".to_string()]).unwrap();
+
+        let first = prices.next().await.unwrap().unwrap();
+        assert_eq!(first.symbol, "BTC-USDT");
+        assert_eq!(first.price, Decimal::new(1005, 1));
+        assert!(first.sequence.is_some());
+        assert_eq!(first.timestamp, 1700000000000);
+        assert_eq!(first.quote.unwrap().ask_size, Decimal::from(2));
+
+        let second = tokio::time::timeout(Duration::from_secs(5), prices.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.price, Decimal::new(10125, 2));
+        assert_eq!(bus.metrics_snapshot().get("PriceUpdated"), Some(&2));
+
+        let mut subscribes = 0;
+        while let Ok(seen) = seen.try_recv() {
+            if let Seen::Text(_, text) = seen {
+                assert!(text.contains("btcusdt@ticker"));
+                subscribes += 1;
+            }
+        }
+        assert_eq!(subscribes, 2);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let config = StreamConfig {
+            reconnect_delay: Duration::from_millis(1),
+            max_reconnect_attempts: Some(2),
+            // Nothing listens on port 9 locally
+            ..StreamConfig::new("ws://127.0.0.1:9")
+        };
+        let stream = BinanceStream::with_config(config, EventBus::new());
+        let mut prices = stream.subscribe(&["BTCUSDT".to_string()]).unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), prices.next()).await.unwrap();
+        assert!(result.unwrap().is_err());
+        assert!(prices.next().await.is_none());
+    }
+}
@@ -1,21 +1,44 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
 use async_trait::async_trait;
+use tracing::{debug, instrument, warn};
 
 use crate::error::{TradingError, Result};
 use crate::engine::EventBus;
-use super::event::PriceEvent;
-use super::fetcher_trait::MarketDataFetcher;
+use crate::config::exchange_config::ExchangeType;
+use crate::instrument::{ContractType, Instrument, InstrumentId, SymbolRegistry};
+use super::event::{parse_quote, PriceEvent};
+use super::fetcher_trait::{HistoricalFetcher, InstrumentFetcher, MarketDataFetcher, OrderBookFetcher, PriceBatch, TradeFetcher};
+use super::candle::{Candle, CandleInterval};
+use super::trade::{AggressorSide, TradePrint};
+use super::order_book::{parse_levels, OrderBookUpdate};
+use super::http::get_json;
+use super::monitor::{DataQualityConfig, PriceMonitor};
+use super::rate_limiter::RateLimiter;
+
+pub const BYBIT_REST_URL: &str = "https://api.bybit.com/v5/market";
 
 /// Bybit API response structures
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BybitResponse<T> {
+    /// Non-zero on API errors, which Bybit reports with HTTP 200
+    #[serde(rename = "retCode", default)]
+    pub ret_code: i64,
+    #[serde(rename = "retMsg", default)]
+    pub ret_msg: String,
     pub result: BybitResult<T>,
+    /// Server time in ms
+    #[serde(default)]
+    pub time: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BybitResult<T> {
+    /// Missing when the request failed (`"result": {}`)
+    #[serde(default = "Vec::new")]
     pub list: Vec<T>,
 }
 
@@ -25,48 +48,236 @@ pub struct BybitTickerData {
     #[serde(rename = "lastPrice")]
     pub last_price: String,
     pub volume24h: String,
+    #[serde(rename = "bid1Price", default)]
+    pub bid1_price: Option<String>,
+    #[serde(rename = "bid1Size", default)]
+    pub bid1_size: Option<String>,
+    #[serde(rename = "ask1Price", default)]
+    pub ask1_price: Option<String>,
+    #[serde(rename = "ask1Size", default)]
+    pub ask1_size: Option<String>,
+}
+
+/// `GET /recent-trade` entry
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BybitTradeData {
+    #[serde(rename = "execId")]
+    pub exec_id: String,
+    pub price: String,
+    pub size: String,
+    /// Taker side, "Buy" or "Sell"
+    pub side: String,
+    pub time: String,
+}
+
+/// `GET /instruments-info?category=spot` entry
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BybitInstrumentData {
+    pub symbol: String,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: String,
+    #[serde(rename = "quoteCoin")]
+    pub quote_coin: String,
+    pub status: String,
+    #[serde(rename = "lotSizeFilter")]
+    pub lot_size_filter: BybitLotSizeFilter,
+    #[serde(rename = "priceFilter")]
+    pub price_filter: BybitPriceFilter,
+}
+
+/// Spot lot size: quantities step by `basePrecision`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BybitLotSizeFilter {
+    #[serde(rename = "basePrecision")]
+    pub base_precision: String,
+    #[serde(rename = "minOrderQty")]
+    pub min_order_qty: String,
+    #[serde(rename = "maxOrderQty")]
+    pub max_order_qty: String,
+    /// Minimum order value in the quote coin
+    #[serde(rename = "minOrderAmt")]
+    pub min_order_amt: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BybitPriceFilter {
+    #[serde(rename = "tickSize")]
+    pub tick_size: String,
+}
+
+fn parse_instrument(data: &BybitInstrumentData) -> Result<Instrument> {
+    let decimal = |text: &str| Decimal::from_str_exact(text).map_err(TradingError::Decimal);
+    let lot = &data.lot_size_filter;
+    let instrument = Instrument {
+        price_tick: decimal(&data.price_filter.tick_size)?,
+        qty_step: decimal(&lot.base_precision)?,
+        min_qty: decimal(&lot.min_order_qty)?,
+        max_qty: Some(decimal(&lot.max_order_qty)?),
+        min_notional: decimal(&lot.min_order_amt)?,
+        ..Instrument::new(
+            InstrumentId::spot(&data.base_coin, &data.quote_coin, ExchangeType::Bybit),
+            data.symbol.clone(),
+        )
+    };
+    instrument.validate()?;
+    Ok(instrument)
+}
+
+/// `GET /kline` row: `[startTime, open, high, low, close, volume, turnover]`
+pub type BybitKlineRow = Vec<String>;
+
+/// Bybit interval parameter; second bars are not offered
+fn kline_interval(interval: CandleInterval) -> Result<&'static str> {
+    match interval {
+        CandleInterval::S1 => Err(TradingError::Config("Bybit has no 1s klines".to_string())),
+        CandleInterval::M1 => Ok("1"),
+        CandleInterval::M5 => Ok("5"),
+        CandleInterval::H1 => Ok("60"),
+    }
+}
+
+fn parse_kline(symbol: &str, interval: CandleInterval, row: &BybitKlineRow) -> Result<Candle> {
+    if row.len() < 6 {
+        return Err(TradingError::MarketData(format!("Invalid Bybit kline: {:?}", row)));
+    }
+    let decimal = |index: usize| Decimal::from_str_exact(&row[index]).map_err(TradingError::Decimal);
+    Ok(Candle {
+        symbol: symbol.to_string(),
+        interval,
+        open_time: row[0].parse().map_err(|_| {
+            TradingError::MarketData(format!("Invalid Bybit kline start: {}", row[0]))
+        })?,
+        open: decimal(1)?,
+        high: decimal(2)?,
+        low: decimal(3)?,
+        close: decimal(4)?,
+        volume: decimal(5)?,
+        // Not reported by Bybit
+        trades: 0,
+    })
+}
+
+/// `GET /orderbook` payload
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BybitOrderBookResponse {
+    #[serde(rename = "retCode", default)]
+    pub ret_code: i64,
+    #[serde(rename = "retMsg", default)]
+    pub ret_msg: String,
+    /// Empty (`{}`) when the request failed
+    #[serde(default)]
+    pub result: BybitOrderBookData,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BybitOrderBookData {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "b")]
+    pub bids: Vec<[String; 2]>,
+    #[serde(rename = "a")]
+    pub asks: Vec<[String; 2]>,
+    /// Update id
+    #[serde(rename = "u")]
+    pub update_id: u64,
 }
 
 pub struct BybitFetcher {
     client: Client,
     base_url: String,
     event_bus: EventBus,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    symbols: Arc<SymbolRegistry>,
+    /// Dedupes and checks trade prints before they are published
+    trades: Mutex<PriceMonitor>,
+}
+
+/// Bybit reports API errors with HTTP 200 and a non-zero retCode
+fn check_ret_code(ret_code: i64, ret_msg: &str) -> Result<()> {
+    if ret_code != 0 {
+        return Err(TradingError::MarketData(format!("Bybit error {}: {}", ret_code, ret_msg)));
+    }
+    Ok(())
 }
 
 impl BybitFetcher {
     pub fn new(event_bus: EventBus) -> Self {
+        Self::with_client(event_bus, Client::new(), BYBIT_REST_URL)
+    }
+
+    /// Use a custom HTTP client and endpoint, e.g. a `MockExchange`
+    pub fn with_client(event_bus: EventBus, client: Client, base_url: impl Into<String>) -> Self {
         Self {
-            client: Client::new(),
-            base_url: "https://api.bybit.com/v5/market".to_string(),
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            trades: Mutex::new(PriceMonitor::new(DataQualityConfig::default(), event_bus.clone())),
             event_bus,
+            rate_limiter: None,
+            symbols: Arc::new(SymbolRegistry::new()),
         }
     }
+
+    /// Take request weight from `limiter` (shared by all fetchers of the exchange)
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Map symbols through `registry` instead of the naming convention alone
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.symbols = registry;
+        self
+    }
+
+    /// Spot instrument for caller input in any spelling, and its Bybit symbol
+    fn instrument(&self, symbol: &str) -> Result<(InstrumentId, String)> {
+        let id = self.symbols.resolve(ExchangeType::Bybit, ContractType::Spot, symbol)?;
+        let native = self.symbols.native(&id);
+        Ok((id, native))
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str, weight: u32) -> Result<T> {
+        get_json(&self.client, url, self.rate_limiter.as_deref(), weight).await
+    }
+
+    /// Price event for a parsed ticker under the canonical `symbol`; the event
+    /// loop publishes it once it passes the data-quality checks
+    fn price_event(&self, symbol: &str, ticker: BybitTickerData, exchange_time: Option<u64>) -> Result<PriceEvent> {
+        let price = Decimal::from_str_exact(&ticker.last_price)
+            .map_err(TradingError::Decimal)?;
+        
+        let volume = Decimal::from_str_exact(&ticker.volume24h)
+            .map_err(TradingError::Decimal)?;
+
+        debug!(symbol, %price, %volume, "Fetched ticker");
+        let quote = parse_quote(
+            ticker.bid1_price.as_deref(),
+            ticker.bid1_size.as_deref(),
+            ticker.ask1_price.as_deref(),
+            ticker.ask1_size.as_deref(),
+        )?;
+        Ok(PriceEvent::new(symbol.to_string(), price, volume)?
+            .with_exchange_time(exchange_time)
+            .with_quote(quote))
+    }
 }
 
 #[async_trait]
 impl MarketDataFetcher for BybitFetcher {
+    #[instrument(skip(self), fields(exchange = "Bybit"))]
     async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
-        // Bybit uses USDT suffix for spot trading
-        // If symbol is BTCUSDT, it stays BTCUSDT
-        // If symbol is BTC, convert to BTCUSDT
-        let bybit_symbol = if symbol.contains("USDT") {
-            symbol.to_string()
-        } else {
-            format!("{}USDT", symbol)
-        };
+        let (id, native) = self.instrument(symbol)?;
 
         let url = format!(
             "{}/tickers?category=spot&symbol={}",
-            self.base_url, bybit_symbol
+            self.base_url, native
         );
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json::<BybitResponse<BybitTickerData>>()
-            .await?;
+        let response: BybitResponse<BybitTickerData> = self.get(&url, 1).await?;
+        check_ret_code(response.ret_code, &response.ret_msg)?;
 
+        let exchange_time = response.time;
         let ticker = response
             .result
             .list
@@ -75,21 +286,186 @@ impl MarketDataFetcher for BybitFetcher {
             .ok_or_else(|| TradingError::MarketData(
                 "No ticker data from Bybit".to_string(),
             ))?;
+        self.price_event(&id.symbol(), ticker, exchange_time)
+    }
 
-        let price = Decimal::from_str_exact(&ticker.last_price)
-            .map_err(|e| TradingError::Decimal(e))?;
-        
-        let volume = Decimal::from_str_exact(&ticker.volume24h)
-            .map_err(|e| TradingError::Decimal(e))?;
-
-        let price_event = PriceEvent::new(symbol.to_string(), price, volume)?;
-
-        self.event_bus.publish(crate::engine::Event::PriceUpdated(price_event.clone()))?;
+    /// One category-wide `GET /tickers?category=spot`, picking out the batch
+    #[instrument(skip(self), fields(exchange = "Bybit", count = symbols.len()))]
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let url = format!("{}/tickers?category=spot", self.base_url);
+        let response: BybitResponse<BybitTickerData> = self.get(&url, 1).await?;
+        check_ret_code(response.ret_code, &response.ret_msg)?;
 
-        Ok(price_event)
+        let exchange_time = response.time;
+        let mut by_symbol: HashMap<String, BybitTickerData> =
+            response.result.list.into_iter().map(|ticker| (ticker.symbol.clone(), ticker)).collect();
+        Ok(symbols
+            .iter()
+            .map(|symbol| {
+                let result = self.instrument(symbol).and_then(|(id, native)| {
+                    let ticker = by_symbol
+                        .remove(&native)
+                        .ok_or_else(|| TradingError::MarketData(format!("No Bybit ticker for {}", symbol)))?;
+                    self.price_event(&id.symbol(), ticker, exchange_time)
+                });
+                (symbol.clone(), result)
+            })
+            .collect())
     }
 
     fn exchange_name(&self) -> &str {
         "Bybit"
     }
 }
+
+#[async_trait]
+impl OrderBookFetcher for BybitFetcher {
+    #[instrument(skip(self), fields(exchange = "Bybit"))]
+    async fn fetch_order_book(&self, symbol: &str, depth: usize) -> Result<OrderBookUpdate> {
+        let (id, native) = self.instrument(symbol)?;
+        let url = format!(
+            "{}/orderbook?category=spot&symbol={}&limit={}",
+            self.base_url, native, depth
+        );
+        let response: BybitOrderBookResponse = self.get(&url, 1).await?;
+        check_ret_code(response.ret_code, &response.ret_msg)?;
+        let book = response.result;
+        if book.symbol.is_empty() {
+            return Err(TradingError::MarketData("No order book data from Bybit".to_string()));
+        }
+
+        let update = OrderBookUpdate::snapshot(
+            id.symbol(),
+            book.update_id,
+            parse_levels(&book.bids)?,
+            parse_levels(&book.asks)?,
+        )?;
+        debug!(update_id = update.last_update_id, bids = update.bids.len(), asks = update.asks.len(), "Fetched order book");
+
+        self.event_bus.publish_from(
+            self.exchange_name(),
+            crate::engine::Event::OrderBookUpdated(update.clone()),
+            None,
+        )?;
+        Ok(update)
+    }
+}
+
+#[async_trait]
+impl TradeFetcher for BybitFetcher {
+    #[instrument(skip(self), fields(exchange = "Bybit"))]
+    async fn fetch_trades(&self, symbol: &str, limit: usize) -> Result<Vec<TradePrint>> {
+        let (id, native) = self.instrument(symbol)?;
+        let url = format!(
+            "{}/recent-trade?category=spot&symbol={}&limit={}",
+            self.base_url, native, limit
+        );
+        let response: BybitResponse<BybitTradeData> = self.get(&url, 1).await?;
+        check_ret_code(response.ret_code, &response.ret_msg)?;
+
+        // Bybit lists the newest trade first
+        let mut trades = Vec::with_capacity(response.result.list.len());
+        for raw in response.result.list.into_iter().rev() {
+            let parse_int = |field: &str, value: &str| {
+                value.parse::<u64>().map_err(|_| {
+                    TradingError::MarketData(format!("Invalid Bybit trade {}: {}", field, value))
+                })
+            };
+            let side = match raw.side.as_str() {
+                "Buy" => AggressorSide::Buy,
+                "Sell" => AggressorSide::Sell,
+                other => return Err(TradingError::MarketData(format!("Unknown Bybit trade side: {}", other))),
+            };
+            let trade = TradePrint::new(
+                id.symbol(),
+                parse_int("execId", &raw.exec_id)?,
+                Decimal::from_str_exact(&raw.price).map_err(TradingError::Decimal)?,
+                Decimal::from_str_exact(&raw.size).map_err(TradingError::Decimal)?,
+                side,
+                parse_int("time", &raw.time)?,
+            )?;
+            let Some(trade) = self.trades.lock().unwrap_or_else(PoisonError::into_inner).process_trade(trade, false) else {
+                continue;
+            };
+            self.event_bus.publish_from(
+                self.exchange_name(),
+                crate::engine::Event::TradePrinted(trade.clone()),
+                None,
+            )?;
+            trades.push(trade);
+        }
+        debug!(trades = trades.len(), "Fetched trades");
+        Ok(trades)
+    }
+}
+
+#[async_trait]
+impl InstrumentFetcher for BybitFetcher {
+    /// One `GET /instruments-info?category=spot`, filtered locally
+    #[instrument(skip(self), fields(exchange = "Bybit", count = symbols.len()))]
+    async fn fetch_instruments(&self, symbols: &[String]) -> Result<Vec<Instrument>> {
+        let wanted = symbols
+            .iter()
+            .map(|symbol| self.instrument(symbol).map(|(_, native)| native))
+            .collect::<Result<Vec<String>>>()?;
+        let url = format!("{}/instruments-info?category=spot", self.base_url);
+        let response: BybitResponse<BybitInstrumentData> = self.get(&url, 1).await?;
+        check_ret_code(response.ret_code, &response.ret_msg)?;
+
+        let instruments = response
+            .result
+            .list
+            .iter()
+            .filter(|data| data.status == "Trading")
+            .filter(|data| wanted.is_empty() || wanted.contains(&data.symbol))
+            .filter_map(|data| match parse_instrument(data) {
+                Ok(instrument) => Some(instrument),
+                Err(e) => {
+                    warn!(symbol = %data.symbol, error = %e, "Skipping invalid instrument");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        debug!(instruments = instruments.len(), "Fetched instruments info");
+        Ok(instruments)
+    }
+}
+
+#[async_trait]
+impl HistoricalFetcher for BybitFetcher {
+    #[instrument(skip(self), fields(exchange = "Bybit"))]
+    async fn fetch_kline_page(
+        &self,
+        symbol: &str,
+        interval: CandleInterval,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>> {
+        let (id, native) = self.instrument(symbol)?;
+        let url = format!(
+            "{}/kline?category=spot&symbol={}&interval={}&start={}&end={}&limit={}",
+            self.base_url,
+            native,
+            kline_interval(interval)?,
+            start_ms,
+            end_ms.saturating_sub(1),
+            self.kline_page_limit()
+        );
+        let response: BybitResponse<BybitKlineRow> = self.get(&url, 1).await?;
+        check_ret_code(response.ret_code, &response.ret_msg)?;
+
+        // Bybit lists the newest bar first
+        let candles = response
+            .result
+            .list
+            .iter()
+            .rev()
+            .map(|row| parse_kline(&id.symbol(), interval, row))
+            .collect::<Result<Vec<_>>>()?;
+        debug!(candles = candles.len(), "Fetched klines");
+        Ok(candles)
+    }
+}
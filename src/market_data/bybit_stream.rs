@@ -0,0 +1,201 @@
+use std::sync::Arc;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use crate::config::exchange_config::ExchangeType;
+use crate::engine::EventBus;
+use crate::error::{Result, TradingError};
+use crate::instrument::{ContractType, SymbolRegistry};
+use super::event::{parse_quote, PriceEvent};
+use super::stream_trait::{MarketDataStream, PriceStream};
+use super::websocket::{spawn_stream, StreamConfig, StreamProtocol};
+
+pub const BYBIT_WS_URL: &str = "wss://stream.bybit.com/v5/public/spot";
+
+#[derive(Debug, Deserialize)]
+struct BybitTickerFrame {
+    topic: String,
+    /// Frame time in ms
+    #[serde(default)]
+    ts: Option<u64>,
+    data: BybitStreamTicker,
+}
+
+/// Delta frames only carry changed fields
+#[derive(Debug, Deserialize)]
+struct BybitStreamTicker {
+    symbol: String,
+    #[serde(rename = "lastPrice")]
+    last_price: Option<String>,
+    volume24h: Option<String>,
+    /// Quote fields are only sent on the derivatives tickers
+    #[serde(rename = "bid1Price")]
+    bid1_price: Option<String>,
+    #[serde(rename = "bid1Size")]
+    bid1_size: Option<String>,
+    #[serde(rename = "ask1Price")]
+    ask1_price: Option<String>,
+    #[serde(rename = "ask1Size")]
+    ask1_size: Option<String>,
+}
+
+/// Subscribes by native symbol and publishes canonical ones
+struct BybitProtocol {
+    symbols: Arc<SymbolRegistry>,
+}
+
+impl BybitProtocol {
+    fn canonical(&self, native: &str) -> Result<String> {
+        Ok(self.symbols.lookup(ExchangeType::Bybit, ContractType::Spot, native)?.symbol())
+    }
+}
+
+impl StreamProtocol for BybitProtocol {
+    fn exchange_name(&self) -> &str {
+        "Bybit"
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        let args: Vec<String> = symbols
+            .iter()
+            .map(|symbol| format!("tickers.{}", symbol.to_uppercase()))
+            .collect();
+        vec![serde_json::json!({ "op": "subscribe", "args": args }).to_string()]
+    }
+
+    /// Bybit drops connections that do not send `{"op":"ping"}` every 20 seconds
+    fn ping_message(&self) -> Option<String> {
+        Some(r#"{"op":"ping"}"#.to_string())
+    }
+
+    fn parse(&self, text: &str) -> Result<Option<PriceEvent>> {
+        // Acks and pongs carry "op"/"success" instead of a topic
+        if !text.contains("\"topic\"") {
+            return Ok(None);
+        }
+        let frame: BybitTickerFrame = serde_json::from_str(text)?;
+        if !frame.topic.starts_with("tickers.") {
+            return Ok(None);
+        }
+
+        let ticker = frame.data;
+        let quote = parse_quote(
+            ticker.bid1_price.as_deref(),
+            ticker.bid1_size.as_deref(),
+            ticker.ask1_price.as_deref(),
+            ticker.ask1_size.as_deref(),
+        )?;
+        let (last_price, volume) = match (ticker.last_price, ticker.volume24h) {
+            (Some(price), Some(volume)) => (price, volume),
+            _ => return Ok(None),
+        };
+        let price = Decimal::from_str_exact(&last_price).map_err(TradingError::Decimal)?;
+        let volume = Decimal::from_str_exact(&volume).map_err(TradingError::Decimal)?;
+        let event = PriceEvent::new(self.canonical(&ticker.symbol)?, price, volume)?
+            .with_exchange_time(frame.ts)
+            .with_quote(quote);
+        Ok(Some(event))
+    }
+}
+
+/// Bybit v5 public ticker stream
+pub struct BybitStream {
+    config: StreamConfig,
+    event_bus: EventBus,
+    symbols: Arc<SymbolRegistry>,
+}
+
+impl BybitStream {
+    pub fn new(event_bus: EventBus) -> Self {
+        Self::with_config(StreamConfig::new(BYBIT_WS_URL), event_bus)
+    }
+
+    pub fn with_config(config: StreamConfig, event_bus: EventBus) -> Self {
+        Self { config, event_bus, symbols: Arc::new(SymbolRegistry::new()) }
+    }
+
+    /// Map symbols through `registry` instead of the naming convention alone
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.symbols = registry;
+        self
+    }
+}
+
+impl MarketDataStream for BybitStream {
+    fn subscribe(&self, symbols: &[String]) -> Result<PriceStream> {
+        let natives = symbols
+            .iter()
+            .map(|symbol| {
+                let id = self.symbols.resolve(ExchangeType::Bybit, ContractType::Spot, symbol)?;
+                Ok(self.symbols.native(&id))
+            })
+            .collect::<Result<Vec<String>>>()?;
+        let protocol = BybitProtocol { symbols: Arc::clone(&self.symbols) };
+        spawn_stream(protocol, self.config.clone(), self.event_bus.clone(), &natives)
+    }
+
+    fn exchange_name(&self) -> &str {
+        "Bybit"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use futures::StreamExt;
+    use crate::market_data::websocket::test_server::{self, Seen};
+
+    #[test]
+    fn test_parse_skips_control_frames() {
+        let protocol = BybitProtocol { symbols: Arc::new(SymbolRegistry::new()) };
+        assert!(protocol.parse(r#"{"success":true,"ret_msg":"pong","op":"ping"}"#).unwrap().is_none());
+        let event = protocol.parse(
+            r#"{"topic":"tickers.ETHUSDT","ts":1700000000000,"type":"snapshot","data":{"symbol":"ETHUSDT","lastPrice":"3000.1","volume24h":"42"}}"#,
+        ).unwrap().unwrap();
+        assert_eq!(event.symbol, "ETH-USDT");
+        assert_eq!(event.price, Decimal::new(30001, 1));
+        assert_eq!(event.timestamp, 1700000000000);
+        assert!(event.quote.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_timeout_reconnects() {
+        // The first connection never answers, so the idle timeout must trigger a reconnect
+        let (url, mut seen) = test_server::spawn(
+            |n| match n {
+                1 => Vec::new(),
+                _ => vec![r#"{"topic":"tickers.BTCUSDT","data":{"symbol":"BTCUSDT","lastPrice":"50000","volume24h":"1"}}"#.to_string()],
+            },
+            |_| false,
+        ).await;
+
+        let config = StreamConfig {
+            heartbeat_interval: Duration::from_millis(20),
+            idle_timeout: Duration::from_millis(70),
+            reconnect_delay: Duration::from_millis(5),
+            ..StreamConfig::new(url)
+        };
+        let stream = BybitStream::with_config(config, EventBus::new());
+        let mut prices = stream.subscribe(&["btcusdt".to_string()]).unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), prices.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.price, Decimal::from(50000));
+
+        let mut pinged = false;
+        let mut reconnected = false;
+        while let Ok(seen) = seen.try_recv() {
+            match seen {
+                Seen::Text(1, text) if text == r#"{"op":"ping"}"# => pinged = true,
+                Seen::Text(2, text) => assert!(text.contains("tickers.BTCUSDT") || text.contains("ping")),
+                Seen::Connected(2) => reconnected = true,
+                _ => {}
+            }
+        }
+        assert!(pinged);
+        assert!(reconnected);
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use crate::engine::{Causation, Event, EventBus, EventEnvelope, EventKind, Subscription};
+use crate::error::{Result, TradingError};
+
+/// Source name stamped on `CandleClosed` events
+const SOURCE: &str = "CandleAggregator";
+
+/// Bar length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CandleInterval {
+    S1,
+    M1,
+    M5,
+    H1,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [
+        CandleInterval::S1,
+        CandleInterval::M1,
+        CandleInterval::M5,
+        CandleInterval::H1,
+    ];
+
+    pub fn as_millis(&self) -> u64 {
+        match self {
+            CandleInterval::S1 => 1_000,
+            CandleInterval::M1 => 60_000,
+            CandleInterval::M5 => 300_000,
+            CandleInterval::H1 => 3_600_000,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::S1 => "1s",
+            CandleInterval::M1 => "1m",
+            CandleInterval::M5 => "5m",
+            CandleInterval::H1 => "1h",
+        }
+    }
+
+    /// Start of the bar containing `timestamp`
+    pub fn open_time(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.as_millis()
+    }
+}
+
+impl fmt::Display for CandleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        CandleInterval::ALL
+            .iter()
+            .copied()
+            .find(|interval| interval.as_str() == s)
+            .ok_or_else(|| TradingError::Config(format!("Unknown candle interval: {}", s)))
+    }
+}
+
+/// OHLCV bar covering `[open_time, open_time + interval)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub symbol: String,
+    pub interval: CandleInterval,
+    pub open_time: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// Traded quantity from trade prints; ticker updates only move prices
+    pub volume: Decimal,
+    /// Trade prints in the bar
+    pub trades: u64,
+}
+
+impl Candle {
+    fn new(symbol: &str, interval: CandleInterval, open_time: u64, price: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            interval,
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Decimal::ZERO,
+            trades: 0,
+        }
+    }
+
+    /// Exclusive end of the bar
+    pub fn close_time(&self) -> u64 {
+        self.open_time + self.interval.as_millis()
+    }
+
+    fn update(&mut self, price: Decimal, quantity: Option<Decimal>) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        if let Some(quantity) = quantity {
+            self.volume += quantity;
+            self.trades += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CandleAggregatorConfig {
+    pub intervals: Vec<CandleInterval>,
+    /// How long a bar stays open after its end for late data
+    pub allowed_lateness: Duration,
+    /// Emit flat zero-volume bars for intervals without data
+    pub fill_empty: bool,
+}
+
+impl Default for CandleAggregatorConfig {
+    fn default() -> Self {
+        Self {
+            intervals: vec![CandleInterval::M1],
+            allowed_lateness: Duration::from_secs(2),
+            fill_empty: true,
+        }
+    }
+}
+
+/// Open bars and close watermark of one (symbol, interval) series
+#[derive(Debug)]
+struct Series {
+    symbol: String,
+    interval: CandleInterval,
+    /// Bars that may still receive data, keyed by open time
+    open: BTreeMap<u64, Candle>,
+    /// Close time of the last emitted bar
+    closed_until: Option<u64>,
+    last_close: Option<Decimal>,
+}
+
+/// Builds OHLCV bars from price updates and trade prints.
+///
+/// A bar closes once data (or `advance_to`) has moved `allowed_lateness`
+/// past its end; observations for bars already closed are counted in
+/// `late_dropped` and otherwise ignored.
+#[derive(Debug)]
+pub struct CandleAggregator {
+    config: CandleAggregatorConfig,
+    series: HashMap<(String, CandleInterval), Series>,
+    late_dropped: u64,
+}
+
+impl CandleAggregator {
+    pub fn new(config: CandleAggregatorConfig) -> Result<Self> {
+        if config.intervals.is_empty() {
+            return Err(TradingError::Config("Candle aggregator needs at least one interval".to_string()));
+        }
+        Ok(Self { config, series: HashMap::new(), late_dropped: 0 })
+    }
+
+    /// Record a price observation; `quantity` is Some for trade prints.
+    /// Returns the bars closed by this observation, oldest first per interval.
+    pub fn on_price(&mut self, symbol: &str, price: Decimal, quantity: Option<Decimal>, timestamp: u64) -> Vec<Candle> {
+        let lateness = self.config.allowed_lateness.as_millis() as u64;
+        let fill_empty = self.config.fill_empty;
+        let mut closed = Vec::new();
+
+        for interval in self.config.intervals.clone() {
+            let series = self.series.entry((symbol.to_string(), interval)).or_insert_with(|| Series {
+                symbol: symbol.to_string(),
+                interval,
+                open: BTreeMap::new(),
+                closed_until: None,
+                last_close: None,
+            });
+            let open_time = interval.open_time(timestamp);
+
+            if series.closed_until.is_some_and(|until| open_time < until) {
+                self.late_dropped += 1;
+                debug!(symbol, %interval, timestamp, "Dropping late price for closed candle");
+            } else {
+                series
+                    .open
+                    .entry(open_time)
+                    .or_insert_with(|| Candle::new(symbol, interval, open_time, price))
+                    .update(price, quantity);
+            }
+
+            close_until(series, timestamp.saturating_sub(lateness), fill_empty, &mut closed);
+        }
+        closed
+    }
+
+    /// Close bars whose lateness window ended before `now_ms`, for use when no data arrives
+    pub fn advance_to(&mut self, now_ms: u64) -> Vec<Candle> {
+        let watermark = now_ms.saturating_sub(self.config.allowed_lateness.as_millis() as u64);
+        let mut closed = Vec::new();
+        for series in self.series.values_mut() {
+            close_until(series, watermark, self.config.fill_empty, &mut closed);
+        }
+        closed
+    }
+
+    /// Observations discarded because their bar had already closed
+    pub fn late_dropped(&self) -> u64 {
+        self.late_dropped
+    }
+
+    /// The bar currently being built for `symbol`, if any
+    pub fn current(&self, symbol: &str, interval: CandleInterval) -> Option<&Candle> {
+        self.series
+            .get(&(symbol.to_string(), interval))
+            .and_then(|series| series.open.values().next_back())
+    }
+
+    /// Subscribe to `PriceUpdated` and `TradePrinted` on `bus` and publish `CandleClosed`
+    pub fn attach(self, bus: &EventBus) -> Result<CandleFeed> {
+        let aggregator = Arc::new(Mutex::new(self));
+        let mut subscriptions = Vec::new();
+        for kind in [EventKind::PriceUpdated, EventKind::TradePrinted] {
+            let aggregator = Arc::clone(&aggregator);
+            let publisher = bus.clone();
+            subscriptions.push(bus.subscribe(kind, move |envelope: &EventEnvelope| {
+                let closed = {
+                    let mut aggregator = lock(&aggregator);
+                    match &envelope.event {
+                        Event::PriceUpdated(price) => {
+                            aggregator.on_price(&price.symbol, price.price, None, price.timestamp)
+                        }
+                        Event::TradePrinted(trade) => {
+                            aggregator.on_price(&trade.symbol, trade.price, Some(trade.quantity), trade.timestamp)
+                        }
+                        _ => Vec::new(),
+                    }
+                };
+                publish(&publisher, closed, Some(envelope.caused()));
+            })?);
+        }
+        Ok(CandleFeed { aggregator, bus: bus.clone(), _subscriptions: subscriptions })
+    }
+}
+
+/// Close every bar of `series` that ends at or before `watermark`
+fn close_until(series: &mut Series, watermark: u64, fill_empty: bool, closed: &mut Vec<Candle>) {
+    loop {
+        let next = series.open.first_key_value().map(|(open_time, candle)| (*open_time, candle.close_time()));
+        match next {
+            Some((open_time, close_time)) if close_time <= watermark => {
+                if fill_empty {
+                    fill_gap(series, open_time, closed);
+                }
+                if let Some(candle) = series.open.remove(&open_time) {
+                    series.closed_until = Some(candle.close_time());
+                    series.last_close = Some(candle.close);
+                    closed.push(candle);
+                }
+            }
+            next => {
+                // Intervals without any data up to the watermark (or the next open bar)
+                if fill_empty {
+                    let limit = next.map_or(u64::MAX, |(open_time, _)| open_time);
+                    let interval = series.interval.as_millis();
+                    let end = watermark - watermark % interval;
+                    fill_gap(series, end.min(limit), closed);
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// Emit flat bars at the last close from `closed_until` up to `until`
+fn fill_gap(series: &mut Series, until: u64, closed: &mut Vec<Candle>) {
+    let (Some(mut start), Some(last_close)) = (series.closed_until, series.last_close) else {
+        return;
+    };
+    while start < until {
+        let empty = Candle::new(&series.symbol, series.interval, start, last_close);
+        start = empty.close_time();
+        closed.push(empty);
+    }
+    series.closed_until = Some(start);
+}
+
+fn publish(bus: &EventBus, closed: Vec<Candle>, cause: Option<Causation>) {
+    for candle in closed {
+        let _ = bus.publish_from(SOURCE, Event::CandleClosed(candle), cause);
+    }
+}
+
+fn lock(aggregator: &Mutex<CandleAggregator>) -> MutexGuard<'_, CandleAggregator> {
+    aggregator.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A `CandleAggregator` wired to the bus; dropping it unsubscribes
+pub struct CandleFeed {
+    aggregator: Arc<Mutex<CandleAggregator>>,
+    bus: EventBus,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CandleFeed {
+    /// Close and publish bars that ended before `now_ms` without new data
+    pub fn advance_to(&self, now_ms: u64) {
+        let closed = lock(&self.aggregator).advance_to(now_ms);
+        publish(&self.bus, closed, None);
+    }
+
+    pub fn late_dropped(&self) -> u64 {
+        lock(&self.aggregator).late_dropped()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::event::PriceEvent;
+
+    fn aggregator(lateness_ms: u64, fill_empty: bool) -> CandleAggregator {
+        CandleAggregator::new(CandleAggregatorConfig {
+            intervals: vec![CandleInterval::S1],
+            allowed_lateness: Duration::from_millis(lateness_ms),
+            fill_empty,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_builds_ohlcv_and_accepts_late_data() {
+        let mut agg = aggregator(500, false);
+        assert!(agg.on_price("BTC", Decimal::from(100), Some(Decimal::ONE), 1_000).is_empty());
+        agg.on_price("BTC", Decimal::from(105), None, 1_400);
+        // Next bar starts, but the first is still within its lateness window
+        assert!(agg.on_price("BTC", Decimal::from(103), Some(Decimal::TWO), 2_100).is_empty());
+        agg.on_price("BTC", Decimal::from(95), Some(Decimal::ONE), 1_900);
+
+        let closed = agg.on_price("BTC", Decimal::from(104), None, 2_600);
+        assert_eq!(closed.len(), 1);
+        let bar = &closed[0];
+        assert_eq!((bar.open_time, bar.close_time()), (1_000, 2_000));
+        assert_eq!((bar.open, bar.high, bar.low, bar.close), (
+            Decimal::from(100), Decimal::from(105), Decimal::from(95), Decimal::from(95),
+        ));
+        assert_eq!(bar.volume, Decimal::TWO);
+        assert_eq!(bar.trades, 2);
+
+        // Too late now
+        agg.on_price("BTC", Decimal::from(1), None, 1_999);
+        assert_eq!(agg.late_dropped(), 1);
+        assert_eq!(agg.current("BTC", CandleInterval::S1).unwrap().low, Decimal::from(103));
+    }
+
+    #[test]
+    fn test_fills_empty_intervals() {
+        let mut agg = aggregator(0, true);
+        agg.on_price("BTC", Decimal::from(100), None, 1_000);
+        let closed = agg.on_price("BTC", Decimal::from(110), None, 4_200);
+        let opens: Vec<u64> = closed.iter().map(|c| c.open_time).collect();
+        assert_eq!(opens, vec![1_000, 2_000, 3_000]);
+        assert!(closed[1..].iter().all(|c| c.close == Decimal::from(100) && c.trades == 0));
+
+        // No data at all: the clock alone closes the open bar and the silence after it
+        let closed = agg.advance_to(7_000);
+        let opens: Vec<u64> = closed.iter().map(|c| c.open_time).collect();
+        assert_eq!(opens, vec![4_000, 5_000, 6_000]);
+        assert_eq!(closed[2].close, Decimal::from(110));
+    }
+
+    #[test]
+    fn test_feed_publishes_candle_closed() {
+        let bus = EventBus::new();
+        let feed = aggregator(0, false).attach(&bus).unwrap();
+
+        for (price, ts) in [(100, 1_000), (101, 1_500), (102, 2_000)] {
+            let mut event = PriceEvent::new("BTC".to_string(), Decimal::from(price), Decimal::ONE).unwrap();
+            event.timestamp = ts;
+            bus.publish(Event::PriceUpdated(event)).unwrap();
+        }
+        assert_eq!(bus.metrics_snapshot().get("CandleClosed"), Some(&1));
+
+        feed.advance_to(3_000);
+        assert_eq!(bus.metrics_snapshot().get("CandleClosed"), Some(&2));
+        drop(feed);
+        assert_eq!(bus.subscriber_count(EventKind::PriceUpdated), 0);
+    }
+}
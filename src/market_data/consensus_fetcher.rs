@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use async_trait::async_trait;
+use futures::future::join_all;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use crate::error::{Result, TradingError};
+use crate::engine::{EventBus, Event};
+use super::fetcher_trait::{MarketDataFetcher, PriceBatch};
+use super::event::PriceEvent;
+use super::normalizer::PriceValidator;
+
+const SOURCE: &str = "Consensus";
+
+/// How accepted venue prices are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsensusMethod {
+    Median,
+    /// Weighted by each venue's reported volume; median when no volume is reported
+    VolumeWeighted,
+}
+
+impl fmt::Display for ConsensusMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsensusMethod::Median => write!(f, "median"),
+            ConsensusMethod::VolumeWeighted => write!(f, "vwap"),
+        }
+    }
+}
+
+impl FromStr for ConsensusMethod {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "median" => Ok(ConsensusMethod::Median),
+            "vwap" | "volume_weighted" => Ok(ConsensusMethod::VolumeWeighted),
+            other => Err(TradingError::Config(format!("Unknown consensus method: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsensusConfig {
+    pub method: ConsensusMethod,
+    /// Quotes further than this fraction from the median of all quotes are rejected
+    pub max_deviation: Decimal,
+    /// Accepted quotes needed to produce a price
+    pub min_sources: usize,
+}
+
+impl ConsensusConfig {
+    pub fn new(method: ConsensusMethod) -> Self {
+        Self { method, ..Self::default() }
+    }
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        Self {
+            method: ConsensusMethod::Median,
+            max_deviation: Decimal::new(5, 3),
+            min_sources: 2,
+        }
+    }
+}
+
+/// One venue's quote in a consensus round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueQuote {
+    pub exchange: String,
+    pub price: Decimal,
+    /// Relative distance from the consensus price
+    pub deviation: Decimal,
+    pub rejected: bool,
+}
+
+/// Queries every venue concurrently and returns a consensus price.
+///
+/// Only the per-venue `Event::PriceDivergence` is published here; like any
+/// fetcher, the consensus price is published by the event loop.
+pub struct ConsensusFetcher {
+    sources: Vec<Box<dyn MarketDataFetcher>>,
+    config: ConsensusConfig,
+    event_bus: EventBus,
+}
+
+impl ConsensusFetcher {
+    pub fn new(
+        sources: Vec<Box<dyn MarketDataFetcher>>,
+        config: ConsensusConfig,
+        event_bus: EventBus,
+    ) -> Result<Self> {
+        if config.min_sources == 0 || sources.len() < config.min_sources {
+            return Err(TradingError::Config(format!(
+                "Consensus needs at least {} sources, got {}",
+                config.min_sources.max(1),
+                sources.len()
+            )));
+        }
+        Ok(Self { sources, config, event_bus })
+    }
+
+    /// Combine `quotes` into a price, or None when there are none
+    pub fn consensus(method: ConsensusMethod, quotes: &[PriceEvent]) -> Option<Decimal> {
+        let prices: Vec<Decimal> = quotes.iter().map(|quote| quote.price).collect();
+        match method {
+            ConsensusMethod::Median => median(&prices),
+            ConsensusMethod::VolumeWeighted => {
+                let volume: Decimal = quotes.iter().map(|quote| quote.volume).sum();
+                if volume.is_zero() {
+                    return median(&prices);
+                }
+                let notional: Decimal = quotes.iter().map(|quote| quote.price * quote.volume).sum();
+                Some(notional / volume)
+            }
+        }
+    }
+
+    /// Consensus from one round of venue results, in source order
+    fn combine(&self, symbol: &str, results: Vec<Result<PriceEvent>>) -> Result<PriceEvent> {
+        let mut errors = Vec::new();
+        let mut quotes = Vec::new();
+        for (source, result) in self.sources.iter().zip(results) {
+            match result.and_then(|quote| PriceValidator::validate(&quote).map(|_| quote)) {
+                Ok(quote) => quotes.push((source.exchange_name(), quote)),
+                Err(e) => {
+                    warn!(symbol, exchange = source.exchange_name(), error = %e, "Venue quote failed");
+                    errors.push(format!("{}: {}", source.exchange_name(), e));
+                }
+            }
+        }
+        if quotes.len() < self.config.min_sources {
+            return Err(TradingError::MarketData(format!(
+                "Consensus for {} needs {} quotes, got {} ({})",
+                symbol,
+                self.config.min_sources,
+                quotes.len(),
+                errors.join("; ")
+            )));
+        }
+        // Venues publish the canonical symbol whatever spelling was requested
+        let symbol = quotes.first().map_or_else(|| symbol.to_string(), |(_, quote)| quote.symbol.clone());
+        let symbol = symbol.as_str();
+
+        // Outliers are judged against the median of every quote, which one bad print cannot move far
+        let all: Vec<Decimal> = quotes.iter().map(|(_, quote)| quote.price).collect();
+        let reference = median(&all).unwrap_or_default();
+        let (accepted, rejected): (Vec<_>, Vec<_>) = quotes.into_iter().partition(|(_, quote)| {
+            PriceValidator::validate_against(quote, reference, self.config.max_deviation).is_ok()
+        });
+        let accepted_quotes: Vec<PriceEvent> = accepted.iter().map(|(_, quote)| quote.clone()).collect();
+        let consensus = Self::consensus(self.config.method, &accepted_quotes).unwrap_or(reference);
+
+        let venue = |(exchange, quote): &(&str, PriceEvent), rejected: bool| VenueQuote {
+            exchange: exchange.to_string(),
+            price: quote.price,
+            deviation: PriceValidator::deviation(quote.price, consensus),
+            rejected,
+        };
+        let venues: Vec<VenueQuote> = accepted
+            .iter()
+            .map(|quote| venue(quote, false))
+            .chain(rejected.iter().map(|quote| venue(quote, true)))
+            .collect();
+        for outlier in venues.iter().filter(|venue| venue.rejected) {
+            warn!(symbol, exchange = %outlier.exchange, price = %outlier.price, consensus = %consensus,
+                deviation = %outlier.deviation, "Rejected outlier quote");
+        }
+        let _ = self.event_bus.publish_from(
+            SOURCE,
+            Event::PriceDivergence { symbol: symbol.to_string(), consensus, venues },
+            None,
+        );
+
+        if accepted.len() < self.config.min_sources {
+            return Err(TradingError::MarketData(format!(
+                "No consensus for {}: only {} of {} quotes within {} of {}",
+                symbol,
+                accepted.len(),
+                accepted.len() + rejected.len(),
+                self.config.max_deviation,
+                reference
+            )));
+        }
+
+        // Keep the quote and timestamps of the venue closest to consensus
+        let mut event = accepted
+            .into_iter()
+            .map(|(_, quote)| quote)
+            .min_by_key(|quote| (quote.price - consensus).abs())
+            .expect("at least min_sources accepted");
+        event.price = consensus;
+        event.volume = accepted_quotes.iter().map(|quote| quote.volume).sum();
+        debug!(symbol, price = %consensus, method = %self.config.method, "Consensus price");
+        Ok(event)
+    }
+}
+
+fn median(prices: &[Decimal]) -> Option<Decimal> {
+    let mut sorted = prices.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) / Decimal::TWO),
+        _ => Some(sorted[mid]),
+    }
+}
+
+#[async_trait]
+impl MarketDataFetcher for ConsensusFetcher {
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+        let results = join_all(self.sources.iter().map(|source| source.fetch_price(symbol))).await;
+        self.combine(symbol, results)
+    }
+
+    /// One batch per venue, all venues concurrently
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+        let batches = join_all(self.sources.iter().map(|source| source.fetch_prices(symbols))).await;
+
+        let mut by_venue: Vec<HashMap<String, Result<PriceEvent>>> = Vec::with_capacity(batches.len());
+        for batch in batches {
+            by_venue.push(match batch {
+                Ok(batch) => batch.into_iter().collect(),
+                // Transport failures surface per symbol, like any other missing quote
+                Err(e) => symbols
+                    .iter()
+                    .map(|symbol| (symbol.clone(), Err(TradingError::MarketData(e.to_string()))))
+                    .collect(),
+            });
+        }
+
+        Ok(symbols
+            .iter()
+            .map(|symbol| {
+                let results = by_venue
+                    .iter_mut()
+                    .map(|venue| {
+                        venue.remove(symbol).unwrap_or_else(|| {
+                            Err(TradingError::MarketData(format!("No quote for {}", symbol)))
+                        })
+                    })
+                    .collect();
+                (symbol.clone(), self.combine(symbol, results))
+            })
+            .collect())
+    }
+
+    fn exchange_name(&self) -> &str {
+        SOURCE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::EventKind;
+    use std::sync::{Arc, Mutex};
+
+    struct Fixed {
+        name: &'static str,
+        price: Option<Decimal>,
+        volume: Decimal,
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for Fixed {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            match self.price {
+                Some(price) => PriceEvent::new(symbol.to_string(), price, self.volume),
+                None => Err(TradingError::MarketData(format!("{} unreachable", self.name))),
+            }
+        }
+
+        fn exchange_name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn venue(name: &'static str, price: Option<i64>, volume: i64) -> Box<dyn MarketDataFetcher> {
+        Box::new(Fixed { name, price: price.map(Decimal::from), volume: Decimal::from(volume) })
+    }
+
+    #[tokio::test]
+    async fn test_rejects_outlier_and_publishes_divergence() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        bus.subscribe(EventKind::PriceDivergence, move |envelope| {
+            if let Event::PriceDivergence { venues, .. } = &envelope.event {
+                sink.lock().unwrap().extend(venues.clone());
+            }
+        }).unwrap().detach();
+
+        let sources = vec![venue("A", Some(100), 1), venue("B", Some(102), 1), venue("C", Some(150), 1)];
+        let config = ConsensusConfig { max_deviation: Decimal::new(5, 2), ..ConsensusConfig::default() };
+        let fetcher = ConsensusFetcher::new(sources, config, bus.clone()).unwrap();
+
+        let event = fetcher.fetch_price("BTCUSDT").await.unwrap();
+        assert_eq!(event.price, Decimal::from(101));
+        assert_eq!(event.volume, Decimal::from(2));
+        assert_eq!(bus.metrics_snapshot().get("PriceUpdated"), None);
+
+        let venues = seen.lock().unwrap();
+        let outlier = venues.iter().find(|venue| venue.exchange == "C").unwrap();
+        assert!(outlier.rejected);
+        assert_eq!(outlier.deviation, Decimal::from(49) / Decimal::from(101));
+        assert_eq!(venues.iter().filter(|venue| !venue.rejected).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_volume_weighted_and_too_few_quotes() {
+        let bus = EventBus::new();
+        let sources = vec![venue("A", Some(100), 3), venue("B", Some(104), 1), venue("C", None, 0)];
+        let fetcher = ConsensusFetcher::new(sources, ConsensusConfig::new(ConsensusMethod::VolumeWeighted), bus.clone()).unwrap();
+        let err = fetcher.fetch_price("BTCUSDT").await.unwrap_err().to_string();
+        assert!(err.starts_with("Market data error: No consensus for BTCUSDT: only 0 of 2"), "{}", err);
+
+        let config = ConsensusConfig {
+            method: ConsensusMethod::VolumeWeighted,
+            max_deviation: Decimal::new(5, 2),
+            min_sources: 2,
+        };
+        let sources = vec![venue("A", Some(100), 3), venue("B", Some(104), 1), venue("C", None, 0)];
+        let fetcher = ConsensusFetcher::new(sources, config, bus).unwrap();
+        assert_eq!(fetcher.fetch_price("BTCUSDT").await.unwrap().price, Decimal::from(101));
+
+        assert!(ConsensusFetcher::new(vec![venue("A", Some(1), 1)], ConsensusConfig::default(), EventBus::new()).is_err());
+    }
+}
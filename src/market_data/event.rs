@@ -3,12 +3,44 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use rust_decimal::Decimal;
 use crate::error::{TradingError, Result};
 
+/// Top of book at the time of a price update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    pub bid: Decimal,
+    pub bid_size: Decimal,
+    pub ask: Decimal,
+    pub ask_size: Decimal,
+}
+
+impl Quote {
+    pub fn new(bid: Decimal, bid_size: Decimal, ask: Decimal, ask_size: Decimal) -> Self {
+        Self { bid, bid_size, ask, ask_size }
+    }
+
+    pub fn spread(&self) -> Decimal {
+        self.ask - self.bid
+    }
+
+    pub fn mid(&self) -> Decimal {
+        (self.bid + self.ask) / Decimal::TWO
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceEvent {
     pub symbol: String,
     pub price: Decimal,
+    /// Event time in ms: exchange-reported when available, otherwise `received_at`
     pub timestamp: u64,
     pub volume: Decimal,
+    /// Bus sequence number of the `PriceUpdated` event that carried this price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<Quote>,
+    /// Local wall-clock time (ms) the update was received
+    #[serde(default)]
+    pub received_at: u64,
 }
 
 impl PriceEvent {
@@ -23,6 +55,85 @@ impl PriceEvent {
             price,
             timestamp,
             volume,
+            sequence: None,
+            quote: None,
+            received_at: timestamp,
         })
     }
+
+    pub fn with_quote(mut self, quote: Option<Quote>) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Use the exchange-reported event time as `timestamp`
+    pub fn with_exchange_time(mut self, exchange_time: Option<u64>) -> Self {
+        if let Some(time) = exchange_time {
+            self.timestamp = time;
+        }
+        self
+    }
+
+    /// Exchange-to-local delay in ms (0 without an exchange timestamp or with clock skew)
+    pub fn latency_ms(&self) -> u64 {
+        self.received_at.saturating_sub(self.timestamp)
+    }
+
+    /// Age in ms of the exchange event at local time `now_ms`
+    pub fn age_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.timestamp)
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        self.quote.map(|quote| quote.spread())
+    }
+}
+
+/// Parse an optional exchange decimal string; empty strings count as missing
+pub(crate) fn parse_optional_decimal(value: Option<&str>) -> Result<Option<Decimal>> {
+    match value {
+        Some(text) if !text.is_empty() => Ok(Some(Decimal::from_str_exact(text).map_err(TradingError::Decimal)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Build a quote when all four fields are present
+pub(crate) fn parse_quote(
+    bid: Option<&str>,
+    bid_size: Option<&str>,
+    ask: Option<&str>,
+    ask_size: Option<&str>,
+) -> Result<Option<Quote>> {
+    let fields = (
+        parse_optional_decimal(bid)?,
+        parse_optional_decimal(bid_size)?,
+        parse_optional_decimal(ask)?,
+        parse_optional_decimal(ask_size)?,
+    );
+    Ok(match fields {
+        (Some(bid), Some(bid_size), Some(ask), Some(ask_size)) => Some(Quote::new(bid, bid_size, ask, ask_size)),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exchange_time_and_quote() {
+        let event = PriceEvent::new("BTCUSDT".to_string(), Decimal::from(100), Decimal::ONE)
+            .unwrap()
+            .with_exchange_time(Some(1_000))
+            .with_quote(parse_quote(Some("99.5"), Some("2"), Some("100.5"), Some("3")).unwrap());
+
+        assert_eq!(event.timestamp, 1_000);
+        assert!(event.received_at > event.timestamp);
+        assert_eq!(event.latency_ms(), event.received_at - 1_000);
+        assert_eq!(event.spread(), Some(Decimal::ONE));
+        assert_eq!(event.quote.unwrap().mid(), Decimal::from(100));
+
+        // Missing or empty fields mean no quote
+        assert_eq!(parse_quote(Some("1"), Some(""), Some("2"), Some("1")).unwrap(), None);
+    }
 }
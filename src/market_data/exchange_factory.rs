@@ -1,47 +1,174 @@
+use std::sync::{Arc, OnceLock};
+use reqwest::Client;
+use crate::config::env_config::EnvConfig;
 use crate::config::exchange_config::{ExchangeConfig, ExchangeType};
 use crate::error::Result;
 use crate::engine::EventBus;
-use super::fetcher_trait::MarketDataFetcher;
-use super::binance_fetcher::BinanceFetcher;
-use super::bybit_fetcher::BybitFetcher;
-use super::resilient_fetcher::ResilientFetcher;
+use crate::instrument::SymbolRegistry;
+use super::fetcher_trait::{HistoricalFetcher, InstrumentFetcher, MarketDataFetcher, OrderBookFetcher, TradeFetcher};
+use super::binance_fetcher::{BinanceFetcher, BINANCE_REST_URL};
+use super::bybit_fetcher::{BybitFetcher, BYBIT_REST_URL};
+use super::resilient_fetcher::{HealthConfig, ResilientFetcher};
+use super::consensus_fetcher::{ConsensusConfig, ConsensusFetcher};
+use super::guarded_fetcher::{GuardConfig, GuardedFetcher};
+use super::rate_limiter::RateLimiter;
+use super::stream_trait::MarketDataStream;
+use super::binance_stream::{BinanceStream, BINANCE_WS_URL};
+use super::bybit_stream::{BybitStream, BYBIT_WS_URL};
+use super::websocket::StreamConfig;
 
 pub struct ExchangeFactory;
 
 impl ExchangeFactory {
+    /// Price fetcher for the configured exchange, with timeouts, retries and a circuit breaker
     pub fn create_fetcher(
         config: &ExchangeConfig,
         event_bus: EventBus,
     ) -> Result<Box<dyn MarketDataFetcher>> {
+        Ok(Self::fetcher(config, event_bus))
+    }
+
+    /// Depth fetcher for the configured exchange; also serves prices
+    pub fn create_book_fetcher(
+        config: &ExchangeConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn OrderBookFetcher>> {
+        match &config.exchange_type {
+            ExchangeType::Binance => Ok(Box::new(Self::binance(config, event_bus))),
+            ExchangeType::Bybit => Ok(Box::new(Self::bybit(config, event_bus))),
+        }
+    }
+
+    /// Public trade tape fetcher for the configured exchange; also serves prices
+    pub fn create_trade_fetcher(
+        config: &ExchangeConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn TradeFetcher>> {
+        match &config.exchange_type {
+            ExchangeType::Binance => Ok(Box::new(Self::binance(config, event_bus))),
+            ExchangeType::Bybit => Ok(Box::new(Self::bybit(config, event_bus))),
+        }
+    }
+
+    /// Historical kline fetcher for the configured exchange; also serves prices
+    pub fn create_historical_fetcher(
+        config: &ExchangeConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn HistoricalFetcher>> {
+        match &config.exchange_type {
+            ExchangeType::Binance => Ok(Box::new(Self::binance(config, event_bus))),
+            ExchangeType::Bybit => Ok(Box::new(Self::bybit(config, event_bus))),
+        }
+    }
+
+    /// Instrument rules fetcher for the configured exchange; also serves prices
+    pub fn create_instrument_fetcher(
+        config: &ExchangeConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn InstrumentFetcher>> {
+        match &config.exchange_type {
+            ExchangeType::Binance => Ok(Box::new(Self::binance(config, event_bus))),
+            ExchangeType::Bybit => Ok(Box::new(Self::bybit(config, event_bus))),
+        }
+    }
+
+    /// WebSocket price stream for the configured exchange
+    pub fn create_stream(
+        config: &ExchangeConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn MarketDataStream>> {
+        let stream_config = StreamConfig::new(Self::ws_url(&config.exchange_type, config.ws_url.as_deref()));
         match &config.exchange_type {
-            ExchangeType::Binance => {
-                Ok(Box::new(BinanceFetcher::new(event_bus)))
-            }
-            ExchangeType::Bybit => {
-                Ok(Box::new(BybitFetcher::new(event_bus)))
-            }
+            ExchangeType::Binance => Ok(Box::new(
+                BinanceStream::with_config(stream_config, event_bus).with_symbols(Self::symbols(config)),
+            )),
+            ExchangeType::Bybit => Ok(Box::new(
+                BybitStream::with_config(stream_config, event_bus).with_symbols(Self::symbols(config)),
+            )),
         }
     }
 
     pub fn create_resilient_fetcher(
-        primary: ExchangeType,
-        secondary: ExchangeType,
+        primary: &ExchangeConfig,
+        secondary: &ExchangeConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn MarketDataFetcher>> {
+        Self::create_failover_fetcher(&[primary.clone(), secondary.clone()], HealthConfig::default(), event_bus)
+    }
+
+    /// Health-routed failover across `configs`, in priority order
+    pub fn create_failover_fetcher(
+        configs: &[ExchangeConfig],
+        health: HealthConfig,
         event_bus: EventBus,
     ) -> Result<Box<dyn MarketDataFetcher>> {
-        let primary_fetcher: Box<dyn MarketDataFetcher> = match primary {
-            ExchangeType::Binance => Box::new(BinanceFetcher::new(event_bus.clone())),
-            ExchangeType::Bybit => Box::new(BybitFetcher::new(event_bus.clone())),
+        let sources = configs.iter().map(|config| Self::fetcher(config, event_bus.clone())).collect();
+        Ok(Box::new(ResilientFetcher::with_sources(sources, health, event_bus)?))
+    }
+
+    /// Consensus price across `configs`, queried concurrently
+    pub fn create_consensus_fetcher(
+        configs: &[ExchangeConfig],
+        config: ConsensusConfig,
+        event_bus: EventBus,
+    ) -> Result<Box<dyn MarketDataFetcher>> {
+        let sources = configs.iter().map(|exchange| Self::fetcher(exchange, event_bus.clone())).collect();
+        Ok(Box::new(ConsensusFetcher::new(sources, config, event_bus)?))
+    }
+
+    /// REST endpoint: explicit override, then `<EXCHANGE>_BASE_URL`, then the public API
+    pub fn rest_url(exchange_type: &ExchangeType, configured: Option<&str>) -> String {
+        let (env, default) = match exchange_type {
+            ExchangeType::Binance => (EnvConfig::get_binance_base_url(), BINANCE_REST_URL),
+            ExchangeType::Bybit => (EnvConfig::get_bybit_base_url(), BYBIT_REST_URL),
         };
+        configured.map(str::to_string).or(env).unwrap_or_else(|| default.to_string())
+    }
 
-        let secondary_fetcher: Box<dyn MarketDataFetcher> = match secondary {
-            ExchangeType::Binance => Box::new(BinanceFetcher::new(event_bus.clone())),
-            ExchangeType::Bybit => Box::new(BybitFetcher::new(event_bus.clone())),
+    /// WebSocket endpoint: explicit override, then `<EXCHANGE>_WS_URL`, then the public stream
+    pub fn ws_url(exchange_type: &ExchangeType, configured: Option<&str>) -> String {
+        let (env, default) = match exchange_type {
+            ExchangeType::Binance => (EnvConfig::get_binance_ws_url(), BINANCE_WS_URL),
+            ExchangeType::Bybit => (EnvConfig::get_bybit_ws_url(), BYBIT_WS_URL),
         };
+        configured.map(str::to_string).or(env).unwrap_or_else(|| default.to_string())
+    }
+
+    /// Request weight budget shared by every fetcher of `exchange_type` in the process,
+    /// since exchanges limit per IP
+    pub fn rate_limiter(exchange_type: &ExchangeType) -> Arc<RateLimiter> {
+        static BINANCE: OnceLock<Arc<RateLimiter>> = OnceLock::new();
+        static BYBIT: OnceLock<Arc<RateLimiter>> = OnceLock::new();
+        match exchange_type {
+            ExchangeType::Binance => Arc::clone(BINANCE.get_or_init(|| Arc::new(RateLimiter::binance()))),
+            ExchangeType::Bybit => Arc::clone(BYBIT.get_or_init(|| Arc::new(RateLimiter::bybit()))),
+        }
+    }
+
+    /// Symbol mapping of `config`, default spellings when it has none
+    fn symbols(config: &ExchangeConfig) -> Arc<SymbolRegistry> {
+        config.symbols.clone().unwrap_or_else(|| Arc::new(SymbolRegistry::new()))
+    }
+
+    fn binance(config: &ExchangeConfig, event_bus: EventBus) -> BinanceFetcher {
+        let base_url = Self::rest_url(&ExchangeType::Binance, config.base_url.as_deref());
+        BinanceFetcher::with_client(event_bus, Client::new(), base_url)
+            .with_rate_limiter(Self::rate_limiter(&ExchangeType::Binance))
+            .with_symbols(Self::symbols(config))
+    }
 
-        Ok(Box::new(ResilientFetcher::new(
-            primary_fetcher,
-            secondary_fetcher,
-            event_bus,
-        )))
+    fn bybit(config: &ExchangeConfig, event_bus: EventBus) -> BybitFetcher {
+        let base_url = Self::rest_url(&ExchangeType::Bybit, config.base_url.as_deref());
+        BybitFetcher::with_client(event_bus, Client::new(), base_url)
+            .with_rate_limiter(Self::rate_limiter(&ExchangeType::Bybit))
+            .with_symbols(Self::symbols(config))
+    }
+
+    fn fetcher(config: &ExchangeConfig, event_bus: EventBus) -> Box<dyn MarketDataFetcher> {
+        let fetcher: Box<dyn MarketDataFetcher> = match config.exchange_type {
+            ExchangeType::Binance => Box::new(Self::binance(config, event_bus.clone())),
+            ExchangeType::Bybit => Box::new(Self::bybit(config, event_bus.clone())),
+        };
+        Box::new(GuardedFetcher::new(fetcher, GuardConfig::default(), event_bus))
     }
 }
@@ -1,9 +1,85 @@
 use async_trait::async_trait;
 use crate::error::Result;
+use crate::instrument::Instrument;
 use super::event::PriceEvent;
+use super::order_book::OrderBookUpdate;
+use super::trade::TradePrint;
+use super::candle::{Candle, CandleInterval};
+use super::history::page_klines;
+
+/// Per-symbol results of a batch fetch, in request order
+pub type PriceBatch = Vec<(String, Result<PriceEvent>)>;
 
 #[async_trait]
 pub trait MarketDataFetcher: Send + Sync {
     async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent>;
+
+    /// Fetch several symbols, in one request where the exchange allows it.
+    /// The outer error is a transport failure of the whole batch; a symbol
+    /// that is unknown or unparsable fails only its own entry. The default
+    /// calls `fetch_price` per symbol, so every error lands in its entry.
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+        let mut results = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            results.push((symbol.clone(), self.fetch_price(symbol).await));
+        }
+        Ok(results)
+    }
+
     fn exchange_name(&self) -> &str;
 }
+
+/// Level-2 depth snapshots over REST
+#[async_trait]
+pub trait OrderBookFetcher: MarketDataFetcher {
+    /// Fetch the top `depth` levels per side as a snapshot update
+    async fn fetch_order_book(&self, symbol: &str, depth: usize) -> Result<OrderBookUpdate>;
+}
+
+/// Public trade tape over REST
+#[async_trait]
+pub trait TradeFetcher: MarketDataFetcher {
+    /// Fetch up to `limit` most recent trades, oldest first. Trades returned
+    /// by an earlier call and prints withheld by the data-quality checks are
+    /// left out, so only new trades are published.
+    async fn fetch_trades(&self, symbol: &str, limit: usize) -> Result<Vec<TradePrint>>;
+}
+
+/// Instrument trading rules over REST
+#[async_trait]
+pub trait InstrumentFetcher: MarketDataFetcher {
+    /// Fetch the rules of `symbols` (any spelling), or of every spot
+    /// instrument when empty. Symbols the exchange does not list are left out.
+    async fn fetch_instruments(&self, symbols: &[String]) -> Result<Vec<Instrument>>;
+}
+
+/// Historical klines over REST
+#[async_trait]
+pub trait HistoricalFetcher: MarketDataFetcher {
+    /// Fetch one page of bars opening in `[start_ms, end_ms)`, in any order.
+    /// The window never spans more than `kline_page_limit` bars.
+    async fn fetch_kline_page(
+        &self,
+        symbol: &str,
+        interval: CandleInterval,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>>;
+
+    /// Maximum bars the exchange returns per request
+    fn kline_page_limit(&self) -> usize {
+        1000
+    }
+
+    /// Fetch every bar opening in `[start_ms, end_ms)`, oldest first, one
+    /// page after another. Errors are not retried; `HistoricalData` adds that.
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: CandleInterval,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>> {
+        page_klines(self, None, symbol, interval, start_ms, end_ms).await
+    }
+}
@@ -0,0 +1,622 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatchReader};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, TimeUnit};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use futures::stream;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rust_decimal::Decimal;
+use tracing::{debug, info};
+use crate::engine::{Event, EventBus};
+use crate::error::{Result, TradingError};
+use crate::instrument::SymbolRegistry;
+use super::event::{parse_optional_decimal, PriceEvent, Quote};
+use super::fetcher_trait::MarketDataFetcher;
+use super::monitor::{DataQualityConfig, PriceMonitor};
+use super::normalizer::PriceValidator;
+use super::recorder::Recording;
+use super::stream_trait::{MarketDataStream, PriceStream};
+
+/// Source name of file-backed fetchers and streams
+const SOURCE: &str = "File";
+
+/// How the timestamp column is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    EpochSeconds,
+    EpochMillis,
+    EpochMicros,
+    /// `2024-01-31T12:00:00.250Z`, `2024-01-31 12:00:00+02:00` or `2024-01-31`;
+    /// values without an offset are local times in the source's `timezone`
+    Iso8601,
+}
+
+/// Column names to read from the header row or Parquet schema
+#[derive(Debug, Clone)]
+pub struct CsvColumns {
+    pub timestamp: String,
+    pub price: String,
+    pub volume: Option<String>,
+    /// Per-row symbol; when absent the source's `symbol` is used
+    pub symbol: Option<String>,
+    pub bid: Option<String>,
+    pub bid_size: Option<String>,
+    pub ask: Option<String>,
+    pub ask_size: Option<String>,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        Self {
+            timestamp: "timestamp".to_string(),
+            price: "price".to_string(),
+            volume: Some("volume".to_string()),
+            symbol: Some("symbol".to_string()),
+            bid: None,
+            bid_size: None,
+            ask: None,
+            ask_size: None,
+        }
+    }
+}
+
+/// Positions of the mapped columns in one file
+struct ColumnIndex {
+    timestamp: usize,
+    price: usize,
+    volume: Option<usize>,
+    /// `None` when the source has a fixed symbol
+    symbol: Option<usize>,
+    /// Bid, bid size, ask and ask size, when all four are mapped
+    quote: Option<[usize; 4]>,
+}
+
+impl ColumnIndex {
+    fn used(&self) -> Vec<usize> {
+        let mut used = vec![self.timestamp, self.price];
+        used.extend(self.volume);
+        used.extend(self.symbol);
+        used.extend(self.quote.into_iter().flatten());
+        used
+    }
+}
+
+/// One CSV or Parquet (`.parquet`) file of ticks or bars
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    pub path: PathBuf,
+    pub columns: CsvColumns,
+    /// Symbol for files without a symbol column (or to override it)
+    pub symbol: Option<String>,
+    /// CSV only
+    pub delimiter: char,
+    /// Encoding of text and integer timestamps; Parquet timestamp and date columns are read natively
+    pub timestamp_format: TimestampFormat,
+    /// Zone of ISO timestamps that carry no offset
+    pub timezone: Tz,
+}
+
+impl CsvSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            columns: CsvColumns::default(),
+            symbol: None,
+            delimiter: ',',
+            timestamp_format: TimestampFormat::EpochMillis,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Read naive ISO timestamps in an IANA zone such as `Europe/Berlin`, honouring DST
+    pub fn with_timezone(mut self, name: &str) -> Result<Self> {
+        self.timezone = name
+            .parse()
+            .map_err(|_| TradingError::Config(format!("Unknown time zone '{}'", name)))?;
+        Ok(self)
+    }
+
+    pub fn is_parquet(&self) -> bool {
+        self.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"))
+    }
+
+    /// Read every row as a `PriceEvent`, in timestamp order
+    pub fn load(&self) -> Result<Vec<PriceEvent>> {
+        let mut events = if self.is_parquet() { self.load_parquet()? } else { self.load_csv()? };
+        events.sort_by_key(|event| event.timestamp);
+        debug!(path = %self.path.display(), events = events.len(), "Loaded file market data");
+        Ok(events)
+    }
+
+    fn load_csv(&self) -> Result<Vec<PriceEvent>> {
+        let delimiter = u8::try_from(self.delimiter).map_err(|_| {
+            TradingError::Config(format!("{}: delimiter must be ASCII", self.path.display()))
+        })?;
+        let invalid = |e: csv::Error| TradingError::MarketData(format!("{}: {}", self.path.display(), e));
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(&self.path)
+            .map_err(invalid)?;
+
+        let header = reader.headers().map_err(invalid)?.clone();
+        if header.is_empty() {
+            return Ok(Vec::new());
+        }
+        let columns = self.column_index(&header.iter().collect::<Vec<_>>())?;
+
+        let mut events = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(invalid)?;
+            let line = record.position().map_or(0, |position| position.line());
+            let at = format!("{}:{}", self.path.display(), line);
+            events.push(self.parse_row(&columns, self.timestamp_format, |column| record.get(column), &at)?);
+        }
+        Ok(events)
+    }
+
+    fn load_parquet(&self) -> Result<Vec<PriceEvent>> {
+        let invalid = |e: &dyn std::fmt::Display| TradingError::MarketData(format!("{}: {}", self.path.display(), e));
+        let reader = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&self.path)?)
+            .and_then(|builder| builder.build())
+            .map_err(|e| invalid(&e))?;
+
+        let schema = reader.schema();
+        let header: Vec<&str> = schema.fields().iter().map(|field| field.name().as_str()).collect();
+        let columns = self.column_index(&header)?;
+        let native_time = matches!(
+            schema.field(columns.timestamp).data_type(),
+            DataType::Timestamp(..) | DataType::Date32 | DataType::Date64
+        );
+        let format = if native_time { TimestampFormat::EpochMillis } else { self.timestamp_format };
+        let used = columns.used();
+
+        let mut events = Vec::new();
+        let mut row_number = 0;
+        for batch in reader {
+            let batch = batch.map_err(|e| invalid(&e))?;
+            // Every mapped column as text, so both formats share one row parser
+            let mut text: Vec<Option<ArrayRef>> = vec![None; batch.num_columns()];
+            for &column in &used {
+                let mut array = Arc::clone(batch.column(column));
+                if column == columns.timestamp && native_time {
+                    array = cast(&array, &DataType::Timestamp(TimeUnit::Millisecond, None))
+                        .and_then(|array| cast(&array, &DataType::Int64))
+                        .map_err(|e| invalid(&e))?;
+                }
+                text[column] = Some(cast(&array, &DataType::Utf8).map_err(|e| invalid(&e))?);
+            }
+
+            for row in 0..batch.num_rows() {
+                row_number += 1;
+                let field = |column: usize| {
+                    let array = text.get(column)?.as_ref()?.as_string::<i32>();
+                    array.is_valid(row).then(|| array.value(row))
+                };
+                let at = format!("{} row {}", self.path.display(), row_number);
+                events.push(self.parse_row(&columns, format, field, &at)?);
+            }
+        }
+        Ok(events)
+    }
+
+    fn column_index(&self, header: &[&str]) -> Result<ColumnIndex> {
+        let column = |name: &str| {
+            header.iter().position(|field| field.trim() == name).ok_or_else(|| {
+                TradingError::Config(format!("{}: missing column '{}'", self.path.display(), name))
+            })
+        };
+        let optional = |name: &Option<String>| name.as_deref().map(column).transpose();
+
+        // A fixed symbol makes the symbol column optional
+        let symbol = match &self.symbol {
+            Some(_) => None,
+            None => Some(optional(&self.columns.symbol)?.ok_or_else(|| {
+                TradingError::Config(format!("{}: no symbol column or fixed symbol", self.path.display()))
+            })?),
+        };
+        let quote = match [&self.columns.bid, &self.columns.bid_size, &self.columns.ask, &self.columns.ask_size] {
+            [Some(bid), Some(bid_size), Some(ask), Some(ask_size)] => {
+                Some([column(bid)?, column(bid_size)?, column(ask)?, column(ask_size)?])
+            }
+            _ => None,
+        };
+        Ok(ColumnIndex {
+            timestamp: column(&self.columns.timestamp)?,
+            price: column(&self.columns.price)?,
+            volume: optional(&self.columns.volume)?,
+            symbol,
+            quote,
+        })
+    }
+
+    fn parse_row<'a>(
+        &self,
+        columns: &ColumnIndex,
+        format: TimestampFormat,
+        field: impl Fn(usize) -> Option<&'a str>,
+        at: &str,
+    ) -> Result<PriceEvent> {
+        let error = |message: &str| TradingError::MarketData(format!("{}: {}", at, message));
+        let value = |column: usize| field(column).map(str::trim).filter(|value| !value.is_empty());
+        let get = |column: usize| value(column).ok_or_else(|| error(&format!("missing field {}", column + 1)));
+
+        let symbol = match columns.symbol {
+            Some(column) => get(column)?.to_string(),
+            None => self.symbol.clone().unwrap_or_default(),
+        };
+        let ts = parse_timestamp(get(columns.timestamp)?, format, self.timezone).map_err(|e| error(&e.to_string()))?;
+        let decimal = |value: &str| {
+            // Float columns in Parquet may print in scientific notation
+            Decimal::from_str_exact(value)
+                .or_else(|_| Decimal::from_scientific(value))
+                .map_err(|e| error(&e.to_string()))
+        };
+        let price = decimal(get(columns.price)?)?;
+        // An empty or null volume reads as zero
+        let volume = columns.volume.and_then(value).map(decimal).transpose()?.unwrap_or(Decimal::ZERO);
+        let quote = match columns.quote {
+            Some([bid, bid_size, ask, ask_size]) => {
+                let optional = |column: usize| parse_optional_decimal(value(column));
+                match (optional(bid)?, optional(bid_size)?, optional(ask)?, optional(ask_size)?) {
+                    (Some(bid), Some(bid_size), Some(ask), Some(ask_size)) => {
+                        Some(Quote::new(bid, bid_size, ask, ask_size))
+                    }
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+
+        let mut event = PriceEvent::new(symbol, price, volume)?
+            .with_exchange_time(Some(ts))
+            .with_quote(quote);
+        // Recorded data: replay as if received at the exchange time
+        event.received_at = ts;
+        Ok(event)
+    }
+}
+
+/// Load several sources and merge them into one timeline.
+/// Events with equal timestamps keep the order of `sources`.
+pub fn load_sources(sources: &[CsvSource]) -> Result<Vec<PriceEvent>> {
+    let mut events = Vec::new();
+    for source in sources {
+        events.extend(source.load()?);
+    }
+    events.sort_by_key(|event| event.timestamp);
+    Ok(events)
+}
+
+/// Parse a timestamp into UTC milliseconds
+pub fn parse_timestamp(value: &str, format: TimestampFormat, timezone: Tz) -> Result<u64> {
+    let invalid = || TradingError::Validation(format!("invalid timestamp '{}'", value));
+    let epoch = |scale_num: u64, scale_den: u64| {
+        let number: Decimal = value.parse().map_err(|_| invalid())?;
+        let millis = number * Decimal::from(scale_num) / Decimal::from(scale_den);
+        u64::try_from(millis.trunc()).map_err(|_| invalid())
+    };
+    match format {
+        TimestampFormat::EpochSeconds => epoch(1_000, 1),
+        TimestampFormat::EpochMillis => epoch(1, 1),
+        TimestampFormat::EpochMicros => epoch(1, 1_000),
+        TimestampFormat::Iso8601 => parse_iso8601(value.trim(), timezone).ok_or_else(invalid),
+    }
+}
+
+fn parse_iso8601(value: &str, timezone: Tz) -> Option<u64> {
+    let with_offset = DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z"))
+        .map(|at| at.with_timezone(&Utc));
+    let utc = match with_offset {
+        Ok(at) => at,
+        Err(_) => {
+            let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+                .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_time(NaiveTime::MIN)))
+                .ok()?;
+            // Wall-clock times repeated by a DST change resolve to the first one;
+            // times skipped by it do not exist and are rejected
+            timezone.from_local_datetime(&naive).earliest()?.with_timezone(&Utc)
+        }
+    };
+    u64::try_from(utc.timestamp_millis()).ok()
+}
+
+/// Canonical spelling of a recorded or requested symbol, so files written
+/// with native symbols (`BTCUSDT`) replay like live canonical data; spellings
+/// that cannot be split are kept as they are
+fn canonical(symbol: &str) -> String {
+    SymbolRegistry::new().canonical(symbol).unwrap_or_else(|_| symbol.to_string())
+}
+
+/// Canonicalize the symbols of loaded events
+fn canonicalize(mut events: Vec<PriceEvent>) -> Vec<PriceEvent> {
+    for event in &mut events {
+        event.symbol = canonical(&event.symbol);
+    }
+    events
+}
+
+/// Replays recorded prices through `fetch_price`: each call returns the next
+/// event for the symbol, as a live fetcher would. Once a symbol is exhausted
+/// it returns `TradingError::EndOfData`.
+pub struct FileFetcher {
+    events: Mutex<HashMap<String, VecDeque<PriceEvent>>>,
+}
+
+impl FileFetcher {
+    pub fn new(events: Vec<PriceEvent>) -> Self {
+        let mut by_symbol: HashMap<String, VecDeque<PriceEvent>> = HashMap::new();
+        for event in canonicalize(events) {
+            by_symbol.entry(event.symbol.clone()).or_default().push_back(event);
+        }
+        Self { events: Mutex::new(by_symbol) }
+    }
+
+    pub fn from_csv(sources: &[CsvSource]) -> Result<Self> {
+        let events = load_sources(sources)?;
+        info!(sources = sources.len(), events = events.len(), "Replaying market data from files");
+        Ok(Self::new(events))
+    }
+
+    /// Replay every price in a `MarketDataRecorder` directory
+    pub fn from_recording(dir: impl Into<PathBuf>) -> Result<Self> {
+        let events = Recording::open(dir)?.price_events(&[], 0, u64::MAX)?;
+        info!(events = events.len(), "Replaying recorded market data");
+        Ok(Self::new(events))
+    }
+
+    /// Events not yet returned for `symbol`
+    pub fn remaining(&self, symbol: &str) -> usize {
+        self.events.lock().unwrap().get(&canonical(symbol)).map_or(0, VecDeque::len)
+    }
+}
+
+#[async_trait]
+impl MarketDataFetcher for FileFetcher {
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+        let next = self.events.lock().unwrap().get_mut(&canonical(symbol)).and_then(VecDeque::pop_front);
+        let mut event = next.ok_or_else(|| {
+            TradingError::EndOfData(format!("No more recorded data for {}", symbol))
+        })?;
+        // The recorded sequence belongs to the original session
+        event.sequence = None;
+        Ok(event)
+    }
+
+    fn exchange_name(&self) -> &str {
+        SOURCE
+    }
+}
+
+/// Streams recorded prices in timestamp order, publishing each one that passes
+/// the data-quality checks. With `speed` set, events are paced by their
+/// timestamps (2.0 = twice real time).
+pub struct FileStream {
+    events: Arc<Vec<PriceEvent>>,
+    event_bus: EventBus,
+    pub speed: Option<f64>,
+    pub data_quality: DataQualityConfig,
+}
+
+impl FileStream {
+    pub fn new(events: Vec<PriceEvent>, event_bus: EventBus) -> Self {
+        Self {
+            events: Arc::new(canonicalize(events)),
+            event_bus,
+            speed: None,
+            data_quality: DataQualityConfig::default(),
+        }
+    }
+
+    pub fn from_csv(sources: &[CsvSource], event_bus: EventBus) -> Result<Self> {
+        Ok(Self::new(load_sources(sources)?, event_bus))
+    }
+
+    pub fn from_recording(dir: impl Into<PathBuf>, event_bus: EventBus) -> Result<Self> {
+        Ok(Self::new(Recording::open(dir)?.price_events(&[], 0, u64::MAX)?, event_bus))
+    }
+}
+
+impl MarketDataStream for FileStream {
+    /// An empty `symbols` list replays every symbol
+    fn subscribe(&self, symbols: &[String]) -> Result<PriceStream> {
+        let events = Arc::clone(&self.events);
+        let symbols: Vec<String> = symbols.iter().map(|symbol| canonical(symbol)).collect();
+        let speed = self.speed.filter(|speed| *speed > 0.0);
+
+        // A fresh monitor per subscription, fed in replay order
+        let monitor = PriceMonitor::new(self.data_quality.clone(), self.event_bus.clone());
+        let state = (0usize, None::<u64>, monitor);
+        let stream = stream::unfold(state, move |(mut index, mut previous, mut monitor)| {
+            let events = Arc::clone(&events);
+            let symbols = symbols.clone();
+            async move {
+                while let Some(event) = events.get(index) {
+                    index += 1;
+                    if !symbols.is_empty() && !symbols.contains(&event.symbol) {
+                        continue;
+                    }
+                    if let (Some(speed), Some(previous)) = (speed, previous) {
+                        let gap = event.timestamp.saturating_sub(previous) as f64 / speed;
+                        tokio::time::sleep(Duration::from_millis(gap as u64)).await;
+                    }
+                    previous = Some(event.timestamp);
+                    let mut event = event.clone();
+                    // The recorded sequence belongs to the original session
+                    event.sequence = None;
+                    let result = match PriceValidator::normalize(event).and_then(|event| monitor.admit(SOURCE, event)) {
+                        Ok(Some(event)) => Ok(event),
+                        // Withheld by the data-quality checks
+                        Ok(None) => continue,
+                        Err(err) => Err(err),
+                    };
+                    return Some((result, (index, previous, monitor)));
+                }
+                None
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    fn exchange_name(&self) -> &str {
+        SOURCE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn write_csv(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("file-fetcher-{}-{}.csv", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parses_timestamps() {
+        let iso = |value: &str, zone: Tz| parse_timestamp(value, TimestampFormat::Iso8601, zone);
+        assert_eq!(iso("2023-11-14T22:13:20Z", Tz::UTC).unwrap(), 1_700_000_000_000);
+        assert_eq!(iso("2023-11-14 22:13:20.5", Tz::UTC).unwrap(), 1_700_000_000_500);
+        assert_eq!(iso("2023-11-15T00:13:20+02:00", Tz::UTC).unwrap(), 1_700_000_000_000);
+        assert_eq!(iso("1970-01-02", Tz::UTC).unwrap(), 86_400_000);
+        // Naive values are local to the source zone, with its DST rules
+        let new_york = CsvSource::new("ticks.csv").with_timezone("America/New_York").unwrap().timezone;
+        assert_eq!(iso("2023-11-14 17:13:20", new_york).unwrap(), 1_700_000_000_000);
+        assert_eq!(iso("2023-07-01 12:00:00", new_york).unwrap(), 1_688_227_200_000);
+        assert!(iso("2023-03-12 02:30:00", new_york).is_err());
+        assert!(CsvSource::new("ticks.csv").with_timezone("Mars/Olympus").is_err());
+
+        assert_eq!(parse_timestamp("1700000000.25", TimestampFormat::EpochSeconds, Tz::UTC).unwrap(), 1_700_000_000_250);
+        assert_eq!(parse_timestamp("1700000000000000", TimestampFormat::EpochMicros, Tz::UTC).unwrap(), 1_700_000_000_000);
+        assert!(iso("2023-13-01", Tz::UTC).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_maps_columns_and_merges_symbols() {
+        let btc = write_csv("btc", "time;last;qty;bid;bid_qty;ask;ask_qty\n\
+            2023-11-14 22:13:21;50001;0.5;50000;1;50002;2\n\
+            2023-11-14 22:13:20;\"50000\";0.1;;;;\n");
+        let eth = write_csv("eth", "timestamp,symbol,price,note\n1700000000500,ETHUSDT,3000.5,\"quoted\nacross lines\"\n");
+
+        let btc_source = CsvSource {
+            columns: CsvColumns {
+                timestamp: "time".to_string(),
+                price: "last".to_string(),
+                volume: Some("qty".to_string()),
+                symbol: None,
+                bid: Some("bid".to_string()),
+                bid_size: Some("bid_qty".to_string()),
+                ask: Some("ask".to_string()),
+                ask_size: Some("ask_qty".to_string()),
+            },
+            symbol: Some("BTCUSDT".to_string()),
+            delimiter: ';',
+            timestamp_format: TimestampFormat::Iso8601,
+            ..CsvSource::new(&btc)
+        };
+        let eth_source = CsvSource {
+            columns: CsvColumns { volume: None, ..CsvColumns::default() },
+            ..CsvSource::new(&eth)
+        };
+
+        let events = load_sources(&[btc_source.clone(), eth_source.clone()]).unwrap();
+        let order: Vec<(&str, u64)> = events.iter().map(|e| (e.symbol.as_str(), e.timestamp)).collect();
+        assert_eq!(order, vec![
+            ("BTCUSDT", 1_700_000_000_000),
+            ("ETHUSDT", 1_700_000_000_500),
+            ("BTCUSDT", 1_700_000_001_000),
+        ]);
+        assert!(events[0].quote.is_none());
+        assert_eq!(events[2].spread(), Some(Decimal::TWO));
+        assert_eq!(events[2].latency_ms(), 0);
+
+        let fetcher = FileFetcher::from_csv(&[btc_source, eth_source]).unwrap();
+        let first = fetcher.fetch_price("BTCUSDT").await.unwrap();
+        assert_eq!((first.symbol.as_str(), first.price), ("BTC-USDT", Decimal::from(50000)));
+        assert_eq!(fetcher.fetch_price("BTCUSDT").await.unwrap().price, Decimal::from(50001));
+        assert!(matches!(fetcher.fetch_price("BTCUSDT").await, Err(TradingError::EndOfData(_))));
+        assert_eq!(fetcher.remaining("ETH-USDT"), 1);
+
+        let _ = fs::remove_file(btc);
+        let _ = fs::remove_file(eth);
+    }
+
+    #[tokio::test]
+    async fn test_stream_replays_in_order() {
+        let path = write_csv("stream", "timestamp,symbol,price,volume\n\
+            1000,BTCUSDT,100,0\n3000,ETHUSDT,2,0\n2000,BTCUSDT,101,0\n2500,BTCUSDT,150,0\n");
+        let bus = EventBus::new();
+        let stream = FileStream::from_csv(&[CsvSource::new(&path)], bus.clone()).unwrap();
+
+        // The 150 print is quarantined as a jump and never published
+        let prices: Vec<Decimal> = stream
+            .subscribe(&["BTCUSDT".to_string()])
+            .unwrap()
+            .map(|event| event.unwrap().price)
+            .collect()
+            .await;
+        assert_eq!(prices, vec![Decimal::from(100), Decimal::from(101)]);
+        let counts = bus.metrics_snapshot();
+        assert_eq!(counts.get("PriceUpdated"), Some(&2));
+        assert_eq!(counts.get("DataQuality"), Some(&1));
+        assert_eq!(stream.subscribe(&[]).unwrap().count().await, 3);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_loads_parquet_with_the_same_mapping() {
+        use arrow::array::{Float64Array, RecordBatch, StringArray, TimestampMillisecondArray};
+        use arrow::datatypes::{Field, Schema};
+        use parquet::arrow::ArrowWriter;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("time", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("last", DataType::Float64, false),
+            Field::new("qty", DataType::Float64, true),
+        ]));
+        let batch = RecordBatch::try_new(Arc::clone(&schema), vec![
+            Arc::new(TimestampMillisecondArray::from(vec![1_700_000_001_000, 1_700_000_000_000]).with_timezone("UTC")),
+            Arc::new(StringArray::from(vec!["BTCUSDT", "ETHUSDT"])),
+            Arc::new(Float64Array::from(vec![50000.5, 3000.25])),
+            Arc::new(Float64Array::from(vec![Some(0.5), None])),
+        ]).unwrap();
+        let path = std::env::temp_dir().join(format!("file-fetcher-ticks-{}.parquet", std::process::id()));
+        let mut writer = ArrowWriter::try_new(fs::File::create(&path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let source = CsvSource {
+            columns: CsvColumns {
+                timestamp: "time".to_string(),
+                price: "last".to_string(),
+                volume: Some("qty".to_string()),
+                ..CsvColumns::default()
+            },
+            ..CsvSource::new(&path)
+        };
+        let events = source.load().unwrap();
+        let rows: Vec<(&str, u64, Decimal, Decimal)> =
+            events.iter().map(|e| (e.symbol.as_str(), e.timestamp, e.price, e.volume)).collect();
+        assert_eq!(rows, vec![
+            ("ETHUSDT", 1_700_000_000_000, Decimal::new(300025, 2), Decimal::ZERO),
+            ("BTCUSDT", 1_700_000_001_000, Decimal::new(500005, 1), Decimal::new(5, 1)),
+        ]);
+
+        let missing = CsvSource { columns: CsvColumns::default(), ..source };
+        assert!(matches!(missing.load(), Err(TradingError::Config(_))));
+        let _ = fs::remove_file(path);
+    }
+}
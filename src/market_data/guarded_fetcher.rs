@@ -0,0 +1,446 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::{info, warn};
+use crate::engine::{Event, EventBus};
+use crate::error::{Result, TradingError};
+use super::event::PriceEvent;
+use super::fetcher_trait::{MarketDataFetcher, PriceBatch};
+
+/// Retries for retryable errors (see `TradingError::is_retryable`)
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts per call, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after every attempt
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after failed attempt `attempt` (1-based): exponential, capped,
+    /// then scaled by a random factor in [0.5, 1.0) so clients don't retry in lockstep
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        delay.mul_f64(0.5 + jitter() / 2.0)
+    }
+}
+
+/// Uniform in [0, 1)
+fn jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed calls that open the circuit
+    pub failure_threshold: u32,
+    /// How long calls are rejected before a trial call is let through
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls fail fast with `TradingError::CircuitOpen`
+    Open,
+    /// One trial call decides between `Closed` and `Open`
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half-open",
+        })
+    }
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Consecutive-failure circuit breaker publishing `Event::CircuitStateChanged`
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+    event_bus: EventBus,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig, event_bus: EventBus) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+            event_bus,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.lock().state
+    }
+
+    /// Admit a call, moving to half-open once the open period has passed
+    pub fn allow(&self) -> Result<()> {
+        let mut state = self.lock();
+        match state.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let elapsed = state.opened_at.map_or(Duration::MAX, |at| at.elapsed());
+                if elapsed < self.config.open_duration {
+                    let wait = self.config.open_duration - elapsed;
+                    return Err(TradingError::CircuitOpen(format!("{} for another {}ms", self.name, wait.as_millis())));
+                }
+                state.trial_in_flight = true;
+                let change = self.transition(&mut state, CircuitState::HalfOpen, "open period elapsed".to_string());
+                drop(state);
+                self.publish(change);
+                Ok(())
+            }
+            CircuitState::HalfOpen if state.trial_in_flight => {
+                Err(TradingError::CircuitOpen(format!("{} trial call in progress", self.name)))
+            }
+            CircuitState::HalfOpen => {
+                state.trial_in_flight = true;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.lock();
+        state.failures = 0;
+        state.trial_in_flight = false;
+        let change = match state.state {
+            CircuitState::Closed => None,
+            _ => self.transition(&mut state, CircuitState::Closed, "trial call succeeded".to_string()),
+        };
+        drop(state);
+        self.publish(change);
+    }
+
+    /// The call ended without telling whether the feed is healthy, e.g. a
+    /// rejected request: free the trial slot and leave the state unchanged
+    pub fn record_neutral(&self) {
+        self.lock().trial_in_flight = false;
+    }
+
+    pub fn record_failure(&self, error: &TradingError) {
+        let mut state = self.lock();
+        state.failures += 1;
+        state.trial_in_flight = false;
+        let change = match state.state {
+            CircuitState::Closed if state.failures >= self.config.failure_threshold => {
+                let reason = format!("{} consecutive failures, last: {}", state.failures, error);
+                self.transition(&mut state, CircuitState::Open, reason)
+            }
+            CircuitState::HalfOpen => {
+                self.transition(&mut state, CircuitState::Open, format!("trial call failed: {}", error))
+            }
+            _ => None,
+        };
+        drop(state);
+        self.publish(change);
+    }
+
+    fn transition(&self, state: &mut BreakerState, to: CircuitState, reason: String) -> Option<Event> {
+        let from = state.state;
+        state.state = to;
+        state.opened_at = (to == CircuitState::Open).then(Instant::now);
+        Some(Event::CircuitStateChanged { feed: self.name.clone(), from, to, reason })
+    }
+
+    /// Publish outside the lock so handlers may query the breaker
+    fn publish(&self, change: Option<Event>) {
+        if let Some(event) = change {
+            if let Event::CircuitStateChanged { from, to, reason, .. } = &event {
+                info!(feed = %self.name, %from, %to, reason = %reason, "Circuit breaker state changed");
+            }
+            let _ = self.event_bus.publish_from(&self.name, event, None);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GuardConfig {
+    /// Per-attempt deadline
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+    pub breaker: CircuitBreakerConfig,
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            retry: RetryPolicy::default(),
+            breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
+/// Per-attempt timeouts, jittered retries and a circuit breaker around requests
+/// to one feed. Only retryable errors are retried and count towards opening the circuit.
+pub struct RequestGuard {
+    name: String,
+    config: GuardConfig,
+    breaker: CircuitBreaker,
+}
+
+impl RequestGuard {
+    pub fn new(name: impl Into<String>, config: GuardConfig, event_bus: EventBus) -> Self {
+        let name = name.into();
+        let breaker = CircuitBreaker::new(name.clone(), config.breaker.clone(), event_bus);
+        Self { name, config, breaker }
+    }
+
+    pub fn circuit_state(&self) -> CircuitState {
+        self.breaker.state()
+    }
+
+    /// Run `call` under the timeout, retry policy and breaker
+    pub async fn run<'a, T, F, Fut>(&'a self, target: &str, call: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>> + 'a,
+    {
+        self.breaker.allow()?;
+
+        let mut attempt = 1;
+        loop {
+            let result = match tokio::time::timeout(self.config.timeout, call()).await {
+                Ok(result) => result,
+                Err(_) => Err(TradingError::Timeout(format!(
+                    "{} {} after {}ms",
+                    self.name,
+                    target,
+                    self.config.timeout.as_millis()
+                ))),
+            };
+
+            match result {
+                Ok(value) => {
+                    self.breaker.record_success();
+                    return Ok(value);
+                }
+                Err(e) if e.is_retryable() && attempt < self.config.retry.max_attempts => {
+                    let delay = self.config.retry.delay(attempt);
+                    warn!(feed = %self.name, target, attempt, delay_ms = delay.as_millis() as u64, error = %e, "Retrying market data request");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    if e.is_retryable() {
+                        self.breaker.record_failure(&e);
+                    } else {
+                        // The request itself was bad, which says nothing about the feed
+                        self.breaker.record_neutral();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// Wraps a fetcher in a `RequestGuard` named after its exchange
+pub struct GuardedFetcher {
+    inner: Box<dyn MarketDataFetcher>,
+    guard: RequestGuard,
+}
+
+impl GuardedFetcher {
+    pub fn new(inner: Box<dyn MarketDataFetcher>, config: GuardConfig, event_bus: EventBus) -> Self {
+        let guard = RequestGuard::new(inner.exchange_name(), config, event_bus);
+        Self { inner, guard }
+    }
+
+    pub fn circuit_state(&self) -> CircuitState {
+        self.guard.circuit_state()
+    }
+}
+
+#[async_trait]
+impl MarketDataFetcher for GuardedFetcher {
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+        self.guard.run(symbol, || self.inner.fetch_price(symbol)).await
+    }
+
+    /// The whole batch is one guarded call; per-symbol errors do not trip the breaker
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+        let target = format!("{} symbols", symbols.len());
+        self.guard.run(&target, || self.inner.fetch_prices(symbols)).await
+    }
+
+    fn exchange_name(&self) -> &str {
+        self.inner.exchange_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use rust_decimal::Decimal;
+    use crate::engine::EventKind;
+
+    /// Plays back scripted outcomes, then succeeds
+    struct Scripted {
+        outcomes: Mutex<VecDeque<Option<TradingError>>>,
+        calls: Arc<AtomicUsize>,
+        delay: Duration,
+    }
+
+    impl Scripted {
+        fn new(outcomes: Vec<Option<TradingError>>) -> (Self, Arc<AtomicUsize>) {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let fetcher = Self { outcomes: Mutex::new(outcomes.into()), calls: Arc::clone(&calls), delay: Duration::ZERO };
+            (fetcher, calls)
+        }
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for Scripted {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            match self.outcomes.lock().unwrap().pop_front().flatten() {
+                Some(error) => Err(error),
+                None => PriceEvent::new(symbol.to_string(), Decimal::ONE, Decimal::ONE),
+            }
+        }
+
+        fn exchange_name(&self) -> &str {
+            "Scripted"
+        }
+    }
+
+    fn unavailable() -> Option<TradingError> {
+        Some(TradingError::Http(503, "Service Unavailable".to_string()))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_only_retryable_errors() {
+        let (inner, calls) = Scripted::new(vec![unavailable(), unavailable()]);
+        let fetcher = GuardedFetcher::new(Box::new(inner), GuardConfig::default(), EventBus::new());
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let (inner, calls) = Scripted::new(vec![Some(TradingError::Http(400, "Invalid symbol.".to_string()))]);
+        let fetcher = GuardedFetcher::new(Box::new(inner), GuardConfig::default(), EventBus::new());
+        assert!(matches!(fetcher.fetch_price("NOPE").await, Err(TradingError::Http(400, _))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Slow responses time out and are retried like transport errors
+        let (mut inner, calls) = Scripted::new(vec![]);
+        inner.delay = Duration::from_secs(60);
+        let fetcher = GuardedFetcher::new(Box::new(inner), GuardConfig::default(), EventBus::new());
+        assert!(matches!(fetcher.fetch_price("BTCUSDT").await, Err(TradingError::Timeout(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_circuit_opens_and_recovers() {
+        let bus = EventBus::new();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&changes);
+        bus.subscribe(EventKind::CircuitStateChanged, move |envelope| {
+            if let Event::CircuitStateChanged { to, .. } = &envelope.event {
+                seen.lock().unwrap().push(*to);
+            }
+        }).unwrap().detach();
+
+        let config = GuardConfig {
+            retry: RetryPolicy { max_attempts: 1, ..RetryPolicy::default() },
+            breaker: CircuitBreakerConfig { failure_threshold: 2, open_duration: Duration::from_secs(30) },
+            ..GuardConfig::default()
+        };
+        let (inner, calls) = Scripted::new(vec![unavailable(), unavailable(), unavailable()]);
+        let fetcher = GuardedFetcher::new(Box::new(inner), config, bus);
+
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_err());
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_err());
+        assert_eq!(fetcher.circuit_state(), CircuitState::Open);
+        assert!(matches!(fetcher.fetch_price("BTCUSDT").await, Err(TradingError::CircuitOpen(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Failed trial re-opens; a later successful trial closes
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_err());
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_ok());
+        assert_eq!(fetcher.circuit_state(), CircuitState::Closed);
+
+        use CircuitState::*;
+        assert_eq!(*changes.lock().unwrap(), vec![Open, HalfOpen, Open, HalfOpen, Closed]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_non_retryable_errors_leave_the_circuit_alone() {
+        let config = GuardConfig {
+            retry: RetryPolicy { max_attempts: 1, ..RetryPolicy::default() },
+            breaker: CircuitBreakerConfig { failure_threshold: 1, open_duration: Duration::from_secs(30) },
+            ..GuardConfig::default()
+        };
+        let bad_request = || Some(TradingError::Http(400, "Invalid symbol.".to_string()));
+        let (inner, _) = Scripted::new(vec![unavailable(), bad_request(), bad_request()]);
+        let fetcher = GuardedFetcher::new(Box::new(inner), config, EventBus::new());
+
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_err());
+        assert_eq!(fetcher.circuit_state(), CircuitState::Open);
+
+        // A rejected trial neither closes nor re-opens, and frees the slot for the next trial
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(matches!(fetcher.fetch_price("NOPE").await, Err(TradingError::Http(400, _))));
+        assert_eq!(fetcher.circuit_state(), CircuitState::HalfOpen);
+        assert!(matches!(fetcher.fetch_price("NOPE").await, Err(TradingError::Http(400, _))));
+        assert!(fetcher.fetch_price("BTCUSDT").await.is_ok());
+        assert_eq!(fetcher.circuit_state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use crate::engine::EventBus;
+use crate::error::Result;
+use super::candle::{Candle, CandleInterval};
+use super::fetcher_trait::HistoricalFetcher;
+use super::guarded_fetcher::{GuardConfig, RequestGuard};
+
+/// Fetch every candle opening in `[start_ms, end_ms)`, oldest first.
+///
+/// The range is split into windows of `kline_page_limit` bars so each request
+/// is complete regardless of the order the exchange pages in. Each page goes
+/// through `guard` when given.
+pub(crate) async fn page_klines<F: HistoricalFetcher + ?Sized>(
+    fetcher: &F,
+    guard: Option<&RequestGuard>,
+    symbol: &str,
+    interval: CandleInterval,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<Vec<Candle>> {
+    let step = interval.as_millis();
+    let window = step * fetcher.kline_page_limit().max(1) as u64;
+    let mut candles = BTreeMap::new();
+    let mut cursor = interval.open_time(start_ms);
+
+    while cursor < end_ms {
+        let window_end = (cursor + window).min(end_ms);
+        let fetch_page = || fetcher.fetch_kline_page(symbol, interval, cursor, window_end);
+        let page = match guard {
+            Some(guard) => guard.run(&format!("{} {} klines", symbol, interval), fetch_page).await?,
+            None => fetch_page().await?,
+        };
+        for candle in page {
+            if candle.open_time >= cursor && candle.open_time < window_end {
+                candles.insert(candle.open_time, candle);
+            }
+        }
+        cursor = window_end;
+    }
+
+    debug!(exchange = fetcher.exchange_name(), symbol, %interval, candles = candles.len(), "Fetched klines");
+    Ok(candles.into_values().collect())
+}
+
+/// Time ranges `[start, end)` already downloaded for one series
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Coverage {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl Coverage {
+    fn add(&mut self, start: u64, end: u64) {
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.ranges.len());
+        for (start, end) in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    /// Parts of `[start, end)` not covered yet
+    fn missing(&self, start: u64, end: u64) -> Vec<(u64, u64)> {
+        let mut missing = Vec::new();
+        let mut cursor = start;
+        for &(covered_start, covered_end) in &self.ranges {
+            if covered_end <= cursor {
+                continue;
+            }
+            if covered_start >= end {
+                break;
+            }
+            if covered_start > cursor {
+                missing.push((cursor, covered_start));
+            }
+            cursor = cursor.max(covered_end);
+        }
+        if cursor < end {
+            missing.push((cursor, end));
+        }
+        missing
+    }
+}
+
+/// On-disk kline store: one JSON-lines file of candles plus a coverage file
+/// per exchange, symbol and interval under `dir`
+#[derive(Debug, Clone)]
+pub struct KlineCache {
+    dir: PathBuf,
+}
+
+impl KlineCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn paths(&self, exchange: &str, symbol: &str, interval: CandleInterval) -> (PathBuf, PathBuf) {
+        let base = self.dir.join(exchange.to_lowercase());
+        let stem = format!("{}_{}", symbol.to_uppercase(), interval);
+        (base.join(format!("{}.jsonl", stem)), base.join(format!("{}.coverage.json", stem)))
+    }
+
+    fn load(&self, exchange: &str, symbol: &str, interval: CandleInterval) -> Result<(BTreeMap<u64, Candle>, Coverage)> {
+        let (data, coverage) = self.paths(exchange, symbol, interval);
+        if !data.exists() || !coverage.exists() {
+            return Ok((BTreeMap::new(), Coverage::default()));
+        }
+
+        let mut candles = BTreeMap::new();
+        for line in BufReader::new(fs::File::open(&data)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let candle: Candle = serde_json::from_str(&line)?;
+            candles.insert(candle.open_time, candle);
+        }
+        let coverage = serde_json::from_str(&fs::read_to_string(&coverage)?)?;
+        Ok((candles, coverage))
+    }
+
+    fn store(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: CandleInterval,
+        candles: &BTreeMap<u64, Candle>,
+        coverage: &Coverage,
+    ) -> Result<()> {
+        let (data, coverage_path) = self.paths(exchange, symbol, interval);
+        if let Some(parent) = data.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to temporary files first so an interrupted run leaves the old cache intact
+        let tmp = data.with_extension("jsonl.tmp");
+        let mut out = std::io::BufWriter::new(fs::File::create(&tmp)?);
+        for candle in candles.values() {
+            serde_json::to_writer(&mut out, candle)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        drop(out);
+        fs::rename(&tmp, &data)?;
+
+        let tmp = coverage_path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(coverage)?)?;
+        fs::rename(&tmp, &coverage_path)?;
+        Ok(())
+    }
+}
+
+/// Historical klines paged through a `RequestGuard`. With a `KlineCache`
+/// only ranges not on disk yet are downloaded.
+pub struct HistoricalData {
+    fetcher: Box<dyn HistoricalFetcher>,
+    guard: RequestGuard,
+    cache: Option<KlineCache>,
+}
+
+impl HistoricalData {
+    pub fn new(fetcher: Box<dyn HistoricalFetcher>, config: GuardConfig, event_bus: EventBus) -> Self {
+        let guard = RequestGuard::new(format!("{} klines", fetcher.exchange_name()), config, event_bus);
+        Self { fetcher, guard, cache: None }
+    }
+
+    pub fn with_cache(mut self, cache: KlineCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Candles opening in `[start_ms, end_ms)`, oldest first.
+    /// Only closed bars are cached; the range is cut at the current bar.
+    pub async fn klines(
+        &self,
+        symbol: &str,
+        interval: CandleInterval,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Candle>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let start = interval.open_time(start_ms);
+        let end = end_ms.min(interval.open_time(now));
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        let Some(cache) = &self.cache else {
+            return page_klines(self.fetcher.as_ref(), Some(&self.guard), symbol, interval, start, end).await;
+        };
+
+        let exchange = self.fetcher.exchange_name().to_string();
+        let (mut candles, mut coverage) = cache.load(&exchange, symbol, interval)?;
+        let missing = coverage.missing(start, end);
+
+        if !missing.is_empty() {
+            for &(gap_start, gap_end) in &missing {
+                info!(exchange = %exchange, symbol, %interval, gap_start, gap_end, "Downloading klines");
+                for candle in page_klines(self.fetcher.as_ref(), Some(&self.guard), symbol, interval, gap_start, gap_end).await? {
+                    candles.insert(candle.open_time, candle);
+                }
+                coverage.add(gap_start, gap_end);
+            }
+            cache.store(&exchange, symbol, interval, &candles, &coverage)?;
+        }
+
+        Ok(candles.range(start..end).map(|(_, candle)| candle.clone()).collect())
+    }
+
+    /// The most recent `count` closed bars
+    pub async fn latest(&self, symbol: &str, interval: CandleInterval, count: usize) -> Result<Vec<Candle>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let end = interval.open_time(now);
+        let start = end.saturating_sub(interval.as_millis() * count as u64);
+        self.klines(symbol, interval, start, end).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use async_trait::async_trait;
+    use rust_decimal::Decimal;
+    use crate::error::TradingError;
+    use crate::market_data::event::PriceEvent;
+    use crate::market_data::fetcher_trait::MarketDataFetcher;
+    use crate::market_data::guarded_fetcher::RetryPolicy;
+
+    /// Serves a bar for every interval and records requested windows
+    #[derive(Default)]
+    struct FakeHistory {
+        requests: Arc<Mutex<Vec<(u64, u64)>>>,
+        rate_limits: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for FakeHistory {
+        async fn fetch_price(&self, _symbol: &str) -> Result<PriceEvent> {
+            Err(TradingError::MarketData("not used".to_string()))
+        }
+
+        fn exchange_name(&self) -> &str {
+            "Fake"
+        }
+    }
+
+    #[async_trait]
+    impl HistoricalFetcher for FakeHistory {
+        async fn fetch_kline_page(
+            &self,
+            symbol: &str,
+            interval: CandleInterval,
+            start_ms: u64,
+            end_ms: u64,
+        ) -> Result<Vec<Candle>> {
+            if self.rate_limits.load(Ordering::SeqCst) > 0 {
+                self.rate_limits.fetch_sub(1, Ordering::SeqCst);
+                return Err(TradingError::RateLimited("slow down".to_string()));
+            }
+            self.requests.lock().unwrap().push((start_ms, end_ms));
+            Ok((start_ms..end_ms)
+                .step_by(interval.as_millis() as usize)
+                .map(|open_time| Candle {
+                    symbol: symbol.to_string(),
+                    interval,
+                    open_time,
+                    open: Decimal::from(open_time / 1000),
+                    high: Decimal::from(open_time / 1000),
+                    low: Decimal::from(open_time / 1000),
+                    close: Decimal::from(open_time / 1000),
+                    volume: Decimal::ONE,
+                    trades: 1,
+                })
+                .collect())
+        }
+
+        fn kline_page_limit(&self) -> usize {
+            10
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kline-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_coverage_missing_ranges() {
+        let mut coverage = Coverage::default();
+        coverage.add(10, 20);
+        coverage.add(30, 40);
+        coverage.add(18, 25);
+        assert_eq!(coverage.ranges, vec![(10, 25), (30, 40)]);
+        assert_eq!(coverage.missing(0, 50), vec![(0, 10), (25, 30), (40, 50)]);
+        assert!(coverage.missing(12, 24).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pages_and_tops_up_cache() {
+        let dir = temp_dir("topup");
+        let fake = FakeHistory::default();
+        let requests = Arc::clone(&fake.requests);
+        let history = HistoricalData::new(Box::new(fake), GuardConfig::default(), EventBus::new())
+            .with_cache(KlineCache::new(&dir));
+
+        // 25 one-minute bars -> three windows of at most 10
+        let start = 1_700_000_040_000;
+        let minute = CandleInterval::M1.as_millis();
+        let candles = history.klines("BTCUSDT", CandleInterval::M1, start, start + 25 * minute).await.unwrap();
+        assert_eq!(candles.len(), 25);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        // Served from disk; only the 5 new bars are downloaded
+        let again = history.klines("BTCUSDT", CandleInterval::M1, start, start + 30 * minute).await.unwrap();
+        assert_eq!(again.len(), 30);
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[3], (start + 25 * minute, start + 30 * minute));
+        assert!(dir.join("fake").join("BTCUSDT_1m.jsonl").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_rate_limited_pages() {
+        let retry = RetryPolicy { max_attempts: 3, ..RetryPolicy::default() };
+        let guard = RequestGuard::new("Fake klines", GuardConfig { retry, ..GuardConfig::default() }, EventBus::new());
+        let fake = FakeHistory::default();
+        fake.rate_limits.store(2, Ordering::SeqCst);
+        let candles = page_klines(&fake, Some(&guard), "BTCUSDT", CandleInterval::S1, 0, 5_000).await.unwrap();
+        assert_eq!(candles.len(), 5);
+
+        fake.rate_limits.store(3, Ordering::SeqCst);
+        let err = page_klines(&fake, Some(&guard), "BTCUSDT", CandleInterval::S1, 0, 5_000).await.unwrap_err();
+        assert!(matches!(err, TradingError::RateLimited(_)));
+    }
+
+    #[tokio::test]
+    async fn test_downloads_without_cache() {
+        let fake = FakeHistory::default();
+        let requests = Arc::clone(&fake.requests);
+        let history = HistoricalData::new(Box::new(fake), GuardConfig::default(), EventBus::new());
+
+        let start = 1_700_000_040_000;
+        let minute = CandleInterval::M1.as_millis();
+        for _ in 0..2 {
+            let candles = history.klines("BTCUSDT", CandleInterval::M1, start, start + 5 * minute).await.unwrap();
+            assert_eq!(candles.len(), 5);
+        }
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use crate::error::{Result, TradingError};
+use super::rate_limiter::RateLimiter;
+
+/// Longest slice of an error body kept in the error message
+const MAX_ERROR_BODY: usize = 200;
+
+/// Binance: request weight used in the current minute, counted per IP like `RateLimiter::binance`.
+/// Bybit's `x-bapi-limit-status` is deliberately not read: it counts a single
+/// endpoint per UID, not the per-IP budget the shared limiter tracks.
+const BINANCE_USED_WEIGHT: &str = "x-mbx-used-weight-1m";
+
+/// GET `url` and decode the JSON body, mapping non-2xx statuses to errors.
+/// 429 and 418 (Binance's IP ban) become `RateLimited`; malformed bodies
+/// surface as `MarketData` parse errors.
+///
+/// With a `limiter`, `weight` is taken from it first and the exchange's
+/// weight and `Retry-After` headers are fed back into it.
+pub(crate) async fn get_json<T: DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    limiter: Option<&RateLimiter>,
+    weight: u32,
+) -> Result<T> {
+    if let Some(limiter) = limiter {
+        limiter.acquire(weight).await;
+    }
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    if let Some(limiter) = limiter {
+        observe_headers(limiter, response.headers());
+    }
+
+    if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418 {
+        let retry_after = header_u32(response.headers(), reqwest::header::RETRY_AFTER.as_str());
+        if let (Some(limiter), Some(seconds)) = (limiter, retry_after) {
+            limiter.pause_for(Duration::from_secs(seconds as u64));
+        }
+        let retry_after = retry_after.map(|seconds| format!(" (retry after {}s)", seconds)).unwrap_or_default();
+        return Err(TradingError::RateLimited(format!("{}{}", url, retry_after)));
+    }
+
+    let body = response.text().await?;
+    if !status.is_success() {
+        let mut message: String = body.chars().take(MAX_ERROR_BODY).collect();
+        if message.is_empty() {
+            message = status.canonical_reason().unwrap_or("no body").to_string();
+        }
+        return Err(TradingError::Http(status.as_u16(), message));
+    }
+
+    Ok(serde_json::from_str(&body)?)
+}
+
+fn header_u32(headers: &HeaderMap, name: &str) -> Option<u32> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn observe_headers(limiter: &RateLimiter, headers: &HeaderMap) {
+    if let Some(used) = header_u32(headers, BINANCE_USED_WEIGHT) {
+        limiter.observe_used(used);
+    }
+}
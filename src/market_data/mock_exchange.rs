@@ -0,0 +1,399 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use crate::config::exchange_config::ExchangeType;
+use crate::error::{Result, TradingError};
+
+/// Largest request head accepted before the connection is dropped
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Symbol answered with HTTP 429 and a `Retry-After` header
+pub const RATE_LIMITED_SYMBOL: &str = "RATELIMITUSDT";
+/// Symbol answered with truncated JSON
+pub const MALFORMED_SYMBOL: &str = "MALFORMEDUSDT";
+/// Symbol answered with an empty Bybit `list`
+pub const EMPTY_SYMBOL: &str = "EMPTYUSDT";
+
+const BINANCE_PREFIX: &str = "/api/v3";
+const BYBIT_PREFIX: &str = "/v5/market";
+
+/// Canned HTTP response
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// 200 with a JSON body
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::with_status(200, body)
+    }
+
+    pub fn with_status(status: u16, body: impl Into<String>) -> Self {
+        Self { status, headers: Vec::new(), body: body.into() }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+struct Route {
+    target: String,
+    response: MockResponse,
+    /// Responses left before the route is removed (None = unlimited)
+    remaining: Option<usize>,
+    /// Match the target verbatim, without appended parameters
+    exact: bool,
+}
+
+type Routes = Arc<Mutex<Vec<Route>>>;
+
+/// Local HTTP server replaying canned Binance and Bybit REST responses so
+/// fetchers can be exercised offline.
+///
+/// Routes are matched on the request target (path plus query). A route
+/// without a query matches any query; one with a query also matches
+/// requests that append further parameters. Routes added with `route`
+/// take precedence over the canned ones.
+pub struct MockExchange {
+    addr: SocketAddr,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<String>>>,
+    task: JoinHandle<()>,
+}
+
+impl MockExchange {
+    /// Bind to a free local port and start serving the canned responses
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(|e| {
+            TradingError::Config(format!("Cannot bind mock exchange: {}", e))
+        })?;
+        let addr = listener.local_addr()?;
+        let exact = exact_routes()
+            .into_iter()
+            .map(|(target, response)| Route { target, response, remaining: None, exact: true });
+        let canned = canned_routes()
+            .into_iter()
+            .map(|(target, response)| Route { target, response, remaining: None, exact: false });
+        let canned = exact.chain(canned).collect();
+        let routes: Routes = Arc::new(Mutex::new(canned));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let task = tokio::spawn(serve(listener, routes.clone(), requests.clone()));
+        Ok(Self { addr, routes, requests, task })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Base URL for `BinanceFetcher::with_client` / `ExchangeConfig::base_url`
+    pub fn binance_url(&self) -> String {
+        format!("{}{}", self.url(), BINANCE_PREFIX)
+    }
+
+    /// Base URL for `BybitFetcher::with_client` / `ExchangeConfig::base_url`
+    pub fn bybit_url(&self) -> String {
+        format!("{}{}", self.url(), BYBIT_PREFIX)
+    }
+
+    /// Base URL to put in `ExchangeConfig::base_url` for `exchange_type`
+    pub fn base_url(&self, exchange_type: &ExchangeType) -> String {
+        match exchange_type {
+            ExchangeType::Binance => self.binance_url(),
+            ExchangeType::Bybit => self.bybit_url(),
+        }
+    }
+
+    /// Serve `response` for `target`, overriding earlier routes
+    pub fn route(&self, target: impl Into<String>, response: MockResponse) {
+        let route = Route { target: target.into(), response, remaining: None, exact: false };
+        self.routes.lock().unwrap().insert(0, route);
+    }
+
+    /// Serve `response` for the next `times` requests to `target`, then fall
+    /// back to the routes underneath, e.g. to script a transient outage
+    pub fn route_times(&self, target: impl Into<String>, response: MockResponse, times: usize) {
+        let route = Route { target: target.into(), response, remaining: Some(times), exact: false };
+        self.routes.lock().unwrap().insert(0, route);
+    }
+
+    /// Request targets received so far, oldest first
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockExchange {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Route {
+    fn matches(&self, target: &str) -> bool {
+        if self.exact {
+            self.target == target
+        } else {
+            matches(&self.target, target)
+        }
+    }
+}
+
+fn matches(route: &str, target: &str) -> bool {
+    match target.strip_prefix(route) {
+        Some(rest) => rest.is_empty() || (rest.starts_with('?') && !route.contains('?')) || rest.starts_with('&'),
+        None => false,
+    }
+}
+
+/// Batch ticker endpoints, matched verbatim so they don't shadow the per-symbol routes
+fn exact_routes() -> Vec<(String, MockResponse)> {
+    vec![
+        (
+            format!("{}/ticker/24hr?symbols=%5B%22BTCUSDT%22,%22ETHUSDT%22%5D", BINANCE_PREFIX),
+            MockResponse::json(concat!(
+                r#"[{"symbol":"BTCUSDT","lastPrice":"50000.00","volume":"1234.56","bidPrice":"49999.00","bidQty":"1.500","askPrice":"50001.00","askQty":"1.200","closeTime":1700000000000},"#,
+                r#"{"symbol":"ETHUSDT","lastPrice":"3000.00","volume":"5678.90","bidPrice":"2999.90","bidQty":"1.500","askPrice":"3000.10","askQty":"1.200","closeTime":1700000000000}]"#,
+            )),
+        ),
+        (
+            format!("{}/tickers?category=spot", BYBIT_PREFIX),
+            MockResponse::json(concat!(
+                r#"{"retCode":0,"retMsg":"OK","result":{"category":"spot","list":["#,
+                r#"{"symbol":"BTCUSDT","lastPrice":"50010.00","volume24h":"2345.67","bid1Price":"50009.00","bid1Size":"0.800","ask1Price":"50011.00","ask1Size":"0.600"},"#,
+                r#"{"symbol":"ETHUSDT","lastPrice":"3001.00","volume24h":"6789.01","bid1Price":"3000.90","bid1Size":"0.800","ask1Price":"3001.10","ask1Size":"0.600"},"#,
+                r#"{"symbol":"MALFORMEDUSDT","lastPrice":"abc","volume24h":"1"}"#,
+                r#"]},"time":1700000000000}"#,
+            )),
+        ),
+    ]
+}
+
+fn canned_routes() -> Vec<(String, MockResponse)> {
+    let binance_ticker = |symbol: &str| format!("{}/ticker/24hr?symbol={}", BINANCE_PREFIX, symbol);
+    let bybit_ticker = |symbol: &str| format!("{}/tickers?category=spot&symbol={}", BYBIT_PREFIX, symbol);
+    let binance_ok = |symbol: &str, price: &str, volume: &str, bid: &str, ask: &str| {
+        MockResponse::json(format!(
+            r#"{{"symbol":"{}","lastPrice":"{}","volume":"{}","bidPrice":"{}","bidQty":"1.500","askPrice":"{}","askQty":"1.200","closeTime":1700000000000}}"#,
+            symbol, price, volume, bid, ask
+        ))
+    };
+    let bybit_ok = |list: String| {
+        MockResponse::json(format!(
+            r#"{{"retCode":0,"retMsg":"OK","result":{{"category":"spot","list":[{}]}},"time":1700000000000}}"#,
+            list
+        ))
+    };
+    let bybit_entry = |symbol: &str, price: &str, volume: &str, bid: &str, ask: &str| {
+        format!(
+            r#"{{"symbol":"{}","lastPrice":"{}","volume24h":"{}","bid1Price":"{}","bid1Size":"0.800","ask1Price":"{}","ask1Size":"0.600"}}"#,
+            symbol, price, volume, bid, ask
+        )
+    };
+    let rate_limited = |body: &str| MockResponse::with_status(429, body).header("Retry-After", "1");
+
+    vec![
+        (binance_ticker("BTCUSDT"), binance_ok("BTCUSDT", "50000.00", "1234.56", "49999.00", "50001.00")),
+        (binance_ticker("ETHUSDT"), binance_ok("ETHUSDT", "3000.00", "5678.90", "2999.90", "3000.10")),
+        (binance_ticker(RATE_LIMITED_SYMBOL), rate_limited(r#"{"code":-1003,"msg":"Too many requests."}"#)),
+        (binance_ticker(MALFORMED_SYMBOL), MockResponse::json(r#"{"symbol":"MALFORMEDUSDT","lastPrice":"#)),
+        (
+            format!("{}/ticker/24hr", BINANCE_PREFIX),
+            MockResponse::with_status(400, r#"{"code":-1121,"msg":"Invalid symbol."}"#),
+        ),
+        (
+            format!("{}/exchangeInfo", BINANCE_PREFIX),
+            MockResponse::json(concat!(
+                r#"{"timezone":"UTC","serverTime":1700000000000,"symbols":["#,
+                r#"{"symbol":"BTCUSDT","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDT","filters":["#,
+                r#"{"filterType":"PRICE_FILTER","minPrice":"0.01","maxPrice":"1000000.00","tickSize":"0.01"},"#,
+                r#"{"filterType":"LOT_SIZE","minQty":"0.00001","maxQty":"9000.00000","stepSize":"0.00001"},"#,
+                r#"{"filterType":"ICEBERG_PARTS","limit":10},"#,
+                r#"{"filterType":"NOTIONAL","minNotional":"5.00","applyMinToMarket":true,"maxNotional":"9000000.00"}]},"#,
+                r#"{"symbol":"ETHUSDT","status":"TRADING","baseAsset":"ETH","quoteAsset":"USDT","filters":["#,
+                r#"{"filterType":"PRICE_FILTER","minPrice":"0.01","maxPrice":"1000000.00","tickSize":"0.01"},"#,
+                r#"{"filterType":"LOT_SIZE","minQty":"0.0001","maxQty":"9000.0000","stepSize":"0.0001"},"#,
+                r#"{"filterType":"MIN_NOTIONAL","minNotional":"5.00"}]},"#,
+                r#"{"symbol":"DUSTUSDT","status":"TRADING","baseAsset":"DUST","quoteAsset":"USDT","filters":["#,
+                r#"{"filterType":"PRICE_FILTER","minPrice":"0","maxPrice":"0","tickSize":"0"}]},"#,
+                r#"{"symbol":"LUNAUSDT","status":"BREAK","baseAsset":"LUNA","quoteAsset":"USDT","filters":[]}]}"#,
+            )),
+        ),
+        (
+            format!("{}/depth", BINANCE_PREFIX),
+            MockResponse::json(concat!(
+                r#"{"lastUpdateId":1027024,"#,
+                r#""bids":[["49999.00","1.500"],["49998.50","2.000"],["49997.00","0.750"]],"#,
+                r#""asks":[["50001.00","1.200"],["50002.00","3.100"],["50003.50","0.400"]]}"#,
+            )),
+        ),
+        (
+            format!("{}/trades", BINANCE_PREFIX),
+            MockResponse::json(concat!(
+                r#"[{"id":28457,"price":"50000.00","qty":"0.010","quoteQty":"500.00","time":1700000000100,"isBuyerMaker":true,"isBestMatch":true},"#,
+                r#"{"id":28458,"price":"50001.00","qty":"0.250","quoteQty":"12500.25","time":1700000000200,"isBuyerMaker":false,"isBestMatch":true}]"#,
+            )),
+        ),
+        (
+            format!("{}/klines", BINANCE_PREFIX),
+            MockResponse::json(concat!(
+                r#"[[1699999980000,"50000.00","50100.00","49900.00","50050.00","10.5",1700000039999,"525525.0",100,"5.0","250250.0","0"],"#,
+                r#"[1700000040000,"50050.00","50200.00","50000.00","50150.00","12.0",1700000099999,"601800.0",120,"6.0","300900.0","0"],"#,
+                r#"[1700000100000,"50150.00","50300.00","50100.00","50250.00","9.5",1700000159999,"477375.0",90,"4.5","226125.0","0"]]"#,
+            )),
+        ),
+        (bybit_ticker("BTCUSDT"), bybit_ok(bybit_entry("BTCUSDT", "50010.00", "2345.67", "50009.00", "50011.00"))),
+        (bybit_ticker("ETHUSDT"), bybit_ok(bybit_entry("ETHUSDT", "3001.00", "6789.01", "3000.90", "3001.10"))),
+        (bybit_ticker(EMPTY_SYMBOL), bybit_ok(String::new())),
+        (bybit_ticker(RATE_LIMITED_SYMBOL), rate_limited(r#"{"retCode":10006,"retMsg":"Too many visits!"}"#)),
+        (bybit_ticker(MALFORMED_SYMBOL), MockResponse::json(r#"{"retCode":0,"result":{"list":[{"symbol""#)),
+        (
+            format!("{}/tickers", BYBIT_PREFIX),
+            MockResponse::json(r#"{"retCode":10001,"retMsg":"params error: symbol invalid","result":{},"time":1700000000000}"#),
+        ),
+        (
+            format!("{}/instruments-info", BYBIT_PREFIX),
+            MockResponse::json(concat!(
+                r#"{"retCode":0,"retMsg":"OK","result":{"category":"spot","list":["#,
+                r#"{"symbol":"BTCUSDT","baseCoin":"BTC","quoteCoin":"USDT","status":"Trading","#,
+                r#""lotSizeFilter":{"basePrecision":"0.000001","quotePrecision":"0.00000001","minOrderQty":"0.000048","maxOrderQty":"71.73956243","minOrderAmt":"1","maxOrderAmt":"2000000"},"#,
+                r#""priceFilter":{"tickSize":"0.01"}},"#,
+                r#"{"symbol":"ETHUSDT","baseCoin":"ETH","quoteCoin":"USDT","status":"Trading","#,
+                r#""lotSizeFilter":{"basePrecision":"0.00001","quotePrecision":"0.0000001","minOrderQty":"0.00062","maxOrderQty":"1229.2336343","minOrderAmt":"1","maxOrderAmt":"2000000"},"#,
+                r#""priceFilter":{"tickSize":"0.01"}}"#,
+                r#"]},"time":1700000000000}"#,
+            )),
+        ),
+        (
+            format!("{}/orderbook", BYBIT_PREFIX),
+            MockResponse::json(concat!(
+                r#"{"retCode":0,"retMsg":"OK","result":{"s":"BTCUSDT","#,
+                r#""b":[["50009.00","0.800"],["50008.00","1.100"]],"#,
+                r#""a":[["50011.00","0.600"],["50012.50","2.400"]],"#,
+                r#""ts":1700000000000,"u":184420,"seq":7961638724}}"#,
+            )),
+        ),
+        (
+            format!("{}/recent-trade", BYBIT_PREFIX),
+            // Newest first
+            MockResponse::json(concat!(
+                r#"{"retCode":0,"retMsg":"OK","result":{"category":"spot","list":["#,
+                r#"{"execId":"2290000000061656299","symbol":"BTCUSDT","price":"50010.50","size":"0.002","side":"Sell","time":"1700000000300","isBlockTrade":false},"#,
+                r#"{"execId":"2290000000061656298","symbol":"BTCUSDT","price":"50010.00","size":"0.100","side":"Buy","time":"1700000000250","isBlockTrade":false}"#,
+                r#"]}}"#,
+            )),
+        ),
+        (
+            format!("{}/kline", BYBIT_PREFIX),
+            // Bybit returns candles newest first
+            MockResponse::json(concat!(
+                r#"{"retCode":0,"retMsg":"OK","result":{"category":"spot","symbol":"BTCUSDT","list":["#,
+                r#"["1700000100000","50150.00","50300.00","50100.00","50250.00","9.5","477375.0"],"#,
+                r#"["1700000040000","50050.00","50200.00","50000.00","50150.00","12.0","601800.0"],"#,
+                r#"["1699999980000","50000.00","50100.00","49900.00","50050.00","10.5","525525.0"]]}}"#,
+            )),
+        ),
+    ]
+}
+
+async fn serve(listener: TcpListener, routes: Routes, requests: Arc<Mutex<Vec<String>>>) {
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(_) => continue,
+        };
+        let routes = routes.clone();
+        let requests = requests.clone();
+        tokio::spawn(async move {
+            let _ = handle_connection(stream, &routes, &requests).await;
+        });
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    routes: &Routes,
+    requests: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 || request.len() + n > MAX_REQUEST_BYTES {
+            return Ok(());
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let target = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default()
+        .to_string();
+    requests.lock().unwrap().push(target.clone());
+
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        match routes.iter().position(|route| route.remaining != Some(0) && route.matches(&target)) {
+            Some(index) => {
+                let route = &mut routes[index];
+                if let Some(remaining) = route.remaining.as_mut() {
+                    *remaining -= 1;
+                }
+                route.response.clone()
+            }
+            None => MockResponse::with_status(404, r#"{"msg":"Not Found"}"#),
+        }
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        418 => "I'm a teapot",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_matching() {
+        assert!(matches("/api/v3/klines", "/api/v3/klines?symbol=BTCUSDT"));
+        assert!(matches("/api/v3/ticker/24hr?symbol=BTCUSDT", "/api/v3/ticker/24hr?symbol=BTCUSDT"));
+        assert!(matches("/api/v3/ticker/24hr?symbol=BTCUSDT", "/api/v3/ticker/24hr?symbol=BTCUSDT&type=FULL"));
+        assert!(!matches("/api/v3/ticker/24hr?symbol=BTC", "/api/v3/ticker/24hr?symbol=BTCUSDT"));
+        assert!(!matches("/api/v3/klines", "/api/v3/klinesx"));
+    }
+}
@@ -1,4 +1,10 @@
 pub mod event;
+pub mod order_book;
+pub mod trade;
+pub mod candle;
+pub mod history;
+pub mod file_fetcher;
+pub mod recorder;
 pub mod binance_fetcher;
 pub mod fetcher_trait;
 pub mod exchange_factory;
@@ -6,12 +12,37 @@ pub mod normalizer;
 pub mod bybit_fetcher;
 pub mod monitor;
 pub mod resilient_fetcher;
+pub mod consensus_fetcher;
+pub mod scheduler;
+pub mod stream_trait;
+pub mod websocket;
+pub mod binance_stream;
+pub mod bybit_stream;
+mod http;
+pub mod rate_limiter;
+pub mod guarded_fetcher;
+pub mod mock_exchange;
 
-pub use event::PriceEvent;
+pub use event::{PriceEvent, Quote};
 pub use binance_fetcher::BinanceFetcher;
-pub use fetcher_trait::MarketDataFetcher;
+pub use fetcher_trait::{HistoricalFetcher, InstrumentFetcher, MarketDataFetcher, OrderBookFetcher, PriceBatch, TradeFetcher};
+pub use trade::{AggressorSide, TradePrint};
+pub use candle::{Candle, CandleAggregator, CandleAggregatorConfig, CandleFeed, CandleInterval};
+pub use history::{HistoricalData, KlineCache};
+pub use file_fetcher::{CsvColumns, CsvSource, FileFetcher, FileStream, TimestampFormat};
+pub use recorder::{MarketDataRecorder, RecorderConfig, Recording, RecordingIndex};
+pub use order_book::{BookLevel, BookUpdateKind, OrderBook, OrderBookUpdate};
 pub use normalizer::PriceValidator;
 pub use exchange_factory::ExchangeFactory;
 pub use bybit_fetcher::BybitFetcher;
-pub use monitor::PriceMonitor;
-pub use resilient_fetcher::ResilientFetcher;
+pub use monitor::{DataQualityConfig, PriceMonitor, QualityIssue, QualityOutcome, QualityReport};
+pub use resilient_fetcher::{FeedHealth, HealthConfig, ResilientFetcher};
+pub use scheduler::{PollRound, PollingConfig, PollingScheduler};
+pub use consensus_fetcher::{ConsensusConfig, ConsensusFetcher, ConsensusMethod, VenueQuote};
+pub use rate_limiter::RateLimiter;
+pub use guarded_fetcher::{CircuitBreaker, CircuitBreakerConfig, CircuitState, GuardConfig, GuardedFetcher, RequestGuard, RetryPolicy};
+pub use stream_trait::{MarketDataStream, PriceStream};
+pub use websocket::StreamConfig;
+pub use binance_stream::BinanceStream;
+pub use bybit_stream::BybitStream;
+pub use mock_exchange::{MockExchange, MockResponse};
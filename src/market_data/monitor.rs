@@ -1,38 +1,403 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 use crate::error::{Result, TradingError};
+use crate::engine::{Causation, EventBus, Event};
 use super::event::PriceEvent;
+use super::normalizer::PriceValidator;
+use super::trade::TradePrint;
 
-#[derive(Debug)]
+const SOURCE: &str = "PriceMonitor";
+
+/// Thresholds of the price data-quality checks
+#[derive(Debug, Clone)]
+pub struct DataQualityConfig {
+    /// Exchange-time gap between two updates reported as a warning
+    pub gap_threshold_ms: u64,
+    /// Quarantine prices whose exchange timestamp is this far behind the receive time
+    pub max_staleness_ms: Option<u64>,
+    /// Quarantine moves larger than this fraction of the last price (0.05 = 5%)
+    pub max_jump: Option<Decimal>,
+    /// Quarantine returns more than this many standard deviations from recent returns
+    pub max_zscore: Option<f64>,
+    /// Returns kept per symbol for the z-score
+    pub volatility_window: usize,
+    /// Returns needed before the z-score filter applies
+    pub min_volatility_samples: usize,
+}
+
+impl DataQualityConfig {
+    pub fn new(gap_threshold_ms: u64) -> Self {
+        Self { gap_threshold_ms, ..Self::default() }
+    }
+}
+
+impl Default for DataQualityConfig {
+    fn default() -> Self {
+        Self {
+            gap_threshold_ms: 60_000,
+            max_staleness_ms: Some(30_000),
+            max_jump: Some(Decimal::new(5, 2)),
+            max_zscore: None,
+            volatility_window: 50,
+            min_volatility_samples: 20,
+        }
+    }
+}
+
+/// Verdict on one price update, most severe issue wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum QualityOutcome {
+    Pass,
+    /// Used, but flagged
+    Warning,
+    /// Withheld from strategies and execution
+    Quarantine,
+}
+
+impl fmt::Display for QualityOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QualityOutcome::Pass => write!(f, "Pass"),
+            QualityOutcome::Warning => write!(f, "Warning"),
+            QualityOutcome::Quarantine => write!(f, "Quarantine"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QualityIssue {
+    /// Exchange timestamp this far behind the receive time
+    Stale { age_ms: u64 },
+    /// No update for this long in exchange time
+    Gap { gap_ms: u64 },
+    /// Move from the last accepted price, as a fraction; `confirmed` when a
+    /// second update held the new level and it was accepted
+    Jump { change: Decimal, confirmed: bool },
+    /// Return this many standard deviations from recent returns
+    Spike { zscore: f64, confirmed: bool },
+    CrossedQuote { bid: Decimal, ask: Decimal },
+    LockedQuote { price: Decimal },
+    /// Trade ids skipped before this print
+    TradeGap { missing: u64 },
+    /// Print with a non-positive price or quantity
+    InvalidTrade { price: Decimal, quantity: Decimal },
+}
+
+impl QualityIssue {
+    pub fn severity(&self) -> QualityOutcome {
+        match self {
+            QualityIssue::Gap { .. } | QualityIssue::LockedQuote { .. } => QualityOutcome::Warning,
+            QualityIssue::Jump { confirmed: true, .. } | QualityIssue::Spike { confirmed: true, .. } => {
+                QualityOutcome::Warning
+            }
+            QualityIssue::Stale { .. }
+            | QualityIssue::Jump { .. }
+            | QualityIssue::Spike { .. }
+            | QualityIssue::CrossedQuote { .. }
+            | QualityIssue::TradeGap { .. }
+            | QualityIssue::InvalidTrade { .. } => QualityOutcome::Quarantine,
+        }
+    }
+}
+
+impl fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QualityIssue::Stale { age_ms } => write!(f, "stale by {}ms", age_ms),
+            QualityIssue::Gap { gap_ms } => write!(f, "gap of {}ms", gap_ms),
+            QualityIssue::Jump { change, confirmed } => {
+                write!(f, "jump of {}%{}", (change * Decimal::ONE_HUNDRED).round_dp(2), if *confirmed { " (confirmed)" } else { "" })
+            }
+            QualityIssue::Spike { zscore, confirmed } => {
+                write!(f, "spike of {:.1} sigma{}", zscore, if *confirmed { " (confirmed)" } else { "" })
+            }
+            QualityIssue::CrossedQuote { bid, ask } => write!(f, "crossed quote {} > {}", bid, ask),
+            QualityIssue::LockedQuote { price } => write!(f, "locked quote at {}", price),
+            QualityIssue::TradeGap { missing } => write!(f, "{} trades missing", missing),
+            QualityIssue::InvalidTrade { price, quantity } => write!(f, "invalid trade of {} at {}", quantity, price),
+        }
+    }
+}
+
+/// Outcome of the checks on one update
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityReport {
+    pub outcome: QualityOutcome,
+    pub issues: Vec<QualityIssue>,
+}
+
+#[derive(Debug, Default)]
+struct SymbolState {
+    /// Exchange time and price of the last accepted update
+    last: Option<(u64, Decimal)>,
+    returns: VecDeque<f64>,
+    /// Price of the last update quarantined for a jump or spike, to confirm a level shift
+    pending_move: Option<Decimal>,
+}
+
+/// Data-quality guard for prices: drops duplicates, and publishes
+/// `Event::DataQuality` for stale, gapped, jumping or crossed updates
+/// instead of failing the pipeline
 pub struct PriceMonitor {
-    last_seen: HashMap<String, (u64, Decimal)>,
-    gap_threshold_ms: u64,
+    config: DataQualityConfig,
+    symbols: HashMap<String, SymbolState>,
+    /// Last trade id seen per symbol
+    last_trade_ids: HashMap<String, u64>,
+    event_bus: EventBus,
 }
 
 impl PriceMonitor {
-    pub fn new(gap_threshold_ms: u64) -> Self {
+    pub fn new(config: DataQualityConfig, event_bus: EventBus) -> Self {
         Self {
-            last_seen: HashMap::new(),
-            gap_threshold_ms,
+            config,
+            symbols: HashMap::new(),
+            last_trade_ids: HashMap::new(),
+            event_bus,
+        }
+    }
+
+    /// The event if it may be used; None for duplicates and quarantined updates.
+    /// Anything but a pass is published as `Event::DataQuality`.
+    pub fn process(&mut self, event: PriceEvent) -> Option<PriceEvent> {
+        let report = self.check(&event)?;
+        let outcome = report.outcome;
+        if outcome != QualityOutcome::Pass {
+            warn!(symbol = %event.symbol, price = %event.price, outcome = %outcome, issues = ?report.issues, "Data quality issue");
+            self.report(&event.symbol, report, event.sequence.map(Causation::root));
         }
+        (outcome != QualityOutcome::Quarantine).then_some(event)
     }
 
-    /// Returns None for duplicate events; error for gaps.
-    pub fn process(&mut self, event: PriceEvent) -> Result<Option<PriceEvent>> {
-        if let Some((last_ts, last_price)) = self.last_seen.get(&event.symbol).cloned() {
+    /// `process`, then publish the event as `Event::PriceUpdated` from `source`
+    /// and stamp its sequence. Only prices admitted here reach subscribers.
+    pub fn admit(&mut self, source: &str, event: PriceEvent) -> Result<Option<PriceEvent>> {
+        let Some(mut event) = self.process(event) else {
+            return Ok(None);
+        };
+        event.sequence = Some(self.event_bus.publish_from(source, Event::PriceUpdated(event.clone()), None)?);
+        Ok(Some(event))
+    }
+
+    fn report(&self, symbol: &str, report: QualityReport, cause: Option<Causation>) {
+        let _ = self.event_bus.publish_from(
+            SOURCE,
+            Event::DataQuality { symbol: symbol.to_string(), outcome: report.outcome, issues: report.issues },
+            cause,
+        );
+    }
+
+    /// Run the checks and update the per-symbol state; None for duplicates.
+    /// Quarantined updates do not move the reference price.
+    pub fn check(&mut self, event: &PriceEvent) -> Option<QualityReport> {
+        let config = &self.config;
+        let state = self.symbols.entry(event.symbol.clone()).or_default();
+        let mut issues = Vec::new();
+
+        if let Some(quote) = event.quote {
+            if quote.bid > quote.ask {
+                issues.push(QualityIssue::CrossedQuote { bid: quote.bid, ask: quote.ask });
+            } else if quote.bid == quote.ask {
+                issues.push(QualityIssue::LockedQuote { price: quote.bid });
+            }
+        }
+        if let Some(max) = config.max_staleness_ms {
+            let age_ms = event.latency_ms();
+            if age_ms > max {
+                issues.push(QualityIssue::Stale { age_ms });
+            }
+        }
+
+        let mut ret = None;
+        if let Some((last_ts, last_price)) = state.last {
             if event.timestamp <= last_ts && event.price == last_price {
-                return Ok(None);
+                debug!(symbol = %event.symbol, timestamp = event.timestamp, "Dropping duplicate price");
+                return None;
+            }
+            if event.timestamp > last_ts && event.timestamp - last_ts > config.gap_threshold_ms {
+                issues.push(QualityIssue::Gap { gap_ms: event.timestamp - last_ts });
             }
-            if event.timestamp > last_ts && event.timestamp - last_ts > self.gap_threshold_ms {
-                return Err(TradingError::MarketData(format!(
-                    "Price gap detected for {}: {}ms",
-                    event.symbol,
-                    event.timestamp - last_ts
-                )));
+
+            // A second update nearer the quarantined price than the old one confirms a real move
+            let change = PriceValidator::deviation(event.price, last_price);
+            let confirmed = state.pending_move.is_some_and(|pending| {
+                PriceValidator::deviation(event.price, pending) < change
+            });
+            if config.max_jump.is_some_and(|max| change > max) {
+                issues.push(QualityIssue::Jump { change, confirmed });
+            }
+
+            let r = ((event.price - last_price) / last_price).to_f64().unwrap_or(0.0);
+            if let Some(max) = config.max_zscore {
+                if state.returns.len() >= config.min_volatility_samples {
+                    let n = state.returns.len() as f64;
+                    let mean = state.returns.iter().sum::<f64>() / n;
+                    let sd = (state.returns.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+                    let zscore = (r - mean).abs() / sd;
+                    if sd > 0.0 && zscore > max {
+                        issues.push(QualityIssue::Spike { zscore, confirmed });
+                    }
+                }
             }
+            ret = Some(r);
         }
 
-        self.last_seen.insert(event.symbol.clone(), (event.timestamp, event.price));
-        Ok(Some(event))
+        let outcome = issues.iter().map(QualityIssue::severity).max().unwrap_or(QualityOutcome::Pass);
+        let moved = issues.iter().any(|issue| matches!(issue, QualityIssue::Jump { .. } | QualityIssue::Spike { .. }));
+        if outcome == QualityOutcome::Quarantine {
+            state.pending_move = moved.then_some(event.price);
+        } else {
+            state.pending_move = None;
+            state.last = Some((event.timestamp, event.price));
+            // A confirmed level shift is not volatility of the usual kind
+            if let Some(r) = ret.filter(|_| !moved) {
+                if state.returns.len() == config.volatility_window {
+                    state.returns.pop_front();
+                }
+                state.returns.push_back(r);
+            }
+        }
+        Some(QualityReport { outcome, issues })
     }
-}
\ No newline at end of file
+
+    /// Trade-id based counterpart of `process`: None for trades already seen,
+    /// and for invalid prints or the print after skipped ids, which are published
+    /// as `Event::DataQuality`. Gaps are only checked with `consecutive_ids`, for
+    /// venues whose per-symbol ids have no holes (Binance); like `process`, a gap
+    /// is reported once.
+    pub fn process_trade(&mut self, trade: TradePrint, consecutive_ids: bool) -> Option<TradePrint> {
+        let last_id = self.last_trade_ids.get(&trade.symbol).copied();
+        if last_id.is_some_and(|last_id| trade.trade_id <= last_id) {
+            debug!(symbol = %trade.symbol, trade_id = trade.trade_id, "Dropping duplicate trade");
+            return None;
+        }
+
+        let issue = if trade.price <= Decimal::ZERO || trade.quantity <= Decimal::ZERO {
+            // A bad print does not advance the id, so a gap before it is still caught
+            Some(QualityIssue::InvalidTrade { price: trade.price, quantity: trade.quantity })
+        } else {
+            self.last_trade_ids.insert(trade.symbol.clone(), trade.trade_id);
+            last_id
+                .filter(|last_id| consecutive_ids && trade.trade_id > last_id + 1)
+                .map(|last_id| QualityIssue::TradeGap { missing: trade.trade_id - last_id - 1 })
+        };
+        match issue {
+            Some(issue) => {
+                warn!(symbol = %trade.symbol, trade_id = trade.trade_id, issue = %issue, "Trade data quality issue");
+                let report = QualityReport { outcome: issue.severity(), issues: vec![issue] };
+                self.report(&trade.symbol, report, None);
+                None
+            }
+            None => Some(trade),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::engine::EventKind;
+    use crate::market_data::event::Quote;
+    use crate::market_data::trade::AggressorSide;
+
+    fn print(trade_id: u64) -> TradePrint {
+        TradePrint::new("BTCUSDT".to_string(), trade_id, Decimal::from(100), Decimal::ONE, AggressorSide::Buy, trade_id).unwrap()
+    }
+
+    #[test]
+    fn test_trade_id_dedupe_and_gaps() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        bus.subscribe(EventKind::DataQuality, move |envelope| {
+            if let Event::DataQuality { issues, .. } = &envelope.event {
+                sink.lock().unwrap().extend(issues.clone());
+            }
+        }).unwrap().detach();
+
+        let mut monitor = PriceMonitor::new(DataQualityConfig::default(), bus);
+        assert!(monitor.process_trade(print(10), true).is_some());
+        assert!(monitor.process_trade(print(11), true).is_some());
+        assert!(monitor.process_trade(print(11), true).is_none());
+
+        assert!(monitor.process_trade(print(15), true).is_none());
+        assert!(monitor.process_trade(print(16), true).is_some());
+
+        let mut bad = print(17);
+        bad.quantity = Decimal::ZERO;
+        assert!(monitor.process_trade(bad, true).is_none());
+        assert!(monitor.process_trade(print(17), true).is_some());
+
+        // Venues with increasing but sparse ids skip the gap check
+        assert!(monitor.process_trade(print(40), false).is_some());
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            QualityIssue::TradeGap { missing: 3 },
+            QualityIssue::InvalidTrade { price: Decimal::from(100), quantity: Decimal::ZERO },
+        ]);
+    }
+
+    fn price(price: i64, timestamp: u64) -> PriceEvent {
+        let mut event = PriceEvent::new("BTCUSDT".to_string(), Decimal::from(price), Decimal::ONE).unwrap();
+        event.timestamp = timestamp;
+        event.received_at = timestamp + 100;
+        event
+    }
+
+    #[test]
+    fn test_quarantines_jumps_until_confirmed_and_publishes() {
+        let bus = EventBus::new();
+        let mut monitor = PriceMonitor::new(DataQualityConfig::default(), bus.clone());
+        assert!(monitor.process(price(100, 1_000)).is_some());
+        assert!(monitor.process(price(100, 1_000)).is_none());
+
+        // 20% print is held back; a second one at the new level is accepted with a warning
+        assert!(monitor.process(price(120, 2_000)).is_none());
+        let report = monitor.check(&price(121, 3_000)).unwrap();
+        assert_eq!(report.outcome, QualityOutcome::Warning);
+        assert!(matches!(report.issues[0], QualityIssue::Jump { confirmed: true, .. }));
+
+        // A lone bad print does not move the reference
+        assert!(monitor.process(price(60, 4_000)).is_none());
+        assert!(monitor.process(price(122, 5_000)).is_some());
+
+        // Gaps only warn
+        let report = monitor.check(&price(122, 100_000)).unwrap();
+        assert_eq!(report.issues, vec![QualityIssue::Gap { gap_ms: 95_000 }]);
+        assert_eq!(report.outcome, QualityOutcome::Warning);
+
+        // Only `process` publishes: the two quarantined prints
+        assert_eq!(bus.metrics_snapshot().get("DataQuality"), Some(&2));
+    }
+
+    #[test]
+    fn test_stale_crossed_and_zscore() {
+        let config = DataQualityConfig {
+            max_jump: None,
+            max_zscore: Some(5.0),
+            min_volatility_samples: 4,
+            ..DataQualityConfig::default()
+        };
+        let mut monitor = PriceMonitor::new(config, EventBus::new());
+
+        let mut stale = price(100, 1_000);
+        stale.received_at = 40_000;
+        assert_eq!(monitor.check(&stale).unwrap().issues, vec![QualityIssue::Stale { age_ms: 39_000 }]);
+
+        let mut crossed = price(1000, 1_000).with_quote(Some(Quote::new(Decimal::from(1001), Decimal::ONE, Decimal::from(1000), Decimal::ONE)));
+        assert_eq!(monitor.check(&crossed).unwrap().outcome, QualityOutcome::Quarantine);
+        crossed.quote = Some(Quote::new(Decimal::from(1000), Decimal::ONE, Decimal::from(1000), Decimal::ONE));
+        assert_eq!(monitor.check(&crossed).unwrap().outcome, QualityOutcome::Warning);
+
+        for (i, p) in [1001, 1000, 1001, 1000].into_iter().enumerate() {
+            assert_eq!(monitor.check(&price(p, 2_000 + i as u64)).unwrap().outcome, QualityOutcome::Pass);
+        }
+        let report = monitor.check(&price(1010, 3_000)).unwrap();
+        assert_eq!(report.outcome, QualityOutcome::Quarantine);
+        assert!(matches!(report.issues[0], QualityIssue::Spike { confirmed: false, .. }));
+    }
+}
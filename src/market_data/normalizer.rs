@@ -1,5 +1,6 @@
 use rust_decimal::Decimal;
 use crate::error::{TradingError, Result};
+use crate::instrument::{InstrumentRegistry, DEFAULT_PRECISION};
 use super::event::PriceEvent;
 
 pub struct PriceValidator;
@@ -21,9 +22,46 @@ impl PriceValidator {
         Ok(())
     }
 
+    /// Relative distance of `price` from `reference`
+    pub fn deviation(price: Decimal, reference: Decimal) -> Decimal {
+        if reference.is_zero() {
+            return Decimal::MAX;
+        }
+        (price - reference).abs() / reference.abs()
+    }
+
+    /// Reject prices more than `max_deviation` (a fraction, 0.01 = 1%) away
+    /// from a reference such as a cross-exchange consensus or the last trade
+    pub fn validate_against(event: &PriceEvent, reference: Decimal, max_deviation: Decimal) -> Result<()> {
+        Self::validate(event)?;
+        let deviation = Self::deviation(event.price, reference);
+        if deviation > max_deviation {
+            return Err(TradingError::Validation(format!(
+                "{} price {} is {:.4}% from reference {} (max {}%)",
+                event.symbol,
+                event.price,
+                deviation * Decimal::ONE_HUNDRED,
+                reference,
+                max_deviation * Decimal::ONE_HUNDRED
+            )));
+        }
+        Ok(())
+    }
+
     pub fn normalize(mut event: PriceEvent) -> Result<PriceEvent> {
         Self::validate(&event)?;
-        event.price = event.price.round_dp(8);
+        event.price = event.price.round_dp(DEFAULT_PRECISION);
+        Ok(event)
+    }
+
+    /// `normalize` onto the price tick of listed instruments
+    pub fn normalize_with(mut event: PriceEvent, instruments: &InstrumentRegistry) -> Result<PriceEvent> {
+        let Some(instrument) = instruments.get(&event.symbol) else {
+            return Self::normalize(event);
+        };
+        Self::validate(&event)?;
+        event.price = instrument.round_price(event.price);
+        instrument.validate_price(event.price)?;
         Ok(event)
     }
 }
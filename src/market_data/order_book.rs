@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use flate2::Crc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::error::{Result, TradingError};
+
+/// Levels per side covered by the book checksum
+pub const CHECKSUM_DEPTH: usize = 25;
+
+/// One price level; a zero quantity in a delta removes the level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl BookLevel {
+    pub fn new(price: Decimal, quantity: Decimal) -> Self {
+        Self { price, quantity }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BookUpdateKind {
+    /// Full book; replaces whatever was held before
+    Snapshot,
+    /// Changed levels only
+    Delta,
+}
+
+/// Depth snapshot or incremental update as received from an exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookUpdate {
+    pub symbol: String,
+    pub kind: BookUpdateKind,
+    /// Exchange update ids covered by this message (equal for snapshots)
+    pub first_update_id: u64,
+    pub last_update_id: u64,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    /// CRC32 of the resulting book, for exchanges that publish one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u32>,
+    pub timestamp: u64,
+}
+
+impl OrderBookUpdate {
+    pub fn snapshot(symbol: String, update_id: u64, bids: Vec<BookLevel>, asks: Vec<BookLevel>) -> Result<Self> {
+        Self::build(symbol, BookUpdateKind::Snapshot, update_id, update_id, bids, asks)
+    }
+
+    pub fn delta(
+        symbol: String,
+        first_update_id: u64,
+        last_update_id: u64,
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+    ) -> Result<Self> {
+        if first_update_id > last_update_id {
+            return Err(TradingError::Validation(format!(
+                "Delta update range {}..{} is reversed", first_update_id, last_update_id
+            )));
+        }
+        Self::build(symbol, BookUpdateKind::Delta, first_update_id, last_update_id, bids, asks)
+    }
+
+    fn build(
+        symbol: String,
+        kind: BookUpdateKind,
+        first_update_id: u64,
+        last_update_id: u64,
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+    ) -> Result<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        Ok(Self { symbol, kind, first_update_id, last_update_id, bids, asks, checksum: None, timestamp })
+    }
+}
+
+/// Local level-2 book built from a snapshot plus incremental updates
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    symbol: String,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    /// None until a snapshot arrives, and again after a gap or checksum failure
+    last_update_id: Option<u64>,
+    timestamp: u64,
+}
+
+impl OrderBook {
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_id: None,
+            timestamp: 0,
+        }
+    }
+
+    /// Apply a snapshot or delta.
+    ///
+    /// Returns Ok(false) for deltas already covered by the book. A delta that
+    /// skips update ids or fails its checksum is an error and leaves the book
+    /// waiting for a fresh snapshot (`needs_snapshot`).
+    pub fn apply(&mut self, update: &OrderBookUpdate) -> Result<bool> {
+        if update.symbol != self.symbol {
+            return Err(TradingError::Validation(format!(
+                "Update for {} applied to {} book", update.symbol, self.symbol
+            )));
+        }
+
+        match update.kind {
+            BookUpdateKind::Snapshot => {
+                self.bids.clear();
+                self.asks.clear();
+            }
+            BookUpdateKind::Delta => {
+                let last = self.last_update_id.ok_or_else(|| {
+                    TradingError::MarketData(format!("Order book for {} has no snapshot", self.symbol))
+                })?;
+                if update.last_update_id <= last {
+                    return Ok(false);
+                }
+                if update.first_update_id > last + 1 {
+                    self.last_update_id = None;
+                    return Err(TradingError::MarketData(format!(
+                        "Order book gap for {}: expected update {}, got {}",
+                        self.symbol, last + 1, update.first_update_id
+                    )));
+                }
+            }
+        }
+
+        apply_levels(&mut self.bids, &update.bids);
+        apply_levels(&mut self.asks, &update.asks);
+        self.last_update_id = Some(update.last_update_id);
+        self.timestamp = update.timestamp;
+
+        if let Some(expected) = update.checksum {
+            let actual = self.checksum();
+            if actual != expected {
+                self.last_update_id = None;
+                return Err(TradingError::MarketData(format!(
+                    "Order book checksum mismatch for {}: expected {}, computed {}",
+                    self.symbol, expected, actual
+                )));
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn last_update_id(&self) -> Option<u64> {
+        self.last_update_id
+    }
+
+    pub fn needs_snapshot(&self) -> bool {
+        self.last_update_id.is_none()
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn best_bid(&self) -> Option<BookLevel> {
+        self.bids().next()
+    }
+
+    pub fn best_ask(&self) -> Option<BookLevel> {
+        self.asks().next()
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    pub fn mid_price(&self) -> Option<Decimal> {
+        Some((self.best_ask()?.price + self.best_bid()?.price) / Decimal::TWO)
+    }
+
+    /// True when the best bid is at or above the best ask
+    pub fn is_crossed(&self) -> bool {
+        self.spread().is_some_and(|spread| spread <= Decimal::ZERO)
+    }
+
+    /// Bids from best (highest) to worst
+    pub fn bids(&self) -> impl Iterator<Item = BookLevel> + '_ {
+        self.bids.iter().rev().map(|(price, quantity)| BookLevel::new(*price, *quantity))
+    }
+
+    /// Asks from best (lowest) to worst
+    pub fn asks(&self) -> impl Iterator<Item = BookLevel> + '_ {
+        self.asks.iter().map(|(price, quantity)| BookLevel::new(*price, *quantity))
+    }
+
+    /// Top `levels` per side as (bids, asks)
+    pub fn depth(&self, levels: usize) -> (Vec<BookLevel>, Vec<BookLevel>) {
+        (self.bids().take(levels).collect(), self.asks().take(levels).collect())
+    }
+
+    /// CRC32 over the top `CHECKSUM_DEPTH` levels, interleaved bid then ask,
+    /// as "price:quantity" joined with ':'
+    pub fn checksum(&self) -> u32 {
+        let mut bids = self.bids().take(CHECKSUM_DEPTH);
+        let mut asks = self.asks().take(CHECKSUM_DEPTH);
+        let mut parts = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+        for _ in 0..CHECKSUM_DEPTH {
+            for level in [bids.next(), asks.next()].into_iter().flatten() {
+                parts.push(level.price.to_string());
+                parts.push(level.quantity.to_string());
+            }
+        }
+        crc32(parts.join(":").as_bytes())
+    }
+}
+
+/// IEEE CRC32, as used by exchange book checksums
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+fn apply_levels(side: &mut BTreeMap<Decimal, Decimal>, levels: &[BookLevel]) {
+    for level in levels {
+        if level.quantity.is_zero() {
+            side.remove(&level.price);
+        } else {
+            side.insert(level.price, level.quantity);
+        }
+    }
+}
+
+/// Parse `[["price", "qty"], ...]` as sent by Binance and Bybit
+pub(crate) fn parse_levels(raw: &[[String; 2]]) -> Result<Vec<BookLevel>> {
+    raw.iter()
+        .map(|[price, quantity]| {
+            Ok(BookLevel::new(
+                Decimal::from_str_exact(price).map_err(TradingError::Decimal)?,
+                Decimal::from_str_exact(quantity).map_err(TradingError::Decimal)?,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: i64, quantity: i64) -> BookLevel {
+        BookLevel::new(Decimal::from(price), Decimal::from(quantity))
+    }
+
+    fn seeded() -> OrderBook {
+        let mut book = OrderBook::new("BTCUSDT");
+        let snapshot = OrderBookUpdate::snapshot(
+            "BTCUSDT".to_string(),
+            100,
+            vec![level(99, 1), level(98, 2)],
+            vec![level(101, 1), level(102, 3)],
+        ).unwrap();
+        assert!(book.apply(&snapshot).unwrap());
+        book
+    }
+
+    #[test]
+    fn test_snapshot_and_delta() {
+        let mut book = seeded();
+        assert_eq!(book.best_bid(), Some(level(99, 1)));
+        assert_eq!(book.spread(), Some(Decimal::from(2)));
+
+        // Remove the best bid, add a better ask
+        let delta = OrderBookUpdate::delta(
+            "BTCUSDT".to_string(), 101, 102,
+            vec![level(99, 0)],
+            vec![level(100, 5)],
+        ).unwrap();
+        assert!(book.apply(&delta).unwrap());
+        assert_eq!(book.best_bid(), Some(level(98, 2)));
+        assert_eq!(book.best_ask(), Some(level(100, 5)));
+        assert_eq!(book.mid_price(), Some(Decimal::from(99)));
+        assert_eq!(book.depth(5).1.len(), 3);
+        assert_eq!(book.last_update_id(), Some(102));
+    }
+
+    #[test]
+    fn test_stale_deltas_ignored_and_gaps_detected() {
+        let mut book = seeded();
+        let stale = OrderBookUpdate::delta("BTCUSDT".to_string(), 90, 100, vec![level(50, 1)], vec![]).unwrap();
+        assert!(!book.apply(&stale).unwrap());
+        assert_eq!(book.bids().count(), 2);
+
+        let gapped = OrderBookUpdate::delta("BTCUSDT".to_string(), 105, 106, vec![], vec![]).unwrap();
+        let err = book.apply(&gapped).unwrap_err();
+        assert!(err.to_string().contains("expected update 101"));
+        assert!(book.needs_snapshot());
+
+        // Nothing applies until a new snapshot arrives
+        let next = OrderBookUpdate::delta("BTCUSDT".to_string(), 107, 107, vec![], vec![]).unwrap();
+        assert!(book.apply(&next).is_err());
+    }
+
+    #[test]
+    fn test_checksum_validation() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let book = seeded();
+        let mut delta = OrderBookUpdate::delta("BTCUSDT".to_string(), 101, 101, vec![level(97, 4)], vec![]).unwrap();
+        let mut expected = book.clone();
+        expected.apply(&delta).unwrap();
+        delta.checksum = Some(expected.checksum());
+
+        let mut good = book.clone();
+        assert!(good.apply(&delta).unwrap());
+
+        delta.checksum = Some(expected.checksum() ^ 1);
+        let mut bad = book;
+        assert!(bad.apply(&delta).unwrap_err().to_string().contains("checksum mismatch"));
+        assert!(bad.needs_snapshot());
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// Token bucket of request weight, refilled continuously over `window`.
+///
+/// Exchanges report the weight they have counted in response headers;
+/// `observe_remaining` pulls the bucket down to that figure so requests made
+/// elsewhere from the same IP are accounted for.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    window: Duration,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    /// Set from `Retry-After`; nothing is sent before this
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Allow `capacity` weight per `window`
+    pub fn new(capacity: u32, window: Duration) -> Self {
+        Self {
+            capacity: capacity as f64,
+            window,
+            state: Mutex::new(Bucket { tokens: capacity as f64, refilled_at: Instant::now(), paused_until: None }),
+        }
+    }
+
+    /// Binance spot: 6000 request weight per minute per IP
+    pub fn binance() -> Self {
+        Self::new(6000, Duration::from_secs(60))
+    }
+
+    /// Bybit: 600 requests per 5 seconds per IP
+    pub fn bybit() -> Self {
+        Self::new(600, Duration::from_secs(5))
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity as u32
+    }
+
+    /// Wait until `weight` is available and take it
+    pub async fn acquire(&self, weight: u32) {
+        loop {
+            let wait = match self.try_acquire(weight) {
+                Ok(()) => return,
+                Err(wait) => wait,
+            };
+            debug!(weight, wait_ms = wait.as_millis() as u64, "Rate limit reached, waiting");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take `weight` now, or return how long to wait before it is available
+    pub fn try_acquire(&self, weight: u32) -> std::result::Result<(), Duration> {
+        let now = Instant::now();
+        let mut bucket = self.lock();
+        if let Some(until) = bucket.paused_until {
+            if now < until {
+                return Err(until - now);
+            }
+            bucket.paused_until = None;
+        }
+        self.refill(&mut bucket, now);
+
+        // Requests heavier than the whole bucket go through once it is full
+        let weight = (weight as f64).min(self.capacity);
+        if bucket.tokens >= weight {
+            bucket.tokens -= weight;
+            return Ok(());
+        }
+        let missing = weight - bucket.tokens;
+        Err(self.window.mul_f64(missing / self.capacity).max(Duration::from_millis(1)))
+    }
+
+    /// Weight currently available
+    pub fn available(&self) -> u32 {
+        let mut bucket = self.lock();
+        self.refill(&mut bucket, Instant::now());
+        bucket.tokens as u32
+    }
+
+    /// The exchange reports `remaining` weight left in the current window
+    pub fn observe_remaining(&self, remaining: u32) {
+        let mut bucket = self.lock();
+        self.refill(&mut bucket, Instant::now());
+        bucket.tokens = bucket.tokens.min(remaining as f64);
+    }
+
+    /// The exchange reports `used` weight in the current window
+    pub fn observe_used(&self, used: u32) {
+        self.observe_remaining((self.capacity as u32).saturating_sub(used));
+    }
+
+    /// Send nothing for `duration`, e.g. after a 429 with `Retry-After`
+    pub fn pause_for(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut bucket = self.lock();
+        // A shorter pause never cuts a longer one short, nor refills during it
+        let until = bucket.paused_until.map_or(until, |current| current.max(until));
+        bucket.paused_until = Some(until);
+        bucket.tokens = 0.0;
+        bucket.refilled_at = until;
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        if now > bucket.refilled_at {
+            let elapsed = (now - bucket.refilled_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed / self.window.as_secs_f64() * self.capacity).min(self.capacity);
+            bucket.refilled_at = now;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_waits_for_refill() {
+        let limiter = RateLimiter::new(10, Duration::from_secs(10));
+        limiter.acquire(8).await;
+        assert_eq!(limiter.try_acquire(4), Err(Duration::from_secs(2)));
+
+        let start = Instant::now();
+        limiter.acquire(4).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+        assert_eq!(limiter.available(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_follows_exchange_headers() {
+        let limiter = RateLimiter::new(100, Duration::from_secs(10));
+        limiter.observe_used(95);
+        assert_eq!(limiter.available(), 5);
+        // Reports above what is left locally never add weight
+        limiter.observe_remaining(50);
+        assert_eq!(limiter.available(), 5);
+
+        limiter.pause_for(Duration::from_secs(3));
+        assert_eq!(limiter.try_acquire(1), Err(Duration::from_secs(3)));
+        tokio::time::advance(Duration::from_secs(4)).await;
+        assert_eq!(limiter.try_acquire(10), Ok(()));
+
+        limiter.pause_for(Duration::from_secs(10));
+        limiter.pause_for(Duration::from_secs(2));
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(limiter.available(), 0);
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use crate::engine::{Event, EventBus, EventEnvelope, EventKind, Subscription};
+use crate::error::{Result, TradingError};
+use super::event::PriceEvent;
+
+/// Events written by the recorder
+pub const RECORDED_KINDS: [EventKind; 3] = [
+    EventKind::PriceUpdated,
+    EventKind::OrderBookUpdated,
+    EventKind::TradePrinted,
+];
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub dir: PathBuf,
+    /// Start a new file once the current one reaches this compressed size
+    pub max_file_bytes: u64,
+    /// Start a new file once the current one has been open this long
+    pub max_file_age: Duration,
+    /// Buffered events per compressed write; a crash loses at most this many
+    pub flush_events: usize,
+    /// Events queued for the writer thread; further events are dropped while it is full
+    pub queue_capacity: usize,
+}
+
+impl RecorderConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_file_bytes: 64 * 1024 * 1024,
+            max_file_age: Duration::from_secs(3600),
+            flush_events: 500,
+            queue_capacity: 10_000,
+        }
+    }
+}
+
+/// Event-time span of one symbol within a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: u64,
+    pub end: u64,
+    pub events: u64,
+}
+
+/// One recorded file and what it contains
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordedFile {
+    /// File name relative to the recording directory
+    pub file: String,
+    pub events: u64,
+    pub bytes: u64,
+    pub symbols: BTreeMap<String, TimeRange>,
+}
+
+impl RecordedFile {
+    /// Whether the file holds events for `symbol` (any symbol if `None`) within `[start_ms, end_ms]`
+    pub fn overlaps(&self, symbol: Option<&str>, start_ms: u64, end_ms: u64) -> bool {
+        self.symbols
+            .iter()
+            .filter(|(name, _)| symbol.is_none_or(|symbol| symbol == name.as_str()))
+            .any(|(_, range)| range.start <= end_ms && range.end >= start_ms)
+    }
+}
+
+/// Files of a recording directory in write order, persisted as `index.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingIndex {
+    pub files: Vec<RecordedFile>,
+}
+
+impl RecordingIndex {
+    /// Load the index of `dir`; a missing index is an empty recording
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn store(&self, dir: &Path) -> Result<()> {
+        let tmp = dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, dir.join(INDEX_FILE))?;
+        Ok(())
+    }
+}
+
+/// Symbol and event time of a recordable event
+fn record_key(event: &Event) -> Option<(&str, u64)> {
+    match event {
+        Event::PriceUpdated(price) => Some((&price.symbol, price.timestamp)),
+        Event::OrderBookUpdated(update) => Some((&update.symbol, update.timestamp)),
+        Event::TradePrinted(trade) => Some((&trade.symbol, trade.timestamp)),
+        _ => None,
+    }
+}
+
+struct OpenFile {
+    file: File,
+    opened_at: Instant,
+    /// Position of this file in `index.files`
+    entry: usize,
+}
+
+struct Writer {
+    config: RecorderConfig,
+    index: RecordingIndex,
+    current: Option<OpenFile>,
+    pending: Vec<u8>,
+    pending_events: usize,
+}
+
+impl Writer {
+    /// Time left before the open file is due for rotation
+    fn rotation_due_in(&self) -> Option<Duration> {
+        let current = self.current.as_ref()?;
+        Some(self.config.max_file_age.saturating_sub(current.opened_at.elapsed()))
+    }
+
+    fn record(&mut self, envelope: &EventEnvelope) -> Result<()> {
+        let Some((symbol, timestamp)) = record_key(&envelope.event) else {
+            return Ok(());
+        };
+        if self.current.as_ref().is_some_and(|current| current.opened_at.elapsed() >= self.config.max_file_age) {
+            self.close()?;
+        }
+        let entry = match &self.current {
+            Some(current) => current.entry,
+            None => self.open()?,
+        };
+
+        serde_json::to_writer(&mut self.pending, envelope)?;
+        self.pending.push(b'\n');
+        self.pending_events += 1;
+
+        let file = &mut self.index.files[entry];
+        file.events += 1;
+        file.symbols
+            .entry(symbol.to_string())
+            .and_modify(|range| {
+                range.start = range.start.min(timestamp);
+                range.end = range.end.max(timestamp);
+                range.events += 1;
+            })
+            .or_insert(TimeRange { start: timestamp, end: timestamp, events: 1 });
+
+        if self.pending_events >= self.config.flush_events {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self) -> Result<usize> {
+        fs::create_dir_all(&self.config.dir)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let name = format!("market-data-{}-{:04}.jsonl.gz", now, self.index.files.len());
+        let file = OpenOptions::new().create(true).append(true).open(self.config.dir.join(&name))?;
+        info!(file = %name, "Recording market data");
+
+        self.index.files.push(RecordedFile { file: name, ..RecordedFile::default() });
+        let entry = self.index.files.len() - 1;
+        self.current = Some(OpenFile { file, opened_at: Instant::now(), entry });
+        Ok(entry)
+    }
+
+    /// Write buffered events as one gzip member and persist the index
+    fn flush(&mut self) -> Result<()> {
+        let Some(current) = self.current.as_mut() else {
+            return Ok(());
+        };
+        if self.pending_events > 0 {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&self.pending)?;
+            let member = encoder.finish()?;
+            current.file.write_all(&member)?;
+            current.file.flush()?;
+            self.index.files[current.entry].bytes += member.len() as u64;
+            debug!(events = self.pending_events, bytes = member.len(), "Flushed recorded market data");
+            self.pending.clear();
+            self.pending_events = 0;
+        }
+        self.index.store(&self.config.dir)?;
+
+        if self.index.files[current.entry].bytes >= self.config.max_file_bytes {
+            self.current = None;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.flush()?;
+        self.current = None;
+        Ok(())
+    }
+}
+
+enum Command {
+    Record(Box<EventEnvelope>),
+    Flush(Sender<Result<()>>),
+    Rotate(Sender<Result<()>>),
+    Index(Sender<RecordingIndex>),
+    Stop,
+}
+
+/// Writer thread: applies commands in order and closes files that reach
+/// `max_file_age` even when no further events arrive
+fn run_writer(mut writer: Writer, commands: Receiver<Command>) {
+    loop {
+        let command = match writer.rotation_due_in() {
+            Some(wait) => match commands.recv_timeout(wait) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+            None => match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => break,
+            },
+        };
+        match command {
+            None => {
+                debug!("Rotating recording file by age");
+                if let Err(e) = writer.close() {
+                    warn!(error = %e, "Failed to rotate recording file");
+                }
+            }
+            Some(Command::Record(envelope)) => {
+                if let Err(e) = writer.record(&envelope) {
+                    warn!(sequence = envelope.sequence, error = %e, "Failed to record market data");
+                }
+            }
+            Some(Command::Flush(reply)) => {
+                let _ = reply.send(writer.flush());
+            }
+            Some(Command::Rotate(reply)) => {
+                let _ = reply.send(writer.close());
+            }
+            Some(Command::Index(reply)) => {
+                let _ = reply.send(writer.index.clone());
+            }
+            Some(Command::Stop) => break,
+        }
+    }
+    if let Err(e) = writer.flush() {
+        warn!(error = %e, "Failed to flush market data recorder");
+    }
+}
+
+/// Writes price, book and trade events from a bus to rotating gzip files.
+/// Bus handlers only enqueue; compression and file IO run on a dedicated
+/// writer thread. Dropping it unsubscribes, drains the queue and flushes.
+pub struct MarketDataRecorder {
+    commands: SyncSender<Command>,
+    thread: Option<JoinHandle<()>>,
+    subscriptions: Vec<Subscription>,
+}
+
+impl MarketDataRecorder {
+    /// Start recording into `config.dir`, appending to any existing recording there
+    pub fn start(config: RecorderConfig, bus: &EventBus) -> Result<Self> {
+        if config.flush_events == 0 || config.max_file_bytes == 0 || config.queue_capacity == 0 {
+            return Err(TradingError::Config(
+                "Recorder flush size, file size and queue capacity must be positive".to_string(),
+            ));
+        }
+        let index = RecordingIndex::load(&config.dir)?;
+        let (commands, receiver) = mpsc::sync_channel(config.queue_capacity);
+        let writer = Writer {
+            config,
+            index,
+            current: None,
+            pending: Vec::new(),
+            pending_events: 0,
+        };
+        let thread = thread::Builder::new()
+            .name("market-data-recorder".to_string())
+            .spawn(move || run_writer(writer, receiver))?;
+
+        let mut subscriptions = Vec::with_capacity(RECORDED_KINDS.len());
+        for kind in RECORDED_KINDS {
+            let commands = commands.clone();
+            subscriptions.push(bus.subscribe(kind, move |envelope| {
+                if let Err(TrySendError::Full(_)) = commands.try_send(Command::Record(Box::new(envelope.clone()))) {
+                    warn!(sequence = envelope.sequence, "Recorder queue full, event not recorded");
+                }
+            })?);
+        }
+        Ok(Self { commands, thread: Some(thread), subscriptions })
+    }
+
+    fn request<T>(&self, command: impl FnOnce(Sender<T>) -> Command) -> Result<T> {
+        let stopped = || TradingError::MarketData("Recorder writer thread has stopped".to_string());
+        let (reply, response) = mpsc::channel();
+        self.commands.send(command(reply)).map_err(|_| stopped())?;
+        response.recv().map_err(|_| stopped())
+    }
+
+    /// Write every event queued so far
+    pub fn flush(&self) -> Result<()> {
+        self.request(Command::Flush)?
+    }
+
+    /// Close the current file; the next event starts a new one
+    pub fn rotate(&self) -> Result<()> {
+        self.request(Command::Rotate)?
+    }
+
+    /// Index including every event queued so far
+    pub fn index(&self) -> Result<RecordingIndex> {
+        self.request(Command::Index)
+    }
+}
+
+impl Drop for MarketDataRecorder {
+    fn drop(&mut self) {
+        self.subscriptions.clear();
+        let _ = self.commands.send(Command::Stop);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!("Market data recorder thread panicked");
+            }
+        }
+    }
+}
+
+/// Read access to a directory written by `MarketDataRecorder`
+pub struct Recording {
+    dir: PathBuf,
+    index: RecordingIndex,
+}
+
+impl Recording {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let index = RecordingIndex::load(&dir)?;
+        Ok(Self { dir, index })
+    }
+
+    pub fn index(&self) -> &RecordingIndex {
+        &self.index
+    }
+
+    /// Recorded envelopes for `symbol` (all if `None`) with event time in `[start_ms, end_ms]`,
+    /// in recording order. Only files whose index entry overlaps the range are read.
+    pub fn envelopes(&self, symbol: Option<&str>, start_ms: u64, end_ms: u64) -> Result<Vec<EventEnvelope>> {
+        let mut envelopes = Vec::new();
+        for file in self.index.files.iter().filter(|file| file.overlaps(symbol, start_ms, end_ms)) {
+            let reader = BufReader::new(MultiGzDecoder::new(File::open(self.dir.join(&file.file))?));
+            for line in reader.lines() {
+                let line = line.map_err(|e| TradingError::MarketData(format!("{}: {}", file.file, e)))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let envelope: EventEnvelope = serde_json::from_str(&line)?;
+                let keep = record_key(&envelope.event).is_some_and(|(name, timestamp)| {
+                    symbol.is_none_or(|symbol| symbol == name) && (start_ms..=end_ms).contains(&timestamp)
+                });
+                if keep {
+                    envelopes.push(envelope);
+                }
+            }
+        }
+        Ok(envelopes)
+    }
+
+    /// Recorded prices for `symbols` (all if empty), in event-time order
+    pub fn price_events(&self, symbols: &[String], start_ms: u64, end_ms: u64) -> Result<Vec<PriceEvent>> {
+        let mut prices: Vec<PriceEvent> = self
+            .envelopes(None, start_ms, end_ms)?
+            .into_iter()
+            .filter_map(|envelope| match envelope.event {
+                Event::PriceUpdated(price) if symbols.is_empty() || symbols.contains(&price.symbol) => Some(price),
+                _ => None,
+            })
+            .map(|mut price| {
+                price.sequence = None;
+                price
+            })
+            .collect();
+        prices.sort_by_key(|price| price.timestamp);
+        Ok(prices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use crate::market_data::trade::{AggressorSide, TradePrint};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("recorder-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn price(symbol: &str, price: i64, timestamp: u64) -> Event {
+        let event = PriceEvent::new(symbol.to_string(), Decimal::from(price), Decimal::ONE)
+            .unwrap()
+            .with_exchange_time(Some(timestamp));
+        Event::PriceUpdated(event)
+    }
+
+    #[test]
+    fn test_records_and_reads_back() {
+        let dir = temp_dir("roundtrip");
+        let bus = EventBus::new();
+        let config = RecorderConfig { flush_events: 2, ..RecorderConfig::new(&dir) };
+        let recorder = MarketDataRecorder::start(config, &bus).unwrap();
+
+        bus.publish(price("BTCUSDT", 100, 1_000)).unwrap();
+        bus.publish(price("ETHUSDT", 10, 1_500)).unwrap();
+        let trade = TradePrint::new("BTCUSDT".to_string(), 7, Decimal::from(101), Decimal::ONE, AggressorSide::Buy, 1_800).unwrap();
+        bus.publish(Event::TradePrinted(trade)).unwrap();
+        bus.publish(price("BTCUSDT", 102, 2_000)).unwrap();
+        drop(recorder);
+
+        let recording = Recording::open(&dir).unwrap();
+        let file = &recording.index().files[0];
+        assert_eq!(file.events, 4);
+        assert_eq!(file.symbols["BTCUSDT"], TimeRange { start: 1_000, end: 2_000, events: 3 });
+
+        let btc = recording.price_events(&["BTCUSDT".to_string()], 0, u64::MAX).unwrap();
+        let prices: Vec<Decimal> = btc.iter().map(|event| event.price).collect();
+        assert_eq!(prices, vec![Decimal::from(100), Decimal::from(102)]);
+        assert_eq!(recording.envelopes(Some("BTCUSDT"), 1_500, 1_900).unwrap().len(), 1);
+        assert_eq!(recording.price_events(&[], 0, u64::MAX).unwrap().len(), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_skips_files_by_index() {
+        let dir = temp_dir("rotate");
+        let bus = EventBus::new();
+        let config = RecorderConfig { flush_events: 1, max_file_bytes: 1, ..RecorderConfig::new(&dir) };
+        let recorder = MarketDataRecorder::start(config, &bus).unwrap();
+
+        bus.publish(price("BTCUSDT", 100, 1_000)).unwrap();
+        bus.publish(price("ETHUSDT", 10, 2_000)).unwrap();
+        recorder.rotate().unwrap();
+        assert_eq!(recorder.index().unwrap().files.len(), 2);
+        drop(recorder);
+
+        // Files outside the requested range are never opened
+        fs::remove_file(dir.join(&Recording::open(&dir).unwrap().index().files[1].file)).unwrap();
+        let recording = Recording::open(&dir).unwrap();
+        assert_eq!(recording.price_events(&[], 0, 1_500).unwrap().len(), 1);
+        assert!(recording.price_events(&[], 0, 2_500).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rotates_by_age_without_further_events() {
+        let dir = temp_dir("age");
+        let bus = EventBus::new();
+        let config = RecorderConfig { max_file_age: Duration::from_millis(50), ..RecorderConfig::new(&dir) };
+        let recorder = MarketDataRecorder::start(config, &bus).unwrap();
+
+        bus.publish(price("BTCUSDT", 100, 1_000)).unwrap();
+        // The writer closes and flushes the quiet file on its own
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(Recording::open(&dir).unwrap().price_events(&[], 0, u64::MAX).unwrap().len(), 1);
+
+        bus.publish(price("BTCUSDT", 101, 2_000)).unwrap();
+        assert_eq!(recorder.index().unwrap().files.len(), 2);
+        drop(recorder);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
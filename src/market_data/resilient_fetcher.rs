@@ -1,48 +1,630 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use async_trait::async_trait;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
 use crate::error::{Result, TradingError};
 use crate::engine::{EventBus, Event};
-use super::fetcher_trait::MarketDataFetcher;
+use super::fetcher_trait::{MarketDataFetcher, PriceBatch};
 use super::event::PriceEvent;
 
+/// How source health is scored and when sources are demoted
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Recent calls per source that the score is computed over
+    pub window: usize,
+    /// Calls needed before a source is scored; until then it scores 1.0
+    pub min_samples: usize,
+    /// Calls older than this drop out of the score, so a source skipped
+    /// for a bad spell gets another chance
+    pub sample_ttl: Duration,
+    /// Demote after this many failures in a row
+    pub max_consecutive_failures: u32,
+    /// Demote when the error rate over a scored window exceeds this
+    pub max_error_rate: f64,
+    /// Latency at which a source loses half its latency score
+    pub slow_latency: Duration,
+    /// Prices older than this (by exchange timestamp) count as stale
+    pub max_staleness: Duration,
+    /// How long a demoted source is skipped before it is probed again
+    pub cooldown: Duration,
+    /// Score lead a later source needs before it is tried ahead of an earlier one
+    pub switch_margin: f64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            min_samples: 5,
+            sample_ttl: Duration::from_secs(300),
+            max_consecutive_failures: 3,
+            max_error_rate: 0.5,
+            slow_latency: Duration::from_secs(2),
+            max_staleness: Duration::from_secs(30),
+            cooldown: Duration::from_secs(60),
+            switch_margin: 0.2,
+        }
+    }
+}
+
+/// One call; a batch call is scored by the share of its symbols that failed
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    /// At least one price was served
+    ok: bool,
+    /// Share of symbols that failed, 0.0 to 1.0
+    errors: f64,
+    /// Share of served prices that were stale
+    stale: f64,
+    latency: Duration,
+}
+
+#[derive(Debug, Default)]
+struct SourceHealth {
+    samples: VecDeque<Sample>,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_success: Option<Instant>,
+    demoted_until: Option<Instant>,
+}
+
+impl SourceHealth {
+    fn error_rate(&self) -> f64 {
+        mean(&self.samples, |sample| sample.errors)
+    }
+
+    fn stale_rate(&self) -> f64 {
+        mean(&self.samples, |sample| sample.stale)
+    }
+
+    fn avg_latency(&self) -> Duration {
+        let ok: Vec<Duration> = self.samples.iter().filter(|s| s.ok).map(|s| s.latency).collect();
+        if ok.is_empty() {
+            return Duration::ZERO;
+        }
+        ok.iter().sum::<Duration>() / ok.len() as u32
+    }
+
+    fn expire(&mut self, now: Instant, ttl: Duration) {
+        while self.samples.front().is_some_and(|sample| now.duration_since(sample.at) > ttl) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// 1.0 for a fast, fresh, error-free source; sources with too few calls score 1.0
+    fn score(&self, config: &HealthConfig) -> f64 {
+        if self.samples.len() < config.min_samples {
+            return 1.0;
+        }
+        let latency = self.avg_latency().as_secs_f64() / config.slow_latency.as_secs_f64().max(f64::EPSILON);
+        (1.0 - self.error_rate()) * (1.0 - 0.5 * latency.min(1.0)) * (1.0 - 0.5 * self.stale_rate())
+    }
+}
+
+fn mean(samples: &VecDeque<Sample>, value: impl Fn(&Sample) -> f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(value).sum::<f64>() / samples.len() as f64
+}
+
+/// Point-in-time health of one source
+#[derive(Debug, Clone)]
+pub struct FeedHealth {
+    pub name: String,
+    pub score: f64,
+    pub error_rate: f64,
+    pub avg_latency: Duration,
+    /// Time since the last successful call
+    pub since_success: Option<Duration>,
+    pub last_error: Option<String>,
+    pub demoted: bool,
+}
+
+struct Source {
+    fetcher: Box<dyn MarketDataFetcher>,
+    health: Mutex<SourceHealth>,
+}
+
+impl Source {
+    fn health(&self) -> MutexGuard<'_, SourceHealth> {
+        self.health.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Fails over across any number of fetchers, routing each call to the
+/// healthiest source first. Sources that keep failing are demoted for a
+/// cooldown, then probed once and promoted again if they answer.
 pub struct ResilientFetcher {
-    primary: Box<dyn MarketDataFetcher>,
-    secondary: Box<dyn MarketDataFetcher>,
+    sources: Vec<Source>,
+    config: HealthConfig,
     event_bus: EventBus,
 }
 
 impl ResilientFetcher {
+    /// Two-source failover with default health settings
     pub fn new(
         primary: Box<dyn MarketDataFetcher>,
         secondary: Box<dyn MarketDataFetcher>,
         event_bus: EventBus,
-    ) -> Self {
-        Self {
-            primary,
-            secondary,
+    ) -> Result<Self> {
+        Self::with_sources(vec![primary, secondary], HealthConfig::default(), event_bus)
+    }
+
+    /// Sources in priority order; the first is preferred until another is clearly healthier
+    pub fn with_sources(
+        sources: Vec<Box<dyn MarketDataFetcher>>,
+        config: HealthConfig,
+        event_bus: EventBus,
+    ) -> Result<Self> {
+        if sources.is_empty() {
+            return Err(TradingError::Config("ResilientFetcher needs at least one source".to_string()));
+        }
+        if config.window == 0 {
+            return Err(TradingError::Config("Health window must be positive".to_string()));
+        }
+        Ok(Self {
+            sources: sources
+                .into_iter()
+                .map(|fetcher| Source { fetcher, health: Mutex::new(SourceHealth::default()) })
+                .collect(),
+            config,
             event_bus,
+        })
+    }
+
+    pub fn health(&self) -> Vec<FeedHealth> {
+        let now = Instant::now();
+        self.sources
+            .iter()
+            .map(|source| {
+                let mut health = source.health();
+                health.expire(now, self.config.sample_ttl);
+                FeedHealth {
+                    name: source.fetcher.exchange_name().to_string(),
+                    score: health.score(&self.config),
+                    error_rate: health.error_rate(),
+                    avg_latency: health.avg_latency(),
+                    since_success: health.last_success.map(|at| now - at),
+                    last_error: health.last_error.clone(),
+                    demoted: health.demoted_until.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Name of the source currently tried first
+    pub fn preferred(&self) -> &str {
+        let index = self.route(Instant::now())[0];
+        self.sources[index].fetcher.exchange_name()
+    }
+
+    /// Call order: demoted sources due for a probe, then healthy sources
+    /// (the earliest configured one within `switch_margin` of the best score
+    /// first, the rest by score), then sources still cooling down
+    fn route(&self, now: Instant) -> Vec<usize> {
+        let mut probes = Vec::new();
+        let mut healthy = Vec::new();
+        let mut cooling = Vec::new();
+        for (index, source) in self.sources.iter().enumerate() {
+            let mut health = source.health();
+            health.expire(now, self.config.sample_ttl);
+            match health.demoted_until {
+                Some(until) if now >= until => probes.push(index),
+                Some(_) => cooling.push(index),
+                None => healthy.push((index, health.score(&self.config))),
+            }
+        }
+
+        let best = healthy.iter().map(|(_, score)| *score).fold(f64::MIN, f64::max);
+        let lead = healthy.iter().position(|(_, score)| *score >= best - self.config.switch_margin);
+        let lead = lead.map(|position| healthy.remove(position));
+        healthy.sort_by(|(a_index, a), (b_index, b)| b.total_cmp(a).then(a_index.cmp(b_index)));
+
+        probes
+            .into_iter()
+            .chain(lead.map(|(index, _)| index))
+            .chain(healthy.into_iter().map(|(index, _)| index))
+            .chain(cooling)
+            .collect()
+    }
+
+    /// Score one call from the results of every symbol it covered
+    fn record(&self, index: usize, results: &[std::result::Result<&PriceEvent, &TradingError>], latency: Duration, now: Instant) {
+        if results.is_empty() {
+            return;
+        }
+        let source = &self.sources[index];
+        let name = source.fetcher.exchange_name();
+        let mut health = source.health();
+        let was_demoted = health.demoted_until.is_some();
+
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        let max_age = self.config.max_staleness.as_millis() as u64;
+        let served: Vec<&PriceEvent> = results.iter().filter_map(|result| result.ok()).collect();
+        let stale = served.iter().filter(|event| event.age_ms(now_ms) > max_age).count();
+        let sample = Sample {
+            at: now,
+            ok: !served.is_empty(),
+            errors: (results.len() - served.len()) as f64 / results.len() as f64,
+            stale: if served.is_empty() { 0.0 } else { stale as f64 / served.len() as f64 },
+            latency,
+        };
+        if let Some(Err(e)) = results.iter().find(|result| result.is_err()) {
+            health.last_error = Some(e.to_string());
+        }
+        if sample.ok {
+            health.consecutive_failures = 0;
+            health.last_success = Some(now);
+        } else {
+            health.consecutive_failures += 1;
         }
+        if health.samples.len() == self.config.window {
+            health.samples.pop_front();
+        }
+        health.samples.push_back(sample);
+
+        let change = if sample.ok && was_demoted {
+            // Fresh start so old errors don't demote it again straight away
+            health.demoted_until = None;
+            health.samples.clear();
+            health.samples.push_back(sample);
+            Some((true, "probe succeeded".to_string()))
+        } else if was_demoted {
+            health.demoted_until = Some(now + self.config.cooldown);
+            None
+        } else if sample.errors > 0.0 {
+            let scored = health.samples.len() >= self.config.min_samples;
+            let reason = if health.consecutive_failures >= self.config.max_consecutive_failures {
+                Some(format!("{} consecutive failures", health.consecutive_failures))
+            } else if scored && health.error_rate() > self.config.max_error_rate {
+                Some(format!("error rate {:.0}%", health.error_rate() * 100.0))
+            } else {
+                None
+            };
+            reason.map(|reason| {
+                health.demoted_until = Some(now + self.config.cooldown);
+                let last = health.last_error.clone().unwrap_or_default();
+                (false, format!("{}, last: {}", reason, last))
+            })
+        } else {
+            None
+        };
+        drop(health);
+
+        if let Some((healthy, reason)) = change {
+            if healthy {
+                info!(feed = name, reason = %reason, "Feed promoted");
+            } else {
+                warn!(feed = name, reason = %reason, cooldown_s = self.config.cooldown.as_secs(), "Feed demoted");
+            }
+            let _ = self.event_bus.publish_from(
+                self.exchange_name(),
+                Event::FeedHealthChanged { feed: name.to_string(), healthy, reason },
+                None,
+            );
+        }
+    }
+
+    /// Publish `Event::FeedFailover` for `symbol` if any source failed it;
+    /// `to` is the source that finally served it
+    fn failover(&self, symbol: &str, failures: &[(&str, String)], to: Option<&str>) {
+        let Some(&(from, _)) = failures.first() else {
+            return;
+        };
+        let errors = failure_chain(failures);
+        match to {
+            Some(to) => warn!(symbol, from, to, errors = ?errors, "Failed over to next feed"),
+            None => error!(symbol, errors = ?errors, "All feeds failed"),
+        }
+        let _ = self.event_bus.publish_from(
+            self.exchange_name(),
+            Event::FeedFailover {
+                symbol: symbol.to_string(),
+                from: from.to_string(),
+                to: to.map(str::to_string),
+                errors,
+            },
+            None,
+        );
     }
 }
 
+fn failure_chain(failures: &[(&str, String)]) -> Vec<String> {
+    failures.iter().map(|(name, error)| format!("{}: {}", name, error)).collect()
+}
+
+fn all_failed(symbol: &str, failures: &[(&str, String)]) -> TradingError {
+    TradingError::MarketData(format!("All feeds failed for {}: {}", symbol, failure_chain(failures).join("; ")))
+}
+
 #[async_trait]
 impl MarketDataFetcher for ResilientFetcher {
     async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
-        match self.primary.fetch_price(symbol).await {
-            Ok(event) => Ok(event),
-            Err(primary_err) => {
-                let msg = format!("Primary feed failed: {}", primary_err);
-                let _ = self.event_bus.publish(Event::Error(msg));
+        let mut failures: Vec<(&str, String)> = Vec::new();
 
-                self.secondary.fetch_price(symbol).await.map_err(|secondary_err| {
-                    TradingError::MarketData(format!(
-                        "Secondary feed failed: {}", secondary_err
-                    ))
-                })
+        for index in self.route(Instant::now()) {
+            let source = &self.sources[index];
+            let name = source.fetcher.exchange_name();
+            let started = Instant::now();
+            let result = source.fetcher.fetch_price(symbol).await;
+            self.record(index, &[result.as_ref()], started.elapsed(), Instant::now());
+
+            match result {
+                Ok(event) => {
+                    self.failover(symbol, &failures, Some(name));
+                    return Ok(event);
+                }
+                Err(e) => {
+                    warn!(symbol, exchange = name, error = %e, "Feed failed");
+                    failures.push((name, e.to_string()));
+                }
             }
         }
+
+        self.failover(symbol, &failures, None);
+        Err(all_failed(symbol, &failures))
+    }
+
+    /// Each source gets the symbols the sources before it could not serve
+    async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+        let mut pending: Vec<String> = symbols.to_vec();
+        let mut served: HashMap<String, (&str, PriceEvent)> = HashMap::new();
+        let mut failures: HashMap<String, Vec<(&str, String)>> = HashMap::new();
+
+        for index in self.route(Instant::now()) {
+            if pending.is_empty() {
+                break;
+            }
+            let source = &self.sources[index];
+            let name = source.fetcher.exchange_name();
+            let started = Instant::now();
+            let result = source.fetcher.fetch_prices(&pending).await;
+            let latency = started.elapsed();
+
+            let batch = match result {
+                Ok(batch) => batch,
+                Err(e) => {
+                    warn!(exchange = name, count = pending.len(), error = %e, "Feed failed");
+                    self.record(index, &[Err(&e)], latency, Instant::now());
+                    for symbol in &pending {
+                        failures.entry(symbol.clone()).or_default().push((name, e.to_string()));
+                    }
+                    continue;
+                }
+            };
+
+            // A requested symbol the source left out of its batch counts as failed there
+            let mut returned: HashMap<String, Result<PriceEvent>> = batch.into_iter().collect();
+            let missing = TradingError::MarketData("no quote returned".to_string());
+            let results: Vec<_> = pending
+                .iter()
+                .map(|symbol| returned.get(symbol).map_or(Err(&missing), |result| result.as_ref()))
+                .collect();
+            self.record(index, &results, latency, Instant::now());
+            for symbol in std::mem::take(&mut pending) {
+                match returned.remove(&symbol) {
+                    Some(Ok(event)) => {
+                        served.insert(symbol, (name, event));
+                    }
+                    Some(Err(e)) => {
+                        failures.entry(symbol.clone()).or_default().push((name, e.to_string()));
+                        pending.push(symbol);
+                    }
+                    None => {
+                        failures.entry(symbol.clone()).or_default().push((name, missing.to_string()));
+                        pending.push(symbol);
+                    }
+                }
+            }
+        }
+
+        Ok(symbols
+            .iter()
+            .map(|symbol| {
+                let failed = failures.remove(symbol).unwrap_or_default();
+                let to = served.get(symbol).map(|(name, _)| *name);
+                self.failover(symbol, &failed, to);
+                let result = match served.remove(symbol) {
+                    Some((_, event)) => Ok(event),
+                    None => Err(all_failed(symbol, &failed)),
+                };
+                (symbol.clone(), result)
+            })
+            .collect())
     }
 
     fn exchange_name(&self) -> &str {
         "ResilientFetcher"
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use rust_decimal::Decimal;
+    use crate::engine::EventKind;
+
+    /// Fails while `down` is set, answers after `delay` and counts calls
+    struct Switchable {
+        name: &'static str,
+        delay: Duration,
+        down: Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    fn source(name: &'static str, down: bool) -> (Box<dyn MarketDataFetcher>, Arc<AtomicBool>, Arc<AtomicUsize>) {
+        slow_source(name, down, Duration::ZERO)
+    }
+
+    fn slow_source(
+        name: &'static str,
+        down: bool,
+        delay: Duration,
+    ) -> (Box<dyn MarketDataFetcher>, Arc<AtomicBool>, Arc<AtomicUsize>) {
+        let down = Arc::new(AtomicBool::new(down));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let fetcher = Switchable { name, delay, down: Arc::clone(&down), calls: Arc::clone(&calls) };
+        (Box::new(fetcher), down, calls)
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for Switchable {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.down.load(Ordering::SeqCst) {
+                return Err(TradingError::Http(503, format!("{} down", self.name)));
+            }
+            PriceEvent::new(symbol.to_string(), Decimal::ONE, Decimal::ONE)
+        }
+
+        fn exchange_name(&self) -> &str {
+            self.name
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_demotes_failing_source_and_promotes_after_cooldown() {
+        let bus = EventBus::new();
+        let (a, a_down, a_calls) = source("A", true);
+        let (b, _, b_calls) = source("B", false);
+        let (c, _, _) = source("C", false);
+        let fetcher = ResilientFetcher::with_sources(vec![a, b, c], HealthConfig::default(), bus.clone()).unwrap();
+
+        for _ in 0..3 {
+            fetcher.fetch_price("BTCUSDT").await.unwrap();
+        }
+        assert!(fetcher.health()[0].demoted);
+        assert_eq!(fetcher.preferred(), "B");
+
+        // A is skipped during its cooldown
+        for _ in 0..5 {
+            fetcher.fetch_price("BTCUSDT").await.unwrap();
+        }
+        assert_eq!(a_calls.load(Ordering::SeqCst), 3);
+        assert_eq!(b_calls.load(Ordering::SeqCst), 8);
+
+        // After the cooldown A is probed once and promoted when it answers
+        a_down.store(false, Ordering::SeqCst);
+        tokio::time::advance(Duration::from_secs(61)).await;
+        fetcher.fetch_price("BTCUSDT").await.unwrap();
+        assert_eq!(a_calls.load(Ordering::SeqCst), 4);
+        assert!(!fetcher.health()[0].demoted);
+
+        let counts = bus.metrics_snapshot();
+        assert_eq!(counts.get(EventKind::FeedFailover.as_str()), Some(&3));
+        assert_eq!(counts.get(EventKind::FeedHealthChanged.as_str()), Some(&2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_routes_around_slow_source_until_samples_expire() {
+        let bus = EventBus::new();
+        let (a, _, a_calls) = slow_source("A", false, Duration::from_secs(2));
+        let (b, _, b_calls) = source("B", false);
+        let fetcher = ResilientFetcher::with_sources(vec![a, b], HealthConfig::default(), bus).unwrap();
+
+        // A keeps priority until it has enough calls to be scored
+        for _ in 0..7 {
+            fetcher.fetch_price("BTCUSDT").await.unwrap();
+        }
+        assert_eq!(a_calls.load(Ordering::SeqCst), 5);
+        assert_eq!(b_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fetcher.preferred(), "B");
+        assert!((fetcher.health()[0].score - 0.5).abs() < 1e-9);
+
+        tokio::time::advance(Duration::from_secs(301)).await;
+        assert_eq!(fetcher.preferred(), "A");
+    }
+
+    #[tokio::test]
+    async fn test_reports_every_error_when_all_fail() {
+        let bus = EventBus::new();
+        let (a, _, _) = source("A", true);
+        let (b, _, _) = source("B", true);
+        let fetcher = ResilientFetcher::new(a, b, bus.clone()).unwrap();
+
+        let err = fetcher.fetch_price("BTCUSDT").await.unwrap_err().to_string();
+        assert!(err.contains("A: HTTP error 503: A down"), "{}", err);
+        assert!(err.contains("B: HTTP error 503: B down"), "{}", err);
+        assert!(ResilientFetcher::with_sources(vec![], HealthConfig::default(), bus).is_err());
+    }
+
+    /// Serves every symbol except those listed
+    struct Partial {
+        failing: &'static [&'static str],
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for Partial {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            if self.failing.contains(&symbol) {
+                return Err(TradingError::Http(400, format!("Invalid symbol {}", symbol)));
+            }
+            PriceEvent::new(symbol.to_string(), Decimal::ONE, Decimal::ONE)
+        }
+
+        fn exchange_name(&self) -> &str {
+            "Partial"
+        }
+    }
+
+    /// Batch source that leaves `omitted` out of its answer
+    struct Omitting {
+        omitted: &'static str,
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for Omitting {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            PriceEvent::new(symbol.to_string(), Decimal::ONE, Decimal::ONE)
+        }
+
+        async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+            let mut batch = Vec::new();
+            for symbol in symbols.iter().filter(|symbol| *symbol != self.omitted) {
+                batch.push((symbol.clone(), self.fetch_price(symbol).await));
+            }
+            Ok(batch)
+        }
+
+        fn exchange_name(&self) -> &str {
+            "Omitting"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_omitted_symbols_move_to_next_source() {
+        let (b, _, b_calls) = source("B", false);
+        let fetcher = ResilientFetcher::new(Box::new(Omitting { omitted: "C" }), b, EventBus::new()).unwrap();
+        let symbols: Vec<String> = ["A", "C"].iter().map(|s| s.to_string()).collect();
+
+        let batch = fetcher.fetch_prices(&symbols).await.unwrap();
+        assert!(batch.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(b_calls.load(Ordering::SeqCst), 1);
+        assert!((fetcher.health()[0].error_rate - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_scores_batches_by_failed_share() {
+        let partial: Box<dyn MarketDataFetcher> = Box::new(Partial { failing: &["C", "D", "E"] });
+        let config = HealthConfig { min_samples: 2, ..HealthConfig::default() };
+        let fetcher = ResilientFetcher::with_sources(vec![partial], config, EventBus::new()).unwrap();
+        let symbols = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let batch = fetcher.fetch_prices(&symbols(&["A", "B", "C", "D"])).await.unwrap();
+        assert_eq!(batch.iter().filter(|(_, result)| result.is_ok()).count(), 2);
+        let health = &fetcher.health()[0];
+        assert!((health.error_rate - 0.5).abs() < 1e-9);
+        assert!(!health.demoted);
+
+        // Three of four failing pushes the error rate past the 50% threshold
+        fetcher.fetch_prices(&symbols(&["A", "C", "D", "E"])).await.unwrap();
+        assert!(fetcher.health()[0].demoted);
+    }
+}
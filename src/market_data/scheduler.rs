@@ -0,0 +1,175 @@
+use std::time::Duration;
+use futures::future::join_all;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{debug, warn};
+use crate::error::{Result, TradingError};
+use super::fetcher_trait::{MarketDataFetcher, PriceBatch};
+
+/// Cadence and batching of price polls
+#[derive(Debug, Clone)]
+pub struct PollingConfig {
+    /// Time between two polls of the universe
+    pub interval: Duration,
+    /// Most symbols sent in one `fetch_prices` call
+    pub max_batch: usize,
+}
+
+impl PollingConfig {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, ..Self::default() }
+    }
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            max_batch: 100,
+        }
+    }
+}
+
+/// Outcome of one poll over the universe
+#[derive(Debug, Default)]
+pub struct PollRound {
+    /// Per-symbol results of the batches that went through, in universe order
+    pub prices: PriceBatch,
+    /// Batches that failed as a whole, with the symbols they covered
+    pub failed_batches: Vec<(Vec<String>, TradingError)>,
+}
+
+/// Polls a universe of symbols at a fixed cadence, in as few requests as the
+/// fetcher allows
+pub struct PollingScheduler {
+    config: PollingConfig,
+    universe: Vec<String>,
+    /// Created on the first `wait`, which needs a runtime
+    interval: Option<Interval>,
+}
+
+impl PollingScheduler {
+    pub fn new(config: PollingConfig, universe: Vec<String>) -> Result<Self> {
+        if config.interval.is_zero() {
+            return Err(TradingError::Config("Polling interval must be greater than 0".to_string()));
+        }
+        if config.max_batch == 0 {
+            return Err(TradingError::Config("Polling batch size must be greater than 0".to_string()));
+        }
+        let mut scheduler = Self { config, universe: Vec::new(), interval: None };
+        scheduler.set_universe(universe);
+        Ok(scheduler)
+    }
+
+    pub fn universe(&self) -> &[String] {
+        &self.universe
+    }
+
+    /// Replace the polled symbols; duplicates are dropped, order is kept
+    pub fn set_universe(&mut self, universe: Vec<String>) {
+        self.universe.clear();
+        for symbol in universe {
+            self.add_symbol(symbol);
+        }
+    }
+
+    pub fn add_symbol(&mut self, symbol: impl Into<String>) {
+        let symbol = symbol.into();
+        if !self.universe.contains(&symbol) {
+            self.universe.push(symbol);
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.config.interval
+    }
+
+    /// Wait for the next poll slot. The first returns at once; a slow poll
+    /// delays the schedule rather than bunching the polls that follow.
+    pub async fn wait(&mut self) {
+        let period = self.config.interval;
+        let interval = self.interval.get_or_insert_with(|| {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+        interval.tick().await;
+    }
+
+    /// Fetch the whole universe, batches concurrently
+    pub async fn poll(&self, fetcher: &dyn MarketDataFetcher) -> PollRound {
+        let batches: Vec<&[String]> = self.universe.chunks(self.config.max_batch).collect();
+        let results = join_all(batches.iter().map(|batch| fetcher.fetch_prices(batch))).await;
+
+        let mut round = PollRound::default();
+        for (batch, result) in batches.into_iter().zip(results) {
+            match result {
+                Ok(prices) => round.prices.extend(prices),
+                Err(e) => {
+                    warn!(exchange = fetcher.exchange_name(), count = batch.len(), error = %e, "Batch poll failed");
+                    round.failed_batches.push((batch.to_vec(), e));
+                }
+            }
+        }
+        debug!(symbols = self.universe.len(), prices = round.prices.len(), "Polled universe");
+        round
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use async_trait::async_trait;
+    use rust_decimal::Decimal;
+    use crate::market_data::event::PriceEvent;
+
+    /// Records batch sizes; batches containing "DOWN" fail as a whole
+    struct Batching {
+        batches: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl MarketDataFetcher for Batching {
+        async fn fetch_price(&self, symbol: &str) -> Result<PriceEvent> {
+            if symbol == "BAD" {
+                return Err(TradingError::MarketData("unknown symbol".to_string()));
+            }
+            PriceEvent::new(symbol.to_string(), Decimal::ONE, Decimal::ONE)
+        }
+
+        async fn fetch_prices(&self, symbols: &[String]) -> Result<PriceBatch> {
+            self.batches.lock().unwrap().push(symbols.len());
+            if symbols.iter().any(|symbol| symbol == "DOWN") {
+                return Err(TradingError::Timeout("batch".to_string()));
+            }
+            let mut results = Vec::new();
+            for symbol in symbols {
+                results.push((symbol.clone(), self.fetch_price(symbol).await));
+            }
+            Ok(results)
+        }
+
+        fn exchange_name(&self) -> &str {
+            "Batching"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polls_universe_in_batches() {
+        let fetcher = Batching { batches: Mutex::new(Vec::new()) };
+        let universe = ["A", "B", "BAD", "A", "C", "DOWN"].iter().map(|s| s.to_string()).collect();
+        let config = PollingConfig { max_batch: 2, ..PollingConfig::default() };
+        let scheduler = PollingScheduler::new(config, universe).unwrap();
+        assert_eq!(scheduler.universe(), ["A", "B", "BAD", "C", "DOWN"]);
+
+        let round = scheduler.poll(&fetcher).await;
+        assert_eq!(*fetcher.batches.lock().unwrap(), vec![2, 2, 1]);
+        let ok: Vec<&str> = round.prices.iter().filter(|(_, r)| r.is_ok()).map(|(s, _)| s.as_str()).collect();
+        assert_eq!(ok, ["A", "B", "C"]);
+        assert!(round.prices.iter().any(|(symbol, result)| symbol == "BAD" && result.is_err()));
+        assert_eq!(round.failed_batches.len(), 1);
+        assert_eq!(round.failed_batches[0].0, ["DOWN"]);
+
+        assert!(PollingScheduler::new(PollingConfig::new(Duration::ZERO), Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+use std::pin::Pin;
+use futures::Stream;
+use crate::error::Result;
+use super::event::PriceEvent;
+
+/// Price updates pushed by a streaming connection
+pub type PriceStream = Pin<Box<dyn Stream<Item = Result<PriceEvent>> + Send>>;
+
+/// Streaming counterpart of `MarketDataFetcher`
+pub trait MarketDataStream: Send + Sync {
+    /// Subscribe to `symbols` and yield their price updates.
+    /// The connection is kept alive (and re-established) until the stream is dropped;
+    /// it ends with an error once reconnection attempts are exhausted.
+    fn subscribe(&self, symbols: &[String]) -> Result<PriceStream>;
+    fn exchange_name(&self) -> &str;
+}
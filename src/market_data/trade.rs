@@ -0,0 +1,44 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::error::Result;
+
+/// Side of the taker that removed liquidity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggressorSide {
+    Buy,
+    Sell,
+}
+
+/// One executed trade from an exchange's public tape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradePrint {
+    pub symbol: String,
+    /// Exchange trade id; consecutive per symbol on Binance, increasing on Bybit
+    pub trade_id: u64,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub side: AggressorSide,
+    /// Exchange execution time (ms)
+    pub timestamp: u64,
+    /// Local wall-clock time (ms) the print was received
+    pub received_at: u64,
+}
+
+impl TradePrint {
+    pub fn new(
+        symbol: String,
+        trade_id: u64,
+        price: Decimal,
+        quantity: Decimal,
+        side: AggressorSide,
+        timestamp: u64,
+    ) -> Result<Self> {
+        let received_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        Ok(Self { symbol, trade_id, price, quantity, side, timestamp, received_at })
+    }
+
+    pub fn notional(&self) -> Decimal {
+        self.price * self.quantity
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use tokio::time::{Instant, MissedTickBehavior};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+use crate::engine::{Event, EventBus};
+use crate::error::{Result, TradingError};
+use super::event::PriceEvent;
+use super::monitor::{DataQualityConfig, PriceMonitor};
+use super::normalizer::PriceValidator;
+use super::stream_trait::PriceStream;
+
+/// Events buffered between the socket task and the consumer
+const STREAM_BUFFER: usize = 1024;
+
+/// Connection settings shared by the WebSocket streams
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// WebSocket endpoint, e.g. "wss://stream.binance.com:9443/ws"
+    pub url: String,
+    /// How often a ping is sent to keep the connection alive
+    pub heartbeat_interval: Duration,
+    /// Reconnect when nothing has been received for this long
+    pub idle_timeout: Duration,
+    /// First reconnect delay; doubled after every failed attempt
+    pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+    /// Give up after this many consecutive failed attempts (None = retry forever)
+    pub max_reconnect_attempts: Option<u32>,
+    /// Checks every streamed price passes before it is published
+    pub data_quality: DataQualityConfig,
+}
+
+impl StreamConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            heartbeat_interval: Duration::from_secs(20),
+            idle_timeout: Duration::from_secs(60),
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+            max_reconnect_attempts: None,
+            data_quality: DataQualityConfig::default(),
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.reconnect_delay.saturating_mul(factor).min(self.max_reconnect_delay)
+    }
+}
+
+/// Venue-specific parts of a WebSocket price stream
+pub(crate) trait StreamProtocol: Send + Sync + 'static {
+    fn exchange_name(&self) -> &str;
+
+    /// Frames sent after every (re)connect to subscribe to `symbols`
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String>;
+
+    /// Application-level ping; None sends a WebSocket ping frame instead
+    fn ping_message(&self) -> Option<String> {
+        None
+    }
+
+    /// Parse a text frame; Ok(None) for acknowledgements, pongs and other control traffic
+    fn parse(&self, text: &str) -> Result<Option<PriceEvent>>;
+}
+
+/// Spawn the connection task for `protocol` and return the consumer side
+pub(crate) fn spawn_stream<P: StreamProtocol>(
+    protocol: P,
+    config: StreamConfig,
+    event_bus: EventBus,
+    symbols: &[String],
+) -> Result<PriceStream> {
+    if symbols.is_empty() {
+        return Err(TradingError::Config("Stream needs at least one symbol".to_string()));
+    }
+    let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+        TradingError::MarketData("Market data streams require a running tokio runtime".to_string())
+    })?;
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    let driver = StreamDriver {
+        protocol: Arc::new(protocol),
+        monitor: Mutex::new(PriceMonitor::new(config.data_quality.clone(), event_bus.clone())),
+        config,
+        event_bus,
+        symbols: symbols.to_vec(),
+        tx,
+    };
+    runtime.spawn(driver.run());
+
+    Ok(Box::pin(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    })))
+}
+
+/// Why a session ended
+enum SessionEnd {
+    /// The consumer dropped the stream
+    Closed,
+    /// Connection lost; `received` tells whether the session delivered anything
+    Lost { reason: String, received: bool },
+}
+
+struct StreamDriver<P> {
+    protocol: Arc<P>,
+    /// Kept across reconnects so checks continue from the last accepted price
+    monitor: Mutex<PriceMonitor>,
+    config: StreamConfig,
+    event_bus: EventBus,
+    symbols: Vec<String>,
+    tx: mpsc::Sender<Result<PriceEvent>>,
+}
+
+impl<P: StreamProtocol> StreamDriver<P> {
+    async fn run(self) {
+        let exchange = self.protocol.exchange_name().to_string();
+        let mut attempt = 0u32;
+
+        loop {
+            let (reason, received) = match self.session().await {
+                SessionEnd::Closed => return,
+                SessionEnd::Lost { reason, received } => (reason, received),
+            };
+
+            attempt = if received { 1 } else { attempt + 1 };
+            warn!(exchange = %exchange, attempt, reason = %reason, "Stream disconnected");
+            let _ = self.event_bus.publish_from(
+                &exchange,
+                Event::Error(format!("Stream disconnected: {}", reason)),
+                None,
+            );
+
+            if self.config.max_reconnect_attempts.is_some_and(|max| attempt > max) {
+                let _ = self.tx.send(Err(TradingError::MarketData(format!(
+                    "{} stream gave up after {} reconnect attempts: {}",
+                    exchange, attempt - 1, reason
+                )))).await;
+                return;
+            }
+
+            tokio::select! {
+                _ = self.tx.closed() => return,
+                _ = tokio::time::sleep(self.config.backoff(attempt)) => {}
+            }
+        }
+    }
+
+    async fn session(&self) -> SessionEnd {
+        let lost = |reason: String, received: bool| SessionEnd::Lost { reason, received };
+
+        let (mut socket, _) = match connect_async(self.config.url.as_str()).await {
+            Ok(conn) => conn,
+            Err(err) => return lost(format!("connect failed: {}", err), false),
+        };
+        info!(exchange = self.protocol.exchange_name(), url = %self.config.url, symbols = ?self.symbols, "Stream connected");
+
+        for message in self.protocol.subscribe_messages(&self.symbols) {
+            if let Err(err) = socket.send(Message::Text(message)).await {
+                return lost(format!("subscribe failed: {}", err), false);
+            }
+        }
+
+        let mut heartbeat = tokio::time::interval(self.config.heartbeat_interval);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        heartbeat.tick().await;
+        let mut last_seen = Instant::now();
+        let mut received = false;
+
+        loop {
+            tokio::select! {
+                _ = self.tx.closed() => {
+                    let _ = socket.close(None).await;
+                    return SessionEnd::Closed;
+                }
+                _ = heartbeat.tick() => {
+                    if last_seen.elapsed() > self.config.idle_timeout {
+                        return lost("heartbeat timeout".to_string(), received);
+                    }
+                    let ping = match self.protocol.ping_message() {
+                        Some(text) => Message::Text(text),
+                        None => Message::Ping(Vec::new()),
+                    };
+                    if let Err(err) = socket.send(ping).await {
+                        return lost(format!("ping failed: {}", err), received);
+                    }
+                }
+                frame = socket.next() => {
+                    last_seen = Instant::now();
+                    let text = match frame {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => return lost("closed by server".to_string(), received),
+                        Some(Ok(_)) => continue,
+                        Some(Err(err)) => return lost(err.to_string(), received),
+                    };
+
+                    let item = match self.protocol.parse(&text) {
+                        Ok(Some(event)) => match self.publish(event) {
+                            Ok(Some(event)) => Ok(event),
+                            // Withheld by the data-quality checks
+                            Ok(None) => continue,
+                            Err(err) => Err(err),
+                        },
+                        Ok(None) => continue,
+                        Err(err) => Err(err),
+                    };
+                    if item.is_ok() {
+                        received = true;
+                    }
+                    if self.tx.send(item).await.is_err() {
+                        return SessionEnd::Closed;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Normalize the update and publish it, like the event loop does, only if
+    /// it passes the data-quality checks; None when withheld
+    fn publish(&self, event: PriceEvent) -> Result<Option<PriceEvent>> {
+        debug!(exchange = self.protocol.exchange_name(), symbol = %event.symbol, price = %event.price, "Streamed price");
+        let event = PriceValidator::normalize(event)?;
+        let mut monitor = self.monitor.lock().unwrap_or_else(PoisonError::into_inner);
+        monitor.admit(self.protocol.exchange_name(), event)
+    }
+}
+
+/// Local stand-in exchange used by the stream tests
+#[cfg(test)]
+pub(crate) mod test_server {
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// What the server saw on one connection
+    #[derive(Debug)]
+    pub(crate) enum Seen {
+        Connected(usize),
+        Text(usize, String),
+    }
+
+    /// Accept connections forever; on connection `n` send `script(n)` after the
+    /// first client frame, then drop the socket if `hang_up(n)` else keep reading
+    pub(crate) async fn spawn(
+        script: impl Fn(usize) -> Vec<String> + Send + 'static,
+        hang_up: impl Fn(usize) -> bool + Send + 'static,
+    ) -> (String, mpsc::UnboundedReceiver<Seen>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (seen_tx, seen_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut connection = 0;
+            while let Ok((tcp, _)) = listener.accept().await {
+                connection += 1;
+                let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                let _ = seen_tx.send(Seen::Connected(connection));
+                let mut scripted = false;
+                while let Some(Ok(frame)) = socket.next().await {
+                    if let Message::Text(text) = frame {
+                        let _ = seen_tx.send(Seen::Text(connection, text));
+                    }
+                    if !scripted {
+                        scripted = true;
+                        for message in script(connection) {
+                            socket.send(Message::Text(message)).await.unwrap();
+                        }
+                        if hang_up(connection) {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        (url, seen_rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let config = StreamConfig {
+            reconnect_delay: Duration::from_millis(100),
+            max_reconnect_delay: Duration::from_millis(500),
+            ..StreamConfig::new("ws://localhost")
+        };
+        assert_eq!(config.backoff(1), Duration::from_millis(100));
+        assert_eq!(config.backoff(3), Duration::from_millis(400));
+        assert_eq!(config.backoff(10), Duration::from_millis(500));
+    }
+}
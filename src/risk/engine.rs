@@ -1,4 +1,5 @@
 use rust_decimal::Decimal;
+use tracing::{error, warn};
 use crate::error::{Result, TradingError};
 use crate::portfolio::portfolio::Portfolio;
 use crate::portfolio::position::PositionSide;
@@ -46,6 +47,11 @@ impl RiskEngine {
         self.portfolio.open_positions()
     }
 
+    /// Current drop of equity below the account balance
+    pub fn daily_loss(&self) -> Decimal {
+        self.daily_loss
+    }
+
     pub fn is_kill_switch_active(&self) -> bool {
         self.kill_switch
     }
@@ -55,8 +61,10 @@ impl RiskEngine {
     }
 
     pub fn activate_kill_switch(&mut self, reason: impl Into<String>) {
+        let reason = reason.into();
+        error!(%reason, "Kill-switch activated");
         self.kill_switch = true;
-        self.kill_switch_reason = Some(reason.into());
+        self.kill_switch_reason = Some(reason);
     }
 
     pub fn deactivate_kill_switch(&mut self) {
@@ -78,7 +86,6 @@ impl RiskEngine {
         position_size: Decimal,
         stop_loss_distance: Decimal,
     ) -> Result<()> {
-        let _ = symbol;
         if self.kill_switch {
             return Err(TradingError::Risk(
                 "Kill-switch active; trading halted".to_string(),
@@ -99,6 +106,7 @@ impl RiskEngine {
 
         let notional = entry_price * position_size;
         if self.limits.is_position_too_large(notional)? {
+            warn!(symbol, %notional, "Position notional exceeds limit");
             return Err(TradingError::Risk(
                 "Position notional exceeds limit".to_string(),
             ));
@@ -113,6 +121,7 @@ impl RiskEngine {
 
         let used_leverage = (projected_exposure / equity).round_dp(8);
         if self.limits.is_leverage_exceeded(used_leverage)? {
+            warn!(symbol, %used_leverage, "Leverage exceeds limit");
             return Err(TradingError::Risk(
                 "Leverage exceeds limit".to_string(),
             ));
@@ -1,5 +1,6 @@
 use rust_decimal::Decimal;
 use crate::error::{TradingError, Result};
+use crate::instrument::{Instrument, DEFAULT_PRECISION};
 
 /// Calculates position size based on risk management rules
 pub struct PositionSizer;
@@ -15,6 +16,39 @@ impl PositionSizer {
         account_balance: Decimal,
         risk_percentage: Decimal,
         stop_loss_distance: Decimal,
+    ) -> Result<Decimal> {
+        Ok(Self::risk_size(account_balance, risk_percentage, stop_loss_distance)?.round_dp(DEFAULT_PRECISION))
+    }
+
+    /// `calculate` for a listed instrument: the size is rounded down to the
+    /// quantity step, capped at the maximum quantity and checked against the
+    /// minimum quantity and the minimum notional at `entry_price`
+    pub fn calculate_for(
+        instrument: &Instrument,
+        account_balance: Decimal,
+        risk_percentage: Decimal,
+        stop_loss_distance: Decimal,
+        entry_price: Decimal,
+    ) -> Result<Decimal> {
+        let risk_size = Self::risk_size(account_balance, risk_percentage, stop_loss_distance)?;
+        let mut size = instrument.round_qty(risk_size);
+        if let Some(max_qty) = instrument.max_qty {
+            size = size.min(instrument.round_qty(max_qty));
+        }
+        if size.is_zero() || size < instrument.min_qty {
+            return Err(TradingError::Validation(format!(
+                "Risk budget sizes {} at {}, below the minimum quantity {}",
+                instrument.symbol(), risk_size.round_dp(DEFAULT_PRECISION), instrument.min_qty
+            )));
+        }
+        instrument.validate_notional(size, entry_price)?;
+        Ok(size)
+    }
+
+    fn risk_size(
+        account_balance: Decimal,
+        risk_percentage: Decimal,
+        stop_loss_distance: Decimal,
     ) -> Result<Decimal> {
         // Validate inputs
         if account_balance <= Decimal::ZERO {
@@ -39,9 +73,7 @@ impl PositionSizer {
         let risk_amount = account_balance * (risk_percentage / Decimal::from(100));
 
         // Calculate position size
-        let position_size = risk_amount / stop_loss_distance;
-
-        Ok(position_size.round_dp(8))
+        Ok(risk_amount / stop_loss_distance)
     }
 
     /// Calculate max position size as percentage of account
@@ -63,6 +95,6 @@ impl PositionSizer {
         }
 
         let max_size = account_balance * (max_position_percentage / Decimal::from(100));
-        Ok(max_size.round_dp(8))
+        Ok(max_size.round_dp(DEFAULT_PRECISION))
     }
 }
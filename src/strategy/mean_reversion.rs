@@ -1,4 +1,5 @@
 use rust_decimal::Decimal;
+use tracing::debug;
 use crate::market_data::event::PriceEvent;
 use crate::error::{Result, TradingError};
 use super::strategy::{Strategy, Signal};
@@ -69,6 +70,7 @@ impl Strategy for MeanReversionStrategy {
         };
 
         let deviation = self.calculate_deviation(event.price, mean);
+        debug!(strategy = %self.name, symbol = %event.symbol, price = %event.price, %mean, %deviation, "Evaluated mean reversion");
 
         // Buy if price is below mean by threshold
         if event.price < mean && deviation > self.threshold {
@@ -94,13 +96,21 @@ impl Strategy for MeanReversionStrategy {
 
         // Stop loss is 2% below entry for simplicity
         let stop_loss_distance = entry_price * Decimal::from_str_exact("0.02")
-            .map_err(|e| TradingError::Decimal(e))?;
+            .map_err(TradingError::Decimal)?;
 
         // Position size based on 2% risk
         let position_size = entry_price; // Simplified: 1 unit at current price
 
         Ok((entry_price, stop_loss_distance, position_size))
     }
+
+    fn update(&mut self, event: &PriceEvent) -> Result<()> {
+        if self.prices.len() == self.window_size {
+            self.prices.remove(0);
+        }
+        self.prices.push(event.price);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -126,4 +136,22 @@ mod tests {
         );
         assert!(strategy.is_err());
     }
+
+    #[test]
+    fn test_update_rolls_window() {
+        let mut strategy = MeanReversionStrategy::new(
+            Decimal::new(2, 2),
+            2,
+            Decimal::from(2),
+        ).unwrap();
+
+        for price in [100, 100, 100] {
+            let event = PriceEvent::new("BTCUSDT".to_string(), Decimal::from(price), Decimal::ONE).unwrap();
+            strategy.update(&event).unwrap();
+        }
+        assert_eq!(strategy.prices.len(), 2);
+
+        let low = PriceEvent::new("BTCUSDT".to_string(), Decimal::from(90), Decimal::ONE).unwrap();
+        assert_eq!(strategy.signal(&low).unwrap(), Signal::Buy);
+    }
 }
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod strategy;
 pub mod mean_reversion;
 pub mod strategy_factory;
@@ -1,9 +1,11 @@
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::market_data::candle::Candle;
 use crate::market_data::event::PriceEvent;
 use crate::error::Result;
 
 /// Signal generated by a strategy
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Signal {
     Buy,
     Sell,
@@ -20,4 +22,21 @@ pub trait Strategy: Send + Sync {
 
     /// Get risk parameters (entry price, stop loss distance, position size)
     fn get_risk_params(&self, current_price: Decimal) -> Result<(Decimal, Decimal, Decimal)>;
+
+    /// Feed a processed price event into the strategy's internal state
+    fn update(&mut self, _event: &PriceEvent) -> Result<()> {
+        Ok(())
+    }
+
+    /// Prime internal state from historical bars, oldest first.
+    /// Each close is fed through `update` stamped with the bar's close time.
+    fn warm_up(&mut self, candles: &[Candle]) -> Result<()> {
+        for candle in candles {
+            let mut event = PriceEvent::new(candle.symbol.clone(), candle.close, candle.volume)?;
+            event.timestamp = candle.close_time();
+            event.received_at = candle.close_time();
+            self.update(&event)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,68 @@
+use std::str::FromStr;
+use tracing_subscriber::EnvFilter;
+use crate::config::EnvConfig;
+use crate::error::{Result, TradingError};
+
+/// Level used when `RUST_LOG` is not set
+const DEFAULT_FILTER: &str = "info";
+
+/// Output format of log records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines for terminals
+    #[default]
+    Pretty,
+    /// One JSON object per record, with span fields, for log shippers
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => Err(TradingError::Config(format!("Unknown log format: {}", other))),
+        }
+    }
+}
+
+/// Install the global tracing subscriber. Levels are filtered with `RUST_LOG`
+/// directives (e.g. `info,event_trading::execution=debug`), defaulting to `info`.
+pub fn init(format: LogFormat) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let installed = match format {
+        LogFormat::Pretty => builder.try_init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .try_init(),
+    };
+
+    installed.map_err(|e| TradingError::Config(format!("Cannot install logger: {}", e)))
+}
+
+/// `init` with the format taken from `LOG_FORMAT` (pretty when unset)
+pub fn init_from_env() -> Result<()> {
+    let format = match EnvConfig::get_log_format() {
+        Some(format) => format.parse()?,
+        None => LogFormat::default(),
+    };
+    init(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
@@ -0,0 +1,8 @@
+// Metrics export and logging
+pub mod logging;
+pub mod prometheus;
+pub mod server;
+
+pub use logging::LogFormat;
+pub use prometheus::MetricsRegistry;
+pub use server::MetricsServer;
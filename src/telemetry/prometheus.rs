@@ -0,0 +1,142 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use crate::engine::EventBus;
+use crate::execution::ExecutionMetrics;
+
+/// Collects bus and execution metrics and renders them in Prometheus text format
+#[derive(Clone)]
+pub struct MetricsRegistry {
+    event_bus: EventBus,
+    execution: Arc<Mutex<Option<ExecutionMetrics>>>,
+}
+
+impl MetricsRegistry {
+    pub fn new(event_bus: EventBus) -> Self {
+        Self {
+            event_bus,
+            execution: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Replace the execution snapshot served on the next scrape
+    pub fn update_execution(&self, metrics: ExecutionMetrics) {
+        if let Ok(mut slot) = self.execution.lock() {
+            *slot = Some(metrics);
+        }
+    }
+
+    /// Render every metric in Prometheus text exposition format (version 0.0.4)
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.render_bus(&mut out);
+        let execution = self.execution.lock().ok().and_then(|slot| slot.clone());
+        if let Some(execution) = execution {
+            render_execution(&mut out, &execution);
+        }
+        out
+    }
+
+    fn render_bus(&self, out: &mut String) {
+        let mut counts: Vec<(String, u64)> = self.event_bus.metrics_snapshot().into_iter().collect();
+        counts.sort();
+        header(out, "trading_events_published_total", "Events published on the bus by type", "counter");
+        for (event_type, count) in &counts {
+            let _ = writeln!(out, "trading_events_published_total{{type=\"{}\"}} {}", event_type, count);
+        }
+
+        let metrics = self.event_bus.metrics();
+        header(out, "trading_event_handler_seconds", "Handler execution time by event type", "summary");
+        for (event_type, stats) in &metrics.event_types {
+            let latency = &stats.handler_latency;
+            for (quantile, value) in [("0.5", latency.p50), ("0.99", latency.p99)] {
+                let _ = writeln!(
+                    out,
+                    "trading_event_handler_seconds{{type=\"{}\",quantile=\"{}\"}} {}",
+                    event_type, quantile, value.as_secs_f64()
+                );
+            }
+            let _ = writeln!(
+                out,
+                "trading_event_handler_seconds_sum{{type=\"{}\"}} {}",
+                event_type, latency.sum.as_secs_f64()
+            );
+            let _ = writeln!(out, "trading_event_handler_seconds_count{{type=\"{}\"}} {}", event_type, latency.count);
+        }
+
+        let feed = &metrics.feed_latency;
+        header(out, "trading_feed_latency_seconds", "Exchange event time to local receipt of price updates", "summary");
+        for (quantile, value) in [("0.5", feed.p50), ("0.99", feed.p99)] {
+            let _ = writeln!(out, "trading_feed_latency_seconds{{quantile=\"{}\"}} {}", quantile, value.as_secs_f64());
+        }
+        let _ = writeln!(out, "trading_feed_latency_seconds_sum {}", feed.sum.as_secs_f64());
+        let _ = writeln!(out, "trading_feed_latency_seconds_count {}", feed.count);
+
+        gauge(out, "trading_event_queue_depth", "Events queued for async subscribers", self.event_bus.pending_events());
+        counter(out, "trading_events_dropped_total", "Events discarded by subscriber overflow policies", self.event_bus.dropped_events());
+        gauge(out, "trading_dead_letters", "Events whose handler panicked", self.event_bus.dead_letters().len());
+    }
+}
+
+fn render_execution(out: &mut String, metrics: &ExecutionMetrics) {
+    header(out, "trading_orders", "Orders by status", "gauge");
+    for (status, count) in &metrics.orders_by_status {
+        let _ = writeln!(out, "trading_orders{{status=\"{}\"}} {}", status.as_str(), count);
+    }
+    counter(out, "trading_fills_total", "Fills received", metrics.fills);
+    counter(out, "trading_trades_total", "Trades opened", metrics.trades);
+    gauge(out, "trading_balance", "Account balance", metrics.balance);
+    gauge(out, "trading_equity", "Balance plus unrealized PnL", metrics.equity);
+    gauge(out, "trading_open_positions", "Open positions", metrics.open_positions);
+    gauge(out, "trading_daily_loss", "Current drop of equity below balance", metrics.daily_loss);
+    gauge(out, "trading_kill_switch_active", "1 when the risk kill-switch is active", u8::from(metrics.kill_switch_active));
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
+    header(out, name, help, "gauge");
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
+    header(out, name, help, "counter");
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use crate::engine::Event;
+    use crate::execution::order::OrderStatus;
+
+    #[test]
+    fn test_render_includes_bus_and_execution() {
+        let bus = EventBus::new();
+        bus.publish(Event::Error("x".to_string())).unwrap();
+        let registry = MetricsRegistry::new(bus);
+        assert!(!registry.render().contains("trading_balance"));
+
+        registry.update_execution(ExecutionMetrics {
+            orders_by_status: vec![(OrderStatus::Filled, 2), (OrderStatus::Rejected, 1)],
+            fills: 3,
+            trades: 2,
+            balance: Decimal::from(10000),
+            equity: Decimal::new(99505, 1),
+            open_positions: 1,
+            daily_loss: Decimal::new(495, 1),
+            kill_switch_active: true,
+        });
+
+        let text = registry.render();
+        assert!(text.contains("trading_events_published_total{type=\"Error\"} 1"));
+        assert!(text.contains("trading_orders{status=\"filled\"} 2"));
+        assert!(text.contains("trading_fills_total 3"));
+        assert!(text.contains("trading_equity 9950.5"));
+        assert!(text.contains("trading_daily_loss 49.5"));
+        assert!(text.contains("trading_kill_switch_active 1"));
+    }
+}
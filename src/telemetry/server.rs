@@ -0,0 +1,115 @@
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use crate::error::{Result, TradingError};
+use super::prometheus::MetricsRegistry;
+
+/// Largest request head accepted before the connection is dropped
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Minimal HTTP server exposing `GET /metrics` for Prometheus scrapers
+pub struct MetricsServer {
+    listener: TcpListener,
+    registry: MetricsRegistry,
+}
+
+impl MetricsServer {
+    /// Bind to `addr` (e.g. "0.0.0.0:9100"; port 0 picks a free port)
+    pub async fn bind(addr: &str, registry: MetricsRegistry) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await.map_err(|e| {
+            TradingError::Config(format!("Cannot bind metrics endpoint to {}: {}", addr, e))
+        })?;
+        Ok(Self { listener, registry })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serve scrapes on a background task until the runtime shuts down
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.serve())
+    }
+
+    pub async fn serve(self) {
+        loop {
+            let (stream, _) = match self.listener.accept().await {
+                Ok(conn) => conn,
+                Err(_) => continue,
+            };
+            let registry = self.registry.clone();
+            tokio::spawn(async move {
+                let _ = handle_connection(stream, &registry).await;
+            });
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, registry: &MetricsRegistry) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 || request.len() + n > MAX_REQUEST_BYTES {
+            return Ok(());
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let (status, body) = match (method, path.split('?').next().unwrap_or_default()) {
+        ("GET", "/metrics") => ("200 OK", registry.render()),
+        ("GET", _) => ("404 Not Found", "Not Found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method Not Allowed\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        CONTENT_TYPE,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Event, EventBus};
+
+    async fn start(bus: EventBus) -> String {
+        let server = MetricsServer::bind("127.0.0.1:0", MetricsRegistry::new(bus)).await.unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn();
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics() {
+        let bus = EventBus::new();
+        bus.publish(Event::RiskHalt { reason: "test".to_string() }).unwrap();
+        let base = start(bus).await;
+
+        let response = reqwest::get(format!("{}/metrics", base)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+        let body = response.text().await.unwrap();
+        assert!(body.contains("trading_events_published_total{type=\"RiskHalt\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path_is_404() {
+        let base = start(EventBus::new()).await;
+        let response = reqwest::get(format!("{}/health", base)).await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+}
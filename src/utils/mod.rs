@@ -1 +1 @@
-pub mod clock;
\ No newline at end of file
+pub mod clock;
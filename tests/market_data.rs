@@ -0,0 +1,419 @@
+//! Offline integration tests for the REST fetchers, run against the bundled mock exchange
+
+use event_trading::config::exchange_config::{ExchangeConfig, ExchangeType};
+use event_trading::engine::EventBus;
+use event_trading::market_data::mock_exchange::{EMPTY_SYMBOL, MALFORMED_SYMBOL, RATE_LIMITED_SYMBOL};
+use event_trading::market_data::{
+    BinanceFetcher, BybitFetcher, ExchangeFactory, MarketDataFetcher, MockExchange, MockResponse,
+    AggressorSide, CandleInterval, ConsensusConfig, ConsensusFetcher, GuardConfig, GuardedFetcher, HistoricalData, HistoricalFetcher, KlineCache,
+    InstrumentFetcher, OrderBook, OrderBookFetcher, RateLimiter, ResilientFetcher, RetryPolicy, TradeFetcher,
+};
+use event_trading::instrument::{Instrument, InstrumentId, InstrumentRegistry};
+use std::sync::Arc;
+use std::time::Duration;
+use event_trading::TradingError;
+use reqwest::Client;
+use rust_decimal::Decimal;
+
+fn binance(mock: &MockExchange, bus: &EventBus) -> BinanceFetcher {
+    BinanceFetcher::with_client(bus.clone(), Client::new(), mock.binance_url())
+}
+
+fn bybit(mock: &MockExchange, bus: &EventBus) -> BybitFetcher {
+    BybitFetcher::with_client(bus.clone(), Client::new(), mock.bybit_url())
+}
+
+#[tokio::test]
+async fn test_fetches_canned_tickers() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    let event = binance(&mock, &bus).fetch_price("BTCUSDT").await.unwrap();
+    assert_eq!(event.symbol, "BTC-USDT");
+    assert_eq!(event.price, Decimal::new(5000000, 2));
+    assert_eq!(event.timestamp, 1700000000000);
+    assert!(event.received_at > event.timestamp);
+    assert_eq!(event.quote.unwrap().ask, Decimal::new(5000100, 2));
+
+    // A bare asset is quoted in USDT; both venues return the canonical symbol
+    let event = bybit(&mock, &bus).fetch_price("ETH").await.unwrap();
+    assert_eq!(event.symbol, "ETH-USDT");
+    assert_eq!(event.price, Decimal::new(300100, 2));
+    assert_eq!(event.spread(), Some(Decimal::new(20, 2)));
+
+    // Prices are published by the event loop once they pass the data-quality checks
+    assert_eq!(bus.metrics_snapshot().get("PriceUpdated"), None);
+    assert_eq!(
+        mock.requests(),
+        vec![
+            "/api/v3/ticker/24hr?symbol=BTCUSDT".to_string(),
+            "/v5/market/tickers?category=spot&symbol=ETHUSDT".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit_is_reported() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    for result in [
+        binance(&mock, &bus).fetch_price(RATE_LIMITED_SYMBOL).await,
+        bybit(&mock, &bus).fetch_price(RATE_LIMITED_SYMBOL).await,
+    ] {
+        match result {
+            Err(TradingError::RateLimited(msg)) => assert!(msg.contains("retry after 1s"), "{}", msg),
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_json_is_an_error() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    for result in [
+        binance(&mock, &bus).fetch_price(MALFORMED_SYMBOL).await,
+        bybit(&mock, &bus).fetch_price(MALFORMED_SYMBOL).await,
+    ] {
+        match result {
+            Err(TradingError::MarketData(msg)) => assert!(msg.contains("JSON parse error"), "{}", msg),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+    assert_eq!(bus.metrics_snapshot().get("PriceUpdated"), None);
+}
+
+#[tokio::test]
+async fn test_exchange_errors() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    let err = bybit(&mock, &bus).fetch_price(EMPTY_SYMBOL).await.unwrap_err();
+    assert!(err.to_string().contains("No ticker data from Bybit"));
+
+    // Bybit reports bad requests in the body with HTTP 200
+    let err = bybit(&mock, &bus).fetch_price("NOPEUSDT").await.unwrap_err();
+    assert!(err.to_string().contains("Bybit error 10001"));
+
+    match binance(&mock, &bus).fetch_price("NOPEUSDT").await {
+        Err(TradingError::Http(400, body)) => assert!(body.contains("Invalid symbol")),
+        other => panic!("expected HTTP 400, got {:?}", other),
+    }
+
+    mock.route("/api/v3/ticker/24hr?symbol=BTCUSDT", MockResponse::with_status(503, ""));
+    match binance(&mock, &bus).fetch_price("BTCUSDT").await {
+        Err(TradingError::Http(503, _)) => {}
+        other => panic!("expected HTTP 503, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_resilient_fetcher_fails_over() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+    mock.route("/api/v3/ticker/24hr", MockResponse::with_status(500, "boom"));
+
+    let fetcher = ResilientFetcher::new(
+        Box::new(binance(&mock, &bus)),
+        Box::new(bybit(&mock, &bus)),
+        bus.clone(),
+    ).unwrap();
+    let event = fetcher.fetch_price("BTCUSDT").await.unwrap();
+    assert_eq!(event.price, Decimal::new(5001000, 2));
+
+    let counts = bus.metrics_snapshot();
+    assert_eq!(counts.get("FeedFailover"), Some(&1));
+}
+
+#[tokio::test]
+async fn test_batch_tickers_keep_symbol_errors_apart() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+    let symbols = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let batch = binance(&mock, &bus).fetch_prices(&symbols(&["BTCUSDT", "ETHUSDT"])).await.unwrap();
+    assert_eq!(batch[1].1.as_ref().unwrap().price, Decimal::new(300000, 2));
+    // An unknown symbol fails the batch request, so each symbol is retried alone
+    let batch = binance(&mock, &bus).fetch_prices(&symbols(&["BTCUSDT", "NOPEUSDT"])).await.unwrap();
+    assert!(batch[0].1.is_ok());
+    assert!(matches!(batch[1].1, Err(TradingError::Http(400, _))));
+
+    let batch = bybit(&mock, &bus)
+        .fetch_prices(&symbols(&["BTCUSDT", "ETH", MALFORMED_SYMBOL, "NOPEUSDT"]))
+        .await
+        .unwrap();
+    assert_eq!(batch[1].0, "ETH");
+    assert_eq!(batch[1].1.as_ref().unwrap().symbol, "ETH-USDT");
+    assert_eq!(batch[1].1.as_ref().unwrap().price, Decimal::new(300100, 2));
+    assert!(matches!(batch[2].1, Err(TradingError::Decimal(_))));
+    assert!(batch[3].1.as_ref().unwrap_err().to_string().contains("No Bybit ticker for NOPEUSDT"));
+    assert_eq!(
+        mock.requests().iter().filter(|target| target.starts_with("/v5/market/tickers")).count(),
+        1
+    );
+
+    // Transport failures fail the batch; the resilient fetcher moves the whole batch on
+    mock.route("/api/v3/ticker/24hr", MockResponse::with_status(500, "boom"));
+    let fetcher = ResilientFetcher::new(Box::new(binance(&mock, &bus)), Box::new(bybit(&mock, &bus)), bus.clone()).unwrap();
+    let batch = fetcher.fetch_prices(&symbols(&["BTCUSDT", "ETHUSDT"])).await.unwrap();
+    assert_eq!(batch[0].1.as_ref().unwrap().price, Decimal::new(5001000, 2));
+    assert_eq!(bus.metrics_snapshot().get("FeedFailover"), Some(&2));
+}
+
+#[tokio::test]
+async fn test_consensus_across_exchanges() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    let fetcher = ConsensusFetcher::new(
+        vec![Box::new(binance(&mock, &bus)), Box::new(bybit(&mock, &bus))],
+        ConsensusConfig::default(),
+        bus.clone(),
+    )
+    .unwrap();
+    let event = fetcher.fetch_price("BTCUSDT").await.unwrap();
+    assert_eq!(event.price, Decimal::new(5000500, 2));
+
+    let counts = bus.metrics_snapshot();
+    assert_eq!(counts.get("PriceDivergence"), Some(&1));
+    assert_eq!(counts.get("PriceUpdated"), None);
+
+    // A venue printing 10% away leaves too few quotes for a consensus
+    mock.route(
+        "/api/v3/ticker/24hr?symbol=BTCUSDT",
+        MockResponse::json(r#"{"symbol":"BTCUSDT","lastPrice":"55000.00","volume":"1","closeTime":1700000000000}"#),
+    );
+    let err = fetcher.fetch_price("BTCUSDT").await.unwrap_err();
+    assert!(err.to_string().contains("No consensus for BTC-USDT"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_factory_uses_configured_base_url() {
+    let mock = MockExchange::start().await.unwrap();
+    let config = ExchangeConfig {
+        exchange_type: ExchangeType::Bybit,
+        api_key: None,
+        api_secret: None,
+        enabled: true,
+        base_url: Some(mock.base_url(&ExchangeType::Bybit)),
+        ws_url: None,
+        symbols: None,
+    };
+    config.validate().unwrap();
+
+    let fetcher = ExchangeFactory::create_fetcher(&config, EventBus::new()).unwrap();
+    let event = fetcher.fetch_price("BTCUSDT").await.unwrap();
+    assert_eq!(event.price, Decimal::new(5001000, 2));
+    assert_eq!(mock.requests().len(), 1);
+
+    // Failover and consensus sources honor the same overrides
+    let binance_config = ExchangeConfig {
+        exchange_type: ExchangeType::Binance,
+        base_url: Some(mock.binance_url()),
+        ..config.clone()
+    };
+    let configs = [config, binance_config];
+    let fetcher = ExchangeFactory::create_resilient_fetcher(&configs[0], &configs[1], EventBus::new()).unwrap();
+    assert_eq!(fetcher.fetch_price("BTCUSDT").await.unwrap().price, Decimal::new(5001000, 2));
+    assert_eq!(mock.requests().len(), 2);
+
+    let consensus = ConsensusConfig::new("median".parse().unwrap());
+    let fetcher = ExchangeFactory::create_consensus_fetcher(&configs, consensus, EventBus::new()).unwrap();
+    assert_eq!(fetcher.fetch_price("BTCUSDT").await.unwrap().price, Decimal::new(5000500, 2));
+    assert_eq!(mock.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn test_factory_uses_listed_native_symbols() {
+    let mock = MockExchange::start().await.unwrap();
+    let sats = InstrumentId::spot("SATS", "USDT", ExchangeType::Binance);
+    let instruments = InstrumentRegistry::from_instruments(vec![Instrument::new(sats, "1000SATSUSDT")]).unwrap();
+    let config = ExchangeConfig {
+        exchange_type: ExchangeType::Binance,
+        api_key: None,
+        api_secret: None,
+        enabled: true,
+        base_url: Some(mock.binance_url()),
+        ws_url: None,
+        symbols: Some(Arc::new(instruments.symbol_registry())),
+    };
+
+    let fetcher = ExchangeFactory::create_fetcher(&config, EventBus::new()).unwrap();
+    let _ = fetcher.fetch_price("SATS-USDT").await;
+    assert_eq!(mock.requests()[0], "/api/v3/ticker/24hr?symbol=1000SATSUSDT");
+}
+
+#[tokio::test]
+async fn test_fetches_order_books() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    let update = binance(&mock, &bus).fetch_order_book("BTCUSDT", 5).await.unwrap();
+    assert_eq!(update.last_update_id, 1027024);
+    let mut book = OrderBook::new("BTC-USDT");
+    book.apply(&update).unwrap();
+    assert_eq!(book.best_bid().unwrap().price, Decimal::new(4999900, 2));
+    assert_eq!(book.spread(), Some(Decimal::from(2)));
+
+    let config = ExchangeConfig {
+        exchange_type: ExchangeType::Bybit,
+        api_key: None,
+        api_secret: None,
+        enabled: true,
+        base_url: Some(mock.bybit_url()),
+        ws_url: None,
+        symbols: None,
+    };
+    let fetcher = ExchangeFactory::create_book_fetcher(&config, bus.clone()).unwrap();
+    let update = fetcher.fetch_order_book("BTC", 50).await.unwrap();
+    assert_eq!(update.symbol, "BTC-USDT");
+    assert_eq!(update.asks[0].quantity, Decimal::new(600, 3));
+
+    assert_eq!(bus.metrics_snapshot().get("OrderBookUpdated"), Some(&2));
+    assert_eq!(
+        mock.requests(),
+        vec![
+            "/api/v3/depth?symbol=BTCUSDT&limit=5".to_string(),
+            "/v5/market/orderbook?category=spot&symbol=BTCUSDT&limit=50".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_fetches_instrument_rules() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    // Halted symbols and invalid rules (zero tick) are left out; MIN_NOTIONAL is read like NOTIONAL
+    let listing = binance(&mock, &bus).fetch_instruments(&[]).await.unwrap();
+    let registry = InstrumentRegistry::from_instruments(listing).unwrap();
+    assert_eq!(registry.len(), 2);
+    let btc = registry.get("BTC-USDT").unwrap();
+    assert_eq!((btc.price_tick, btc.qty_step), (Decimal::new(1, 2), Decimal::new(1, 5)));
+    assert_eq!(btc.max_qty, Some(Decimal::from(9000)));
+    assert_eq!(registry.get("ETHUSDT").unwrap().min_notional, Decimal::from(5));
+
+    let listing = bybit(&mock, &bus).fetch_instruments(&["ETH".to_string()]).await.unwrap();
+    assert_eq!(listing.len(), 1);
+    let eth = &listing[0];
+    assert_eq!(eth.symbol(), "ETH-USDT");
+    assert_eq!((eth.qty_step, eth.min_qty, eth.min_notional), (Decimal::new(1, 5), Decimal::new(62, 5), Decimal::ONE));
+    assert_eq!(eth.round_qty(Decimal::new(123456789, 8)), Decimal::new(123456, 5));
+    assert_eq!(
+        mock.requests(),
+        vec![
+            "/api/v3/exchangeInfo?permissions=SPOT".to_string(),
+            "/v5/market/instruments-info?category=spot".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_fetches_trade_tape() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+
+    let fetcher = binance(&mock, &bus);
+    let trades = fetcher.fetch_trades("BTCUSDT", 2).await.unwrap();
+    assert_eq!(trades.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![28457, 28458]);
+    // Polling the same tape again publishes nothing twice
+    assert!(fetcher.fetch_trades("BTCUSDT", 2).await.unwrap().is_empty());
+    assert_eq!(trades[0].side, AggressorSide::Sell);
+    assert_eq!(trades[1].side, AggressorSide::Buy);
+    assert_eq!(trades[1].timestamp, 1700000000200);
+
+    // Bybit returns newest first; the fetcher reorders oldest first
+    let trades = bybit(&mock, &bus).fetch_trades("BTCUSDT", 2).await.unwrap();
+    assert_eq!(trades[0].trade_id, 2290000000061656298);
+    assert_eq!(trades[0].side, AggressorSide::Buy);
+    assert_eq!(trades[1].price, Decimal::new(5001050, 2));
+
+    assert_eq!(bus.metrics_snapshot().get("TradePrinted"), Some(&4));
+}
+
+#[tokio::test]
+async fn test_fetches_historical_klines() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+    let (start, end) = (1699999980000, 1700000160000);
+
+    let candles = binance(&mock, &bus).fetch_klines("BTCUSDT", CandleInterval::M1, start, end).await.unwrap();
+    assert_eq!(candles.len(), 3);
+    assert_eq!(candles[0].open_time, start);
+    assert_eq!(candles[2].close, Decimal::new(5025000, 2));
+    assert_eq!(candles[1].trades, 120);
+
+    // Bybit pages newest first; the result is still oldest first
+    let candles = bybit(&mock, &bus).fetch_klines("BTC", CandleInterval::M1, start, end).await.unwrap();
+    let opens: Vec<u64> = candles.iter().map(|candle| candle.open_time).collect();
+    assert_eq!(opens, vec![start, start + 60_000, start + 120_000]);
+    assert_eq!(candles[1].volume, Decimal::new(120, 1));
+
+    assert!(matches!(
+        bybit(&mock, &bus).fetch_klines("BTC", CandleInterval::S1, start, end).await,
+        Err(TradingError::Config(_))
+    ));
+    let requests = mock.requests();
+    assert!(requests.iter().any(|r| r.contains("interval=1m&startTime=1699999980000&endTime=1700000159999")));
+    assert!(requests.iter().any(|r| r.contains("interval=1&start=1699999980000&end=1700000159999")));
+    // Historical data is not published on the bus
+    assert!(bus.metrics_snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn test_historical_data_is_cached() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+    let dir = std::env::temp_dir().join(format!("kline-cache-it-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let history = HistoricalData::new(Box::new(binance(&mock, &bus)), GuardConfig::default(), bus.clone())
+        .with_cache(KlineCache::new(&dir));
+    let (start, end) = (1699999980000, 1700000160000);
+
+    let first = history.klines("BTCUSDT", CandleInterval::M1, start, end).await.unwrap();
+    let second = history.klines("BTCUSDT", CandleInterval::M1, start + 60_000, end).await.unwrap();
+    assert_eq!(first.len(), 3);
+    assert_eq!(second, first[1..].to_vec());
+    assert_eq!(mock.requests().len(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_guarded_fetcher_rides_out_transient_errors() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+    let target = "/api/v3/ticker/24hr?symbol=BTCUSDT";
+    mock.route_times(target, MockResponse::with_status(503, "Service Unavailable"), 2);
+
+    let config = GuardConfig {
+        retry: RetryPolicy { base_delay: Duration::from_millis(5), ..RetryPolicy::default() },
+        ..GuardConfig::default()
+    };
+    let fetcher = GuardedFetcher::new(Box::new(binance(&mock, &bus)), config, bus.clone());
+    let event = fetcher.fetch_price("BTCUSDT").await.unwrap();
+    assert_eq!(event.price, Decimal::new(5000000, 2));
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_follows_weight_headers() {
+    let mock = MockExchange::start().await.unwrap();
+    let bus = EventBus::new();
+    let limiter = Arc::new(RateLimiter::new(6000, Duration::from_secs(60)));
+    let fetcher = binance(&mock, &bus).with_rate_limiter(Arc::clone(&limiter));
+
+    mock.route(
+        "/api/v3/ticker/24hr?symbol=ETHUSDT",
+        MockResponse::json(r#"{"symbol":"ETHUSDT","lastPrice":"3000.00","volume":"1.0"}"#)
+            .header("x-mbx-used-weight-1m", "5900"),
+    );
+    fetcher.fetch_price("ETHUSDT").await.unwrap();
+    assert!((100..=101).contains(&limiter.available()));
+
+    // 429 pauses every request until Retry-After has passed
+    assert!(fetcher.fetch_price(RATE_LIMITED_SYMBOL).await.is_err());
+    assert!(limiter.try_acquire(1).is_err());
+}